@@ -4,8 +4,15 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-use crate::constants::{AGENTS_CONTENTS, AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME};
-use crate::symlinks::ensure_rules_directory_exists;
+use crate::config::{load_project_config, save_project_config, LegacyCleanup, LegacyImport};
+use crate::constants::{
+    AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME, PROJECT_CONFIG_FILENAME, RULES_DIR,
+};
+use crate::legacy::{find_legacy_rule_files, import_legacy_rule_files, ImportedLegacyFile};
+use crate::merge::{merge_instruction_files, MergeStrategy};
+use crate::repair::backup_file;
+use crate::templates::render_agents_template;
+use crate::transaction::Transaction;
 
 /// Represents the status of agent-related files found in a directory.
 struct AgentFileStatus {
@@ -64,46 +71,90 @@ fn scan_directory_for_agent_files<P: AsRef<Path>>(dir: P) -> io::Result<AgentFil
 /// Creates an AGENTS.md file based on the existing agent files found in the directory.
 ///
 /// This function handles the different scenarios for creating AGENTS.md:
-/// - If both CLAUDE.md and GEMINI.md exist, creates an empty AGENTS.md
+/// - If both CLAUDE.md and GEMINI.md exist, backs them up and combines them as
+///   chosen by `strategy` (merged by heading by default, or an empty AGENTS.md)
 /// - If only CLAUDE.md exists, renames it to AGENTS.md
 /// - If only GEMINI.md exists, renames it to AGENTS.md  
 /// - If neither exists, creates AGENTS.md with default content
 ///
 /// # Arguments
 ///
-/// * `dir` - The directory path where AGENTS.md should be created
+/// * `tx` - The transaction recording the changes to the project directory
 /// * `file_status` - The status of existing agent files in the directory
+/// * `strategy` - How CLAUDE.md and GEMINI.md are combined when both exist
+/// * `template` - Template for a new AGENTS.md, detected from the project if `None`
 ///
 /// # Errors
 ///
-/// Returns an error if file operations (rename/write) fail.
+/// Returns an error if file operations (read/rename/write/backup) fail, or if
+/// the template doesn't exist.
 ///
-fn create_agents_file_based_on_existing_files<P: AsRef<Path>>(
-    dir: P,
+fn create_agents_file_based_on_existing_files(
+    tx: &mut Transaction,
     file_status: AgentFileStatus,
+    strategy: MergeStrategy,
+    template: Option<&str>,
 ) -> io::Result<()> {
-    let dir = dir.as_ref();
-    let agents_path = dir.join(AGENTS_FILENAME);
+    let agents_path = tx.project_dir().join(AGENTS_FILENAME);
 
     match (file_status.claude_path, file_status.gemini_path) {
-        (Some(_), Some(_)) => {
+        (Some(_), Some(_)) if strategy == MergeStrategy::Empty => {
             // Both CLAUDE.md and GEMINI.md exist
-            fs::write(agents_path, "")?;
+            tx.write(&agents_path, "")?;
             println!("Found both CLAUDE.md and GEMINI.md files in the directory.");
             println!("An empty AGENTS.md file has been created.");
             println!("Please manually copy the content from CLAUDE.md and GEMINI.md into AGENTS.md as needed.");
         }
+        (Some(claude_file), Some(gemini_file)) => {
+            // Both CLAUDE.md and GEMINI.md exist: keep copies before combining them
+            let claude = fs::read_to_string(&claude_file)?;
+            let gemini = fs::read_to_string(&gemini_file)?;
+            let dir = tx.project_dir().to_path_buf();
+            backup_file(&dir, CLAUDE_FILENAME, claude.as_bytes())?;
+            backup_file(&dir, GEMINI_FILENAME, gemini.as_bytes())?;
+
+            println!("Found both CLAUDE.md and GEMINI.md files in the directory.");
+            match strategy {
+                MergeStrategy::PreferClaude => {
+                    tx.write(&agents_path, &claude)?;
+                    println!("AGENTS.md has been created from CLAUDE.md.");
+                }
+                MergeStrategy::PreferGemini => {
+                    tx.write(&agents_path, &gemini)?;
+                    println!("AGENTS.md has been created from GEMINI.md.");
+                }
+                _ => {
+                    let merged = merge_instruction_files(&claude, &gemini);
+                    tx.write(&agents_path, &merged.content)?;
+                    println!("Both files have been merged into AGENTS.md.");
+                    if merged.conflicts > 0 {
+                        println!(
+                            "{} section(s) differ between the files and are marked with <<<<<<< and >>>>>>>. Please resolve them in AGENTS.md.",
+                            merged.conflicts
+                        );
+                    }
+                }
+            }
+            println!("Backups of both files have been saved to .known/backups.");
+        }
         (Some(claude_file), None) => {
             // Only CLAUDE.md exists
-            fs::rename(claude_file, agents_path)?;
+            tx.move_path(&claude_file, &agents_path)?;
         }
         (None, Some(gemini_file)) => {
             // Only GEMINI.md exists
-            fs::rename(gemini_file, agents_path)?;
+            tx.move_path(&gemini_file, &agents_path)?;
         }
         (None, None) => {
-            // Neither exists
-            fs::write(agents_path, AGENTS_CONTENTS)?;
+            // Neither exists: start from a template filled in from the project
+            let rendered = render_agents_template(tx.project_dir(), template)?;
+            tx.write(&agents_path, &rendered.content)?;
+            if rendered.name != "minimal" {
+                println!(
+                    "AGENTS.md has been created from the '{}' template.",
+                    rendered.name
+                );
+            }
         }
     }
 
@@ -119,7 +170,7 @@ fn create_agents_file_based_on_existing_files<P: AsRef<Path>>(
 ///
 /// - If an AGENTS.md file already exists, no action is taken
 /// - If a CLAUDE.md file exists, it will be renamed to AGENTS.md
-/// - Otherwise, an empty AGENTS.md file is created
+/// - Otherwise, AGENTS.md is created from a template filled in from the project
 /// - Creates a .rules directory if it doesn't exist
 ///
 /// # Errors
@@ -151,9 +202,16 @@ pub fn create_agents_file() -> io::Result<()> {
 /// 2. If `agents.md` exists in any case variation, returns successfully without changes
 /// 3. If `claude.md` exists, renames it to `AGENTS.md`
 /// 4. If `gemini.md` exists, renames it to `AGENTS.md`
-/// 5. If both `claude.md` and `gemini.md` exist, creates empty `AGENTS.md` and prints instructions
-/// 6. Otherwise, creates an empty `AGENTS.md` file
+/// 5. If both `claude.md` and `gemini.md` exist, backs them up to `.known/backups` and
+///    merges them into `AGENTS.md` by heading, marking divergent sections
+/// 6. Otherwise, creates `AGENTS.md` from a template filled in with the build, test
+///    and lint commands and layout detected in the project (see [`crate::templates`])
 /// 7. Creates a `.rules` directory if it doesn't exist
+/// 8. Imports legacy `.cursorrules`, `.windsurfrules` and `.clinerules` files as set
+///    in the `legacy` section of .known.json
+///
+/// The changes are journaled in `.known/`: they are rolled back if a step fails,
+/// and can be reverted with `known undo` afterwards.
 ///
 /// # Errors
 ///
@@ -164,23 +222,87 @@ pub fn create_agents_file() -> io::Result<()> {
 /// - Directory creation fails
 ///
 pub fn create_agents_file_in_dir<P: AsRef<Path>>(dir: P) -> io::Result<()> {
-    let dir = dir.as_ref();
+    create_agents_file_with_options(dir.as_ref(), &InitOptions::default()).map(|_| ())
+}
+
+/// Options for [`create_agents_file_with_options`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    /// Legacy import mode to save to .known.json before importing
+    pub legacy_import: Option<LegacyImport>,
+    /// Legacy cleanup to save to .known.json before importing
+    pub legacy_cleanup: Option<LegacyCleanup>,
+    /// How CLAUDE.md and GEMINI.md are combined when both exist
+    pub merge_strategy: MergeStrategy,
+    /// Template for a new AGENTS.md; detected from the project if `None`
+    pub template: Option<String>,
+}
 
+/// Creates an AGENTS.md file and imports legacy rules files with the given options.
+///
+/// Behaves like [`create_agents_file_in_dir`], and additionally imports legacy
+/// single-file rules (`.cursorrules`, `.windsurfrules`, `.clinerules`) as set in
+/// the `legacy` section of .known.json, which is updated with the options first.
+/// Legacy files are imported even if AGENTS.md already exists.
+///
+/// # Arguments
+///
+/// * `dir` - The directory path where the AGENTS.md file should be created
+/// * `options` - Template, merge strategy and legacy import settings to save before importing
+///
+/// # Returns
+///
+/// Returns the legacy files that were imported.
+///
+/// # Errors
+///
+/// Returns the same errors as [`create_agents_file_in_dir`], and an error if the
+/// project configuration is malformed or a legacy file cannot be imported.
+pub fn create_agents_file_with_options(
+    dir: &Path,
+    options: &InitOptions,
+) -> io::Result<Vec<ImportedLegacyFile>> {
     let file_status = scan_directory_for_agent_files(dir)?;
+    let mut project_config = load_project_config(dir)?;
 
-    if file_status.agents_exists {
-        return Ok(());
+    let mut legacy = project_config.legacy.clone();
+    if let Some(import) = options.legacy_import {
+        legacy.import = import;
     }
+    if let Some(cleanup) = options.legacy_cleanup {
+        legacy.cleanup = cleanup;
+    }
+    let legacy_changed = legacy != project_config.legacy;
 
-    create_agents_file_based_on_existing_files(dir, file_status)?;
-    ensure_rules_directory_exists(dir)?;
+    if file_status.agents_exists && !legacy_changed && find_legacy_rule_files(dir).is_empty() {
+        return Ok(Vec::new());
+    }
 
-    Ok(())
+    Transaction::run(dir, "init", |tx| {
+        if legacy_changed {
+            project_config.legacy = legacy;
+            tx.snapshot(&dir.join(PROJECT_CONFIG_FILENAME))?;
+            save_project_config(dir, &project_config)?;
+        }
+
+        if !file_status.agents_exists {
+            create_agents_file_based_on_existing_files(
+                tx,
+                file_status,
+                options.merge_strategy,
+                options.template.as_deref(),
+            )?;
+            tx.create_dir(&dir.join(RULES_DIR))?;
+        }
+
+        import_legacy_rule_files(tx, &project_config.legacy)
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::AGENTS_CONTENTS;
     use std::fs;
     use tempfile::tempdir;
 
@@ -278,7 +400,31 @@ mod tests {
         assert!(gemini_path.exists());
 
         let content = fs::read_to_string(&agents_path).unwrap();
-        assert_eq!(content, "");
+        assert_eq!(content, "# Claude content\n\n# Gemini content\n");
+        let backups = fs::read_dir(dir.path().join(".known/backups")).unwrap();
+        assert_eq!(backups.count(), 2);
+    }
+
+    #[test]
+    fn test_both_claude_and_gemini_exist_with_strategies() {
+        let dir = tempdir().unwrap();
+        let agents_path = dir.path().join(AGENTS_FILENAME);
+        fs::write(dir.path().join(CLAUDE_FILENAME), "# Claude content").unwrap();
+        fs::write(dir.path().join(GEMINI_FILENAME), "# Gemini content").unwrap();
+
+        for (strategy, expected) in [
+            (MergeStrategy::Empty, ""),
+            (MergeStrategy::PreferClaude, "# Claude content"),
+            (MergeStrategy::PreferGemini, "# Gemini content"),
+        ] {
+            let options = InitOptions {
+                merge_strategy: strategy,
+                ..InitOptions::default()
+            };
+            create_agents_file_with_options(dir.path(), &options).unwrap();
+            assert_eq!(fs::read_to_string(&agents_path).unwrap(), expected);
+            fs::remove_file(&agents_path).unwrap();
+        }
     }
 
     #[test]
@@ -341,8 +487,8 @@ mod tests {
 
         let result = create_agents_file_in_dir(&readonly_dir);
 
-        if result.is_err() {
-            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        if let Err(e) = result {
+            assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
         }
     }
 }
@@ -0,0 +1,497 @@
+//! Aider integration that keeps the `read:` list in `.aider.conf.yml` in sync.
+//!
+//! Aider doesn't discover instruction files on its own, so known maintains a
+//! managed section of the project's `.aider.conf.yml` listing AGENTS.md and every
+//! rule the project's targets get. Content outside the managed section is never
+//! modified.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::{get_global_rules_dir, load_project_config};
+use crate::constants::{AGENTS_FILENAME, AIDER_CONFIG_FILENAME, AIDER_TARGET, RULES_DIR};
+use crate::daemon::symlinks::project_targets;
+use crate::global::global_file_name;
+use crate::managed::{replace_section, Placement};
+use crate::monorepo::{inherited_rule_files, inheriting_root};
+use crate::rules::{
+    is_rule_disabled, is_rule_in_profile, list_rule_files, rule_applies_to, TargetFormat,
+};
+
+/// Marker line that opens the managed section
+const MANAGED_BLOCK_START: &str = "# >>> known managed section (do not edit) >>>";
+
+/// Marker line that closes the managed section
+const MANAGED_BLOCK_END: &str = "# <<< known managed section <<<";
+
+/// Updates `.aider.conf.yml` if the Aider target is enabled for the project.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if the project configuration cannot be loaded or the
+/// Aider configuration cannot be updated.
+pub fn sync_aider_config_if_enabled<P: AsRef<Path>>(dir: P) -> io::Result<()> {
+    let dir = dir.as_ref();
+    if load_project_config(dir)?.has_target(AIDER_TARGET) {
+        sync_aider_config(dir)?;
+    }
+    Ok(())
+}
+
+/// Writes the managed `read:` section of `.aider.conf.yml` in the specified directory.
+///
+/// The section lists AGENTS.md (if present) followed by every rule that applies
+/// to Aider, isn't disabled and is in the active profile (see the `agents`,
+/// `exclude_agents` and `enabled` rule frontmatter), sorted by name. Rules
+/// inherited from `.rules` directories above the project and the user's global
+/// rules are included, as for the other targets; global rules are listed through
+/// their Git-excluded `global-` links. The configuration file is
+/// created if it doesn't exist and is only rewritten when its content actually
+/// changes.
+///
+/// If the part of the file outside the managed section already defines `read:`,
+/// the file is left unchanged, since a second `read:` key would make it invalid.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if the configuration file was modified.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the file defines `read:` outside the managed
+/// section, or an error if the rules directory or the configuration file cannot
+/// be read, or if writing the configuration file fails.
+pub fn sync_aider_config<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let global_rules_dir = get_global_rules_dir().unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Failed to locate the global rules directory: {}",
+            e
+        );
+        PathBuf::new()
+    });
+    sync_aider_config_with_global_rules(dir.as_ref(), &global_rules_dir)
+}
+
+/// Writes the managed section of `.aider.conf.yml`, taking global rules from the given directory.
+pub(crate) fn sync_aider_config_with_global_rules(
+    dir: &Path,
+    global_rules_dir: &Path,
+) -> io::Result<bool> {
+    let config_path = dir.join(AIDER_CONFIG_FILENAME);
+
+    let existing = if config_path.exists() {
+        fs::read_to_string(&config_path)?
+    } else {
+        String::new()
+    };
+
+    if has_unmanaged_read_key(&existing) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} already defines 'read:' outside the section managed by known. Move its entries into AGENTS.md or .rules, or remove the key, and try again.",
+                config_path.display()
+            ),
+        ));
+    }
+
+    let entries = collect_read_entries(dir, global_rules_dir)?;
+    let updated = replace_managed_block(&existing, Some(&render_managed_block(&entries)));
+
+    if updated == existing {
+        return Ok(false);
+    }
+
+    fs::write(&config_path, updated)?;
+    Ok(true)
+}
+
+/// Removes the managed section from `.aider.conf.yml`, leaving the rest of the file intact.
+///
+/// If the file only contained the managed section, it is deleted.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if the configuration file cannot be read, written or removed.
+pub fn remove_aider_config_block<P: AsRef<Path>>(dir: P) -> io::Result<()> {
+    let config_path = dir.as_ref().join(AIDER_CONFIG_FILENAME);
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let existing = fs::read_to_string(&config_path)?;
+    let updated = replace_managed_block(&existing, None);
+
+    if updated.trim().is_empty() {
+        fs::remove_file(&config_path)?;
+    } else if updated != existing {
+        fs::write(&config_path, updated)?;
+    }
+    Ok(())
+}
+
+/// Replaces the managed section of `.aider.conf.yml` with a plain `read:` list.
+///
+/// Used when a project stops being managed by known, so that Aider keeps reading
+/// the instruction files without the marker comments.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `include_rules` - Whether files in `.rules` stay in the list; pass `false`
+///   when the rules directory is going to be removed
+///
+/// # Returns
+///
+/// Returns `true` if the configuration file was modified.
+///
+/// # Errors
+///
+/// Returns an error if the rules directory or the configuration file cannot be
+/// read, or if writing the configuration file fails.
+pub fn unmanage_aider_config<P: AsRef<Path>>(dir: P, include_rules: bool) -> io::Result<bool> {
+    let dir = dir.as_ref();
+    let config_path = dir.join(AIDER_CONFIG_FILENAME);
+    if !config_path.exists() {
+        return Ok(false);
+    }
+
+    let existing = fs::read_to_string(&config_path)?;
+    if !existing.contains(MANAGED_BLOCK_START) {
+        return Ok(false);
+    }
+
+    // The project no longer gets global rules once it is unmanaged
+    let rules_prefix = format!("{}/", RULES_DIR);
+    let entries: Vec<String> = collect_read_entries(dir, Path::new(""))?
+        .into_iter()
+        .filter(|entry| include_rules || !entry.starts_with(&rules_prefix))
+        .collect();
+    let plain_list = (!entries.is_empty()).then(|| render_read_list(&entries));
+    let updated = replace_managed_block(&existing, plain_list.as_deref());
+
+    if updated.trim().is_empty() {
+        fs::remove_file(&config_path)?;
+    } else {
+        fs::write(&config_path, updated)?;
+    }
+    Ok(true)
+}
+
+/// Collects the paths that Aider should read, relative to the project where possible.
+///
+/// Pass an empty `global_rules_dir` to leave global rules out.
+fn collect_read_entries(dir: &Path, global_rules_dir: &Path) -> io::Result<Vec<String>> {
+    let mut entries = Vec::new();
+
+    if dir.join(AGENTS_FILENAME).exists() {
+        entries.push(AGENTS_FILENAME.to_string());
+    }
+
+    for path in inherited_rule_files(dir)? {
+        if rule_applies_to(&path, AIDER_TARGET)
+            && is_rule_in_profile(dir, &path)
+            && path
+                .file_name()
+                .is_some_and(|name| !is_rule_disabled(dir, name))
+        {
+            entries.push(read_entry(dir, &path));
+        }
+    }
+
+    if !global_rules_dir.as_os_str().is_empty() {
+        for path in list_rule_files(global_rules_dir)? {
+            let global_name = match path.file_name() {
+                Some(name) => global_file_name(name),
+                None => continue,
+            };
+            if rule_applies_to(&path, AIDER_TARGET)
+                && is_rule_in_profile(dir, &path)
+                && !is_rule_disabled(dir, &global_name)
+            {
+                entries.extend(global_read_entry(dir, &path, &global_name));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Returns the path Aider reads a global rule from, relative to the project directory.
+///
+/// `.aider.conf.yml` is usually committed, so global rules are referenced through
+/// their `global-` link in one of the project's symlink targets, which is excluded
+/// from Git, rather than by a path that only exists on this machine. Global rules
+/// that aren't linked into any target are left out.
+fn global_read_entry(dir: &Path, path: &Path, global_name: &OsStr) -> Option<String> {
+    project_targets(dir)
+        .into_iter()
+        .find(|target| target.format == TargetFormat::Symlink && rule_applies_to(path, target.name))
+        .map(|target| {
+            let entry = Path::new(target.dir).join(target.entry_name(global_name));
+            entry.to_string_lossy().replace('\\', "/")
+        })
+}
+
+/// Returns the path Aider reads a rule from, relative to the project directory.
+///
+/// Rules inherited from a parent directory get a `../` path, so the entry works
+/// in every checkout. Other rules outside the project keep their absolute path.
+fn read_entry(dir: &Path, path: &Path) -> String {
+    let root = inheriting_root(dir).unwrap_or_else(|| dir.to_path_buf());
+    for (depth, ancestor) in dir
+        .ancestors()
+        .take_while(|ancestor| ancestor.starts_with(&root))
+        .enumerate()
+    {
+        if let Ok(relative) = path.strip_prefix(ancestor) {
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            return format!("{}{}", "../".repeat(depth), relative);
+        }
+    }
+    path.display().to_string()
+}
+
+/// Renders the managed section, including its marker lines.
+fn render_managed_block(entries: &[String]) -> String {
+    let mut block = String::new();
+    block.push_str(MANAGED_BLOCK_START);
+    block.push('\n');
+    block.push_str(&render_read_list(entries));
+    block.push_str(MANAGED_BLOCK_END);
+    block.push('\n');
+    block
+}
+
+/// Renders the `read:` key listing the given entries.
+fn render_read_list(entries: &[String]) -> String {
+    if entries.is_empty() {
+        return "read: []\n".to_string();
+    }
+
+    let mut list = String::from("read:\n");
+    for entry in entries {
+        list.push_str(&format!("  - \"{}\"\n", entry.replace('"', "\\\"")));
+    }
+    list
+}
+
+/// Replaces (or removes, when `block` is `None`) the managed section in `content`.
+///
+/// When no managed section exists yet, the block is appended to the end of the file.
+fn replace_managed_block(content: &str, block: Option<&str>) -> String {
+    replace_section(
+        content,
+        MANAGED_BLOCK_START,
+        MANAGED_BLOCK_END,
+        block,
+        Placement::Bottom,
+    )
+}
+
+/// Checks whether the user's part of the configuration already defines `read:`.
+fn has_unmanaged_read_key(content: &str) -> bool {
+    let unmanaged = replace_managed_block(content, None);
+    unmanaged.lines().any(|line| line.starts_with("read:"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{save_project_config, ProjectConfig};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sync_aider_config_creates_managed_section() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("b.md"), "b").unwrap();
+        fs::write(rules_path.join("a.md"), "a").unwrap();
+
+        let changed = sync_aider_config(dir.path()).unwrap();
+        assert!(changed);
+
+        let content = fs::read_to_string(dir.path().join(AIDER_CONFIG_FILENAME)).unwrap();
+        assert!(content.contains(MANAGED_BLOCK_START));
+        assert!(content
+            .contains("read:\n  - \"AGENTS.md\"\n  - \".rules/a.md\"\n  - \".rules/b.md\"\n"));
+
+        // A second sync without changes should not rewrite the file
+        assert!(!sync_aider_config(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_sync_aider_config_preserves_user_content() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let config_path = dir.path().join(AIDER_CONFIG_FILENAME);
+        fs::write(&config_path, "model: sonnet\nauto-commits: false").unwrap();
+
+        sync_aider_config(dir.path()).unwrap();
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.starts_with("model: sonnet\nauto-commits: false\n"));
+
+        // Adding a rule rewrites only the managed section
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "style").unwrap();
+        fs::write(
+            &config_path,
+            content.replace("model: sonnet", "model: opus") + "dark-mode: true\n",
+        )
+        .unwrap();
+
+        sync_aider_config(dir.path()).unwrap();
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.starts_with("model: opus\n"));
+        assert!(content.contains("  - \".rules/style.md\"\n"));
+        assert!(content.ends_with("dark-mode: true\n"));
+        assert_eq!(content.matches(MANAGED_BLOCK_START).count(), 1);
+
+        // Removing a rule drops it from the list
+        fs::remove_file(rules_path.join("style.md")).unwrap();
+        sync_aider_config(dir.path()).unwrap();
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(!content.contains("style.md"));
+    }
+
+    #[test]
+    fn test_remove_aider_config_block() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join(AIDER_CONFIG_FILENAME);
+
+        // A file containing only the managed section is deleted
+        sync_aider_config(dir.path()).unwrap();
+        remove_aider_config_block(dir.path()).unwrap();
+        assert!(!config_path.exists());
+
+        // User content survives removal of the managed section
+        fs::write(&config_path, "model: sonnet\n").unwrap();
+        sync_aider_config(dir.path()).unwrap();
+        remove_aider_config_block(dir.path()).unwrap();
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "model: sonnet\n");
+    }
+
+    #[test]
+    fn test_unmanage_aider_config() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "style").unwrap();
+        let config_path = dir.path().join(AIDER_CONFIG_FILENAME);
+        fs::write(&config_path, "model: sonnet\n").unwrap();
+        sync_aider_config(dir.path()).unwrap();
+
+        assert!(unmanage_aider_config(dir.path(), false).unwrap());
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "model: sonnet\nread:\n  - \"AGENTS.md\"\n"
+        );
+        assert!(!unmanage_aider_config(dir.path(), false).unwrap());
+    }
+
+    #[test]
+    fn test_sync_aider_config_if_enabled() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join(AIDER_CONFIG_FILENAME);
+
+        // Not enabled: nothing is written
+        sync_aider_config_if_enabled(dir.path()).unwrap();
+        assert!(!config_path.exists());
+
+        let mut project_config = ProjectConfig::new();
+        project_config.enable_target(AIDER_TARGET);
+        save_project_config(dir.path(), &project_config).unwrap();
+
+        sync_aider_config_if_enabled(dir.path()).unwrap();
+        assert!(config_path.exists());
+    }
+
+    #[test]
+    fn test_sync_aider_config_refuses_unmanaged_read_key() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let config_path = dir.path().join(AIDER_CONFIG_FILENAME);
+        fs::write(&config_path, "read: CONVENTIONS.md\n").unwrap();
+
+        let error = sync_aider_config(dir.path()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "read: CONVENTIONS.md\n"
+        );
+    }
+
+    #[test]
+    fn test_sync_aider_config_includes_inherited_and_global_rules() {
+        let dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"recursive": true, "inherit": true}"#,
+        )
+        .unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(RULES_DIR).join("security.md"), "root").unwrap();
+        let package = root.join("packages").join("web");
+        fs::create_dir_all(package.join(RULES_DIR)).unwrap();
+        fs::write(package.join(AGENTS_FILENAME), "# Web").unwrap();
+        fs::write(package.join(RULES_DIR).join("web.md"), "web").unwrap();
+        fs::write(global_dir.path().join("style.md"), "personal").unwrap();
+
+        sync_aider_config_with_global_rules(&package, global_dir.path()).unwrap();
+
+        let content = fs::read_to_string(package.join(AIDER_CONFIG_FILENAME)).unwrap();
+        assert!(content.contains(
+            "read:\n  - \"AGENTS.md\"\n  - \"../../.rules/security.md\"\n  - \".rules/web.md\"\n  - \".cursor/rules/global-style.md\"\n"
+        ));
+        assert!(!content.contains(&global_dir.path().display().to_string()));
+    }
+
+    #[test]
+    fn test_sync_aider_config_applies_profile_to_global_rules() {
+        let dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"profiles": {"review": {"rules": ["style"]}}, "profile": "review"}"#,
+        )
+        .unwrap();
+        fs::write(global_dir.path().join("style.md"), "personal").unwrap();
+        fs::write(global_dir.path().join("tone.md"), "personal").unwrap();
+
+        sync_aider_config_with_global_rules(dir.path(), global_dir.path()).unwrap();
+
+        let content = fs::read_to_string(dir.path().join(AIDER_CONFIG_FILENAME)).unwrap();
+        assert!(content.contains("global-style.md"));
+        assert!(!content.contains("global-tone.md"));
+    }
+
+    #[test]
+    fn test_has_unmanaged_read_key() {
+        let managed = render_managed_block(&["AGENTS.md".to_string()]);
+        assert!(!has_unmanaged_read_key(&managed));
+        assert!(has_unmanaged_read_key(&format!(
+            "read: CONVENTIONS.md\n{}",
+            managed
+        )));
+    }
+}
@@ -37,12 +37,7 @@ fn build_auto_launch() -> io::Result<AutoLaunch> {
         .set_use_launch_agent(true)
         .set_args(&["run-daemon"])
         .build()
-        .map_err(|e| {
-            Error::new(
-                ErrorKind::Other,
-                format!("Failed to create AutoLaunch instance: {}", e),
-            )
-        })
+        .map_err(|e| Error::other(format!("Failed to create AutoLaunch instance: {}", e)))
 }
 
 /// Enables autostart for the known daemon.
@@ -65,12 +60,9 @@ fn build_auto_launch() -> io::Result<AutoLaunch> {
 ///
 pub fn enable_autostart() -> io::Result<()> {
     let auto_launch = build_auto_launch()?;
-    auto_launch.enable().map_err(|e| {
-        Error::new(
-            ErrorKind::Other,
-            format!("Failed to enable autostart: {}", e),
-        )
-    })?;
+    auto_launch
+        .enable()
+        .map_err(|e| Error::other(format!("Failed to enable autostart: {}", e)))?;
     Ok(())
 }
 
@@ -86,12 +78,9 @@ pub fn enable_autostart() -> io::Result<()> {
 ///
 pub fn disable_autostart() -> io::Result<()> {
     let auto_launch = build_auto_launch()?;
-    auto_launch.disable().map_err(|e| {
-        Error::new(
-            ErrorKind::Other,
-            format!("Failed to disable autostart: {}", e),
-        )
-    })?;
+    auto_launch
+        .disable()
+        .map_err(|e| Error::other(format!("Failed to disable autostart: {}", e)))?;
     Ok(())
 }
 
@@ -105,12 +94,9 @@ pub fn disable_autostart() -> io::Result<()> {
 ///
 pub fn is_autostart_enabled() -> io::Result<bool> {
     let auto_launch = build_auto_launch()?;
-    auto_launch.is_enabled().map_err(|e| {
-        Error::new(
-            ErrorKind::Other,
-            format!("Failed to check autostart status: {}", e),
-        )
-    })
+    auto_launch
+        .is_enabled()
+        .map_err(|e| Error::other(format!("Failed to check autostart status: {}", e)))
 }
 
 #[cfg(test)]
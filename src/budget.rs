@@ -0,0 +1,420 @@
+//! Token estimates for the instructions and rules each agent loads, reported by
+//! `known budget`.
+//!
+//! Counts come from an offline approximation of the byte-pair encodings used by
+//! current models, so they are estimates rather than exact figures, but they are
+//! stable and close enough to compare rules and agents.
+
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+use crate::conditional::render_for_agent;
+use crate::config::{get_global_rules_dir, load_project_config, ClaudeStrategy, GeminiStrategy};
+use crate::constants::{
+    AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, AIDER_TARGET, AMAZONQ_TARGET, GLOBAL_RULE_PREFIX,
+    RULES_DIR,
+};
+use crate::daemon::symlinks::project_targets;
+use crate::monorepo::inherited_rule_files;
+use crate::rules::{
+    is_rule_disabled, is_rule_in_profile, list_rule_files, rule_applies_to, Activation, Rule,
+};
+
+/// Letters in a word that still counts as a single token
+const SHORT_WORD_LETTERS: usize = 6;
+
+/// Letters per token in longer words
+const LETTERS_PER_TOKEN: usize = 4;
+
+/// Digits per token in numbers
+const DIGITS_PER_TOKEN: usize = 3;
+
+/// Estimates the number of tokens in a text.
+///
+/// Words of up to six ASCII letters count as one token and longer words as one
+/// token per four letters. Numbers count one token per three digits, and every
+/// other character, such as punctuation or a non-ASCII letter, counts as one. A
+/// single space before a word is part of the word's token; other whitespace runs
+/// count as one token each.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() {
+            let len = 1 + skip_while(&mut chars, char::is_ascii_alphabetic);
+            tokens += if len <= SHORT_WORD_LETTERS {
+                1
+            } else {
+                len.div_ceil(LETTERS_PER_TOKEN)
+            };
+        } else if c.is_ascii_digit() {
+            let len = 1 + skip_while(&mut chars, char::is_ascii_digit);
+            tokens += len.div_ceil(DIGITS_PER_TOKEN);
+        } else if c.is_whitespace() {
+            let len = 1 + skip_while(&mut chars, |c| c.is_whitespace());
+            // A single space is merged into the following word
+            if c != ' ' || len > 1 {
+                tokens += 1;
+            }
+        } else {
+            tokens += 1;
+        }
+    }
+    tokens
+}
+
+/// Skips the characters matching a predicate and returns how many were skipped.
+fn skip_while(chars: &mut Peekable<Chars>, predicate: impl Fn(&char) -> bool) -> usize {
+    let mut skipped = 0;
+    while chars.next_if(&predicate).is_some() {
+        skipped += 1;
+    }
+    skipped
+}
+
+/// The estimated size of a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleBudget {
+    /// File name of the rule's entries
+    pub name: String,
+    /// Estimated tokens of the rule's instructions, without frontmatter
+    pub tokens: usize,
+    /// How the rule is activated
+    pub activation: Activation,
+}
+
+/// A file an agent loads and when it loads it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedFile {
+    /// Name of the file (for example `AGENTS.md` or `style.md`)
+    pub name: String,
+    /// Estimated tokens the agent loads from the file
+    pub tokens: usize,
+    /// Whether the file is always loaded, or only for matching files
+    pub always: bool,
+}
+
+/// What an agent loads from a project, as estimated by [`budget_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentBudget {
+    /// Name of the agent or target
+    pub agent: String,
+    /// The files the agent loads; rules only loaded on request are left out
+    pub files: Vec<LoadedFile>,
+    /// The agent's budget from `.known.json`, if one is set
+    pub limit: Option<usize>,
+}
+
+impl AgentBudget {
+    /// Returns the estimated tokens the agent always loads
+    pub fn always_tokens(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| file.always)
+            .map(|file| file.tokens)
+            .sum()
+    }
+
+    /// Returns the estimated tokens the agent loads when every glob matches
+    pub fn glob_tokens(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| !file.always)
+            .map(|file| file.tokens)
+            .sum()
+    }
+
+    /// Checks whether the tokens the agent always loads exceed its budget
+    pub fn is_over_budget(&self) -> bool {
+        self.limit.is_some_and(|limit| self.always_tokens() > limit)
+    }
+}
+
+/// Token estimates for a project, per rule and per agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetReport {
+    /// Every rule of the project, sorted by name
+    pub rules: Vec<RuleBudget>,
+    /// Every agent that reads the project's instructions or rules
+    pub agents: Vec<AgentBudget>,
+}
+
+/// Estimates the tokens each agent loads from a project.
+///
+/// Claude Code, Gemini CLI and Codex CLI read AGENTS.md, with the conditional
+/// sections for them, and Claude Code also reads AGENTS.local.md. Every
+/// enabled target gets the rules synced to it: always-on rules count as
+/// always loaded, glob-scoped rules as loaded for matching files, and rules
+/// applied on request are left out. Amazon Q and Aider load every rule they
+/// get.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if the project configuration is malformed, or an
+/// instructions file or rules directory cannot be read.
+pub fn budget_report(dir: &Path) -> io::Result<BudgetReport> {
+    let project_config = load_project_config(dir)?;
+    let limit_for = |agent: &str| project_config.budget.limit_for(agent);
+    let mut agents = Vec::new();
+
+    let agents_path = dir.join(AGENTS_FILENAME);
+    if agents_path.is_file() {
+        let content = fs::read_to_string(&agents_path)?;
+        let local_path = dir.join(AGENTS_LOCAL_FILENAME);
+        let local = if local_path.is_file() {
+            Some(fs::read_to_string(&local_path)?)
+        } else {
+            None
+        };
+        // Only symlinked aliases are rendered for their agent
+        let claude_view = match project_config.aliases.claude {
+            ClaudeStrategy::Symlink => render_for_agent(&content, "claude")?,
+            ClaudeStrategy::Import => content.clone(),
+        };
+        let (gemini_view, gemini_local) = match project_config.aliases.gemini {
+            GeminiStrategy::Symlink => (render_for_agent(&content, "gemini")?, None),
+            GeminiStrategy::Settings => (content.clone(), local.as_deref()),
+        };
+
+        for (agent, view, local) in [
+            ("claude", claude_view.as_str(), local.as_deref()),
+            ("gemini", gemini_view.as_str(), gemini_local),
+            ("codex", content.as_str(), None),
+        ] {
+            let mut files = vec![LoadedFile {
+                name: AGENTS_FILENAME.to_string(),
+                tokens: estimate_tokens(view),
+                always: true,
+            }];
+            if let Some(local) = local {
+                files.push(LoadedFile {
+                    name: AGENTS_LOCAL_FILENAME.to_string(),
+                    tokens: estimate_tokens(local),
+                    always: true,
+                });
+            }
+            agents.push(AgentBudget {
+                agent: agent.to_string(),
+                files,
+                limit: limit_for(agent),
+            });
+        }
+    }
+
+    let rules = project_rules(dir)?;
+    for target in project_targets(dir) {
+        let files = rules
+            .iter()
+            .filter(|rule| rule.is_synced_to(dir, target.name))
+            .filter_map(|rule| {
+                let always = match rule.budget.activation {
+                    _ if target.name == AMAZONQ_TARGET => true,
+                    Activation::Always => true,
+                    Activation::FileMatch(_) => false,
+                    Activation::Manual => return None,
+                };
+                Some(LoadedFile {
+                    name: rule.budget.name.clone(),
+                    tokens: rule.budget.tokens,
+                    always,
+                })
+            })
+            .collect();
+        agents.push(AgentBudget {
+            agent: target.name.to_string(),
+            files,
+            limit: limit_for(target.name),
+        });
+    }
+
+    if project_config.has_target(AIDER_TARGET) {
+        let mut files = Vec::new();
+        if agents_path.is_file() {
+            files.push(LoadedFile {
+                name: AGENTS_FILENAME.to_string(),
+                tokens: estimate_tokens(&fs::read_to_string(&agents_path)?),
+                always: true,
+            });
+        }
+        // Aider's read list only has the rules in the project's own .rules
+        let rules_path = dir.join(RULES_DIR);
+        files.extend(
+            rules
+                .iter()
+                .filter(|rule| rule.path.parent() == Some(rules_path.as_path()))
+                .filter(|rule| rule.is_synced_to(dir, AIDER_TARGET))
+                .map(|rule| LoadedFile {
+                    name: rule.budget.name.clone(),
+                    tokens: rule.budget.tokens,
+                    always: true,
+                }),
+        );
+        agents.push(AgentBudget {
+            agent: AIDER_TARGET.to_string(),
+            files,
+            limit: limit_for(AIDER_TARGET),
+        });
+    }
+
+    Ok(BudgetReport {
+        rules: rules.into_iter().map(|rule| rule.budget).collect(),
+        agents,
+    })
+}
+
+/// A rule of the project with its estimate
+pub(crate) struct ProjectRule {
+    /// Path to the rules file
+    pub(crate) path: PathBuf,
+    /// Whether the rule is a global rule, which profiles don't apply to
+    pub(crate) global: bool,
+    /// Name, size and activation of the rule
+    pub(crate) budget: RuleBudget,
+}
+
+impl ProjectRule {
+    /// Checks whether the rule is synced to a target, as [`crate::rules::RulesTarget::sync_rule`] does.
+    pub(crate) fn is_synced_to(&self, dir: &Path, target_name: &str) -> bool {
+        rule_applies_to(&self.path, target_name)
+            && !is_rule_disabled(dir, self.budget.name.as_ref())
+            && (self.global || is_rule_in_profile(dir, &self.path))
+    }
+}
+
+/// Lists the rules of a project and global rules, sorted by name, with their estimates.
+pub(crate) fn project_rules(dir: &Path) -> io::Result<Vec<ProjectRule>> {
+    let mut paths: Vec<(String, PathBuf, bool)> = inherited_rule_files(dir)?
+        .into_iter()
+        .map(|path| (file_name_of(&path), path, false))
+        .collect();
+    if let Ok(global_rules_dir) = get_global_rules_dir() {
+        for path in list_rule_files(&global_rules_dir)? {
+            let name = format!("{}{}", GLOBAL_RULE_PREFIX, file_name_of(&path));
+            paths.push((name, path, true));
+        }
+    }
+    paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut rules = Vec::new();
+    for (name, path, global) in paths {
+        // Files that aren't valid UTF-8 are estimated anyway; `known lint` reports them
+        let content = String::from_utf8_lossy(&fs::read(&path)?).into_owned();
+        // Rules with invalid frontmatter are synced as plain files
+        let (tokens, activation) = match Rule::parse(&path, &content) {
+            Ok(rule) => (estimate_tokens(&rule.body), rule.metadata.activation()),
+            Err(_) => (estimate_tokens(&content), Activation::Always),
+        };
+        rules.push(ProjectRule {
+            path,
+            global,
+            budget: RuleBudget {
+                name,
+                tokens,
+                activation,
+            },
+        });
+    }
+    Ok(rules)
+}
+
+/// Returns the file name of a path as a string.
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::save_project_config;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("Use tabs."), 3);
+        assert_eq!(estimate_tokens("internationalization"), 5);
+        assert_eq!(estimate_tokens("port 8080\n\n- item"), 6);
+    }
+
+    #[test]
+    fn test_budget_report_counts_what_each_agent_loads() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(AGENTS_FILENAME),
+            "Shared.\n<!-- known:only claude -->\nOnly for Claude.\n<!-- known:end -->\n",
+        )
+        .unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        fs::write(
+            rules_path.join("rust.md"),
+            "---\nglobs: \"*.rs\"\nexclude_agents: [windsurf]\n---\nRun clippy.",
+        )
+        .unwrap();
+        fs::write(
+            rules_path.join("release.md"),
+            "---\ndescription: How to release\n---\nTag the commit.",
+        )
+        .unwrap();
+        let mut project_config = load_project_config(dir.path()).unwrap();
+        project_config.budget.tokens = Some(2);
+        project_config
+            .budget
+            .agents
+            .insert("claude".to_string(), 100);
+        save_project_config(dir.path(), &project_config).unwrap();
+
+        let report = budget_report(dir.path()).unwrap();
+        let rules: Vec<(&str, usize)> = report
+            .rules
+            .iter()
+            .map(|rule| (rule.name.as_str(), rule.tokens))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![("release.md", 4), ("rust.md", 3), ("style.md", 3)]
+        );
+
+        let agent = |name: &str| {
+            report
+                .agents
+                .iter()
+                .find(|agent| agent.agent == name)
+                .unwrap()
+        };
+        assert!(agent("claude").always_tokens() > agent("gemini").always_tokens());
+        assert!(!agent("claude").is_over_budget());
+        assert_eq!(agent("cursor").always_tokens(), 3);
+        assert_eq!(agent("cursor").glob_tokens(), 3);
+        assert!(agent("cursor").is_over_budget());
+        assert_eq!(agent("windsurf").glob_tokens(), 0);
+        assert!(report
+            .agents
+            .iter()
+            .all(|agent| agent.agent != AIDER_TARGET));
+    }
+
+    #[test]
+    fn test_budget_report_estimates_rules_that_are_not_utf8() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "Shared.\n").unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("latin1.md"), b"Caf\xe9 rules.").unwrap();
+
+        let report = budget_report(dir.path()).unwrap();
+        assert_eq!(report.rules.len(), 1);
+        assert_eq!(report.rules[0].name, "latin1.md");
+        assert!(report.rules[0].tokens > 0);
+    }
+}
@@ -0,0 +1,340 @@
+//! Claude Code integration through an importing `CLAUDE.md`.
+//!
+//! Claude Code supports `@path` imports, so instead of a symlink, `CLAUDE.md` can
+//! be a small regular file that imports AGENTS.md. known maintains the import in a
+//! managed section at the top of the file; anything outside the section (such as
+//! Claude-only notes) belongs to the user and is preserved. If the project has an
+//! `AGENTS.local.md`, the section imports it as well.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::constants::{AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, CLAUDE_FILENAME};
+use crate::managed::{find_section, replace_section, Placement};
+
+/// Marker line that opens the managed section
+const MANAGED_BLOCK_START: &str = "<!-- >>> known managed section (do not edit) >>> -->";
+
+/// Marker line that closes the managed section
+const MANAGED_BLOCK_END: &str = "<!-- <<< known managed section <<< -->";
+
+/// State of the import pointer in a project's `CLAUDE.md`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportState {
+    /// The managed section imports AGENTS.md
+    Intact,
+    /// CLAUDE.md doesn't exist
+    Missing,
+    /// CLAUDE.md is a symlink rather than an importing file
+    Symlink,
+    /// CLAUDE.md has no managed section
+    NoManagedSection,
+    /// The managed section was edited and no longer imports AGENTS.md as generated
+    Modified,
+}
+
+/// Writes or refreshes the managed import section of `CLAUDE.md`.
+///
+/// A `CLAUDE.md` symlink is replaced by a regular file. If the file exists
+/// without a managed section, the section is inserted at the top and the
+/// existing content is kept below it. The file is only rewritten when its
+/// content actually changes.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if CLAUDE.md was modified.
+///
+/// # Errors
+///
+/// Returns an error if CLAUDE.md cannot be read, removed or written.
+pub fn sync_claude_import<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let claude_path = dir.as_ref().join(CLAUDE_FILENAME);
+
+    let existing = match fs::symlink_metadata(&claude_path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            fs::remove_file(&claude_path)?;
+            None
+        }
+        Ok(_) => Some(fs::read_to_string(&claude_path)?),
+        Err(_) => None,
+    };
+
+    let block = render_managed_block(dir.as_ref());
+    let updated = match &existing {
+        Some(content) => replace_managed_block(content, Some(&block)),
+        None => block,
+    };
+
+    if existing.as_deref() == Some(updated.as_str()) {
+        return Ok(false);
+    }
+
+    fs::write(&claude_path, updated)?;
+    Ok(true)
+}
+
+/// Removes the managed import section from `CLAUDE.md`.
+///
+/// If the file only contained the managed section, it is deleted. Files that are
+/// symlinks or have no managed section are left untouched.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if CLAUDE.md was deleted.
+///
+/// # Errors
+///
+/// Returns an `AlreadyExists` error if CLAUDE.md contains notes outside the managed
+/// section, since replacing it with a symlink would discard them. Also returns an
+/// error if the file cannot be read, written or removed.
+pub fn remove_claude_import<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let claude_path = dir.as_ref().join(CLAUDE_FILENAME);
+    match fs::symlink_metadata(&claude_path) {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(false),
+    }
+
+    let existing = fs::read_to_string(&claude_path)?;
+    if find_managed_block(&existing).is_none() {
+        return Ok(false);
+    }
+
+    let remaining = replace_managed_block(&existing, None);
+    if !remaining.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} contains notes outside the section managed by known. Move them into {} or remove them before switching to the symlink strategy.",
+                claude_path.display(),
+                AGENTS_FILENAME
+            ),
+        ));
+    }
+
+    fs::remove_file(&claude_path)?;
+    Ok(true)
+}
+
+/// Turns the managed import section of `CLAUDE.md` into plain `@AGENTS.md` imports.
+///
+/// Used when a project stops being managed by known: the import keeps working
+/// without the marker comments, and notes outside the section are kept.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if CLAUDE.md was modified.
+///
+/// # Errors
+///
+/// Returns an error if CLAUDE.md cannot be read or written.
+pub fn unmanage_claude_import<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let claude_path = dir.as_ref().join(CLAUDE_FILENAME);
+    match fs::symlink_metadata(&claude_path) {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(false),
+    }
+
+    let existing = fs::read_to_string(&claude_path)?;
+    if find_managed_block(&existing).is_none() {
+        return Ok(false);
+    }
+
+    let plain_import = render_imports(dir.as_ref());
+    fs::write(
+        &claude_path,
+        replace_managed_block(&existing, Some(&plain_import)),
+    )?;
+    Ok(true)
+}
+
+/// Checks whether `CLAUDE.md` still imports AGENTS.md through the managed section.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if CLAUDE.md exists but cannot be read.
+pub fn check_claude_import<P: AsRef<Path>>(dir: P) -> io::Result<ImportState> {
+    let claude_path = dir.as_ref().join(CLAUDE_FILENAME);
+    let metadata = match fs::symlink_metadata(&claude_path) {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(ImportState::Missing),
+    };
+    if metadata.file_type().is_symlink() {
+        return Ok(ImportState::Symlink);
+    }
+
+    let content = fs::read_to_string(&claude_path)?;
+    match find_managed_block(&content) {
+        None => Ok(ImportState::NoManagedSection),
+        Some((start, end)) if content[start..end] == render_managed_block(dir.as_ref()) => {
+            Ok(ImportState::Intact)
+        }
+        Some(_) => Ok(ImportState::Modified),
+    }
+}
+
+/// Renders the managed section, including its marker lines.
+fn render_managed_block(dir: &Path) -> String {
+    format!(
+        "{}\n{}{}\n",
+        MANAGED_BLOCK_START,
+        render_imports(dir),
+        MANAGED_BLOCK_END
+    )
+}
+
+/// Renders the import lines: AGENTS.md, then AGENTS.local.md if the project has one.
+fn render_imports(dir: &Path) -> String {
+    let mut imports = format!("@{}\n", AGENTS_FILENAME);
+    if dir.join(AGENTS_LOCAL_FILENAME).exists() {
+        imports.push_str(&format!("@{}\n", AGENTS_LOCAL_FILENAME));
+    }
+    imports
+}
+
+/// Finds the byte range of the managed section, including the newline after the end marker.
+fn find_managed_block(content: &str) -> Option<(usize, usize)> {
+    find_section(content, MANAGED_BLOCK_START, MANAGED_BLOCK_END)
+}
+
+/// Replaces (or removes, when `block` is `None`) the managed section in `content`.
+///
+/// When no managed section exists yet, the block is inserted at the top of the
+/// file, separated from the existing content by a blank line.
+fn replace_managed_block(content: &str, block: Option<&str>) -> String {
+    replace_section(
+        content,
+        MANAGED_BLOCK_START,
+        MANAGED_BLOCK_END,
+        block,
+        Placement::Top,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unmanage_claude_import_keeps_plain_import() {
+        let dir = tempdir().unwrap();
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        fs::write(&claude_path, "# Claude notes\n").unwrap();
+        sync_claude_import(dir.path()).unwrap();
+
+        assert!(unmanage_claude_import(dir.path()).unwrap());
+        assert_eq!(
+            fs::read_to_string(&claude_path).unwrap(),
+            "@AGENTS.md\n\n# Claude notes\n"
+        );
+        assert!(!unmanage_claude_import(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_sync_creates_import_file() {
+        let dir = tempdir().unwrap();
+
+        assert!(sync_claude_import(dir.path()).unwrap());
+        assert!(!sync_claude_import(dir.path()).unwrap());
+
+        let content = fs::read_to_string(dir.path().join(CLAUDE_FILENAME)).unwrap();
+        assert!(content.contains("\n@AGENTS.md\n"));
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Intact
+        );
+    }
+
+    #[test]
+    fn test_sync_imports_local_agents_file() {
+        let dir = tempdir().unwrap();
+        sync_claude_import(dir.path()).unwrap();
+
+        fs::write(dir.path().join(AGENTS_LOCAL_FILENAME), "Use my fork.").unwrap();
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Modified
+        );
+        assert!(sync_claude_import(dir.path()).unwrap());
+        let content = fs::read_to_string(dir.path().join(CLAUDE_FILENAME)).unwrap();
+        assert!(content.contains("\n@AGENTS.md\n@AGENTS.local.md\n"));
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Intact
+        );
+    }
+
+    #[test]
+    fn test_sync_preserves_user_notes() {
+        let dir = tempdir().unwrap();
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        fs::write(&claude_path, "# Claude notes\nUse the Task tool.\n").unwrap();
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::NoManagedSection
+        );
+
+        sync_claude_import(dir.path()).unwrap();
+        let content = fs::read_to_string(&claude_path).unwrap();
+        assert!(content.starts_with(MANAGED_BLOCK_START));
+        assert!(content.ends_with("\n\n# Claude notes\nUse the Task tool.\n"));
+
+        // Repairing an edited section keeps the notes as well
+        fs::write(&claude_path, content.replace("@AGENTS.md", "@OTHER.md")).unwrap();
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Modified
+        );
+        assert!(sync_claude_import(dir.path()).unwrap());
+        assert_eq!(fs::read_to_string(&claude_path).unwrap(), content);
+
+        let result = remove_claude_import(dir.path());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&claude_path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_sync_replaces_symlink_and_remove_deletes_generated_file() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(AGENTS_FILENAME, &claude_path).unwrap();
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_file(AGENTS_FILENAME, &claude_path).unwrap();
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Symlink
+        );
+
+        sync_claude_import(dir.path()).unwrap();
+        assert!(fs::symlink_metadata(&claude_path).unwrap().is_file());
+        assert_eq!(
+            fs::read_to_string(dir.path().join(AGENTS_FILENAME)).unwrap(),
+            "# Agents"
+        );
+
+        assert!(remove_claude_import(dir.path()).unwrap());
+        assert_eq!(
+            check_claude_import(dir.path()).unwrap(),
+            ImportState::Missing
+        );
+    }
+}
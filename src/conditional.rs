@@ -0,0 +1,310 @@
+//! Per-agent conditional sections in AGENTS.md.
+//!
+//! Some instructions only apply to one tool, so AGENTS.md can wrap them in marker
+//! comments:
+//!
+//! ```markdown
+//! <!-- known:only claude -->
+//! Use /review before opening a pull request.
+//! <!-- known:end -->
+//! ```
+//!
+//! As long as AGENTS.md has no such section, CLAUDE.md and GEMINI.md are plain
+//! symlinks. Once it has one, known renders a filtered copy for each agent instead,
+//! marked as generated so it is never mistaken for the user's own file.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::constants::{AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME};
+use crate::symlinks::create_platform_symlink;
+
+/// Prefix of the marker line that opens a conditional section
+const SECTION_START_PREFIX: &str = "<!-- known:only ";
+
+/// Marker line that closes a conditional section
+const SECTION_END: &str = "<!-- known:end -->";
+
+/// First line of an alias rendered from AGENTS.md
+const RENDERED_HEADER_PREFIX: &str = "<!-- Generated by known from AGENTS.md";
+
+/// Returns the agent name used in conditional sections for an alias file.
+///
+/// # Arguments
+///
+/// * `alias` - File name of the alias (for example `CLAUDE.md`)
+pub fn alias_agent(alias: &str) -> Option<&'static str> {
+    match alias {
+        CLAUDE_FILENAME => Some("claude"),
+        GEMINI_FILENAME => Some("gemini"),
+        _ => None,
+    }
+}
+
+/// Checks whether an AGENTS.md document contains a conditional section.
+///
+/// Marker lines inside fenced code blocks don't count.
+pub fn has_conditional_sections(content: &str) -> bool {
+    let mut in_fence = false;
+    content.lines().any(|line| {
+        let trimmed = line.trim();
+        if is_fence(trimmed) {
+            in_fence = !in_fence;
+        }
+        !in_fence && section_agents(trimmed).is_some()
+    })
+}
+
+/// Renders the part of an AGENTS.md document that applies to one agent.
+///
+/// Content outside conditional sections is kept, sections naming the agent are
+/// kept without their marker lines, and all other sections are dropped. A
+/// section can name several agents, separated by spaces or commas.
+///
+/// # Arguments
+///
+/// * `content` - Content of AGENTS.md
+/// * `agent` - Name of the agent (for example `claude`)
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if sections are nested, a section is never
+/// closed, or a closing marker has no section to close.
+pub fn render_for_agent(content: &str, agent: &str) -> io::Result<String> {
+    let mut rendered = String::new();
+    let mut in_fence = false;
+    // Line number where the open section starts and whether it is kept
+    let mut open: Option<(usize, bool)> = None;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if is_fence(trimmed) {
+            in_fence = !in_fence;
+        }
+
+        if !in_fence {
+            if let Some(agents) = section_agents(trimmed) {
+                if let Some((start, _)) = open {
+                    return Err(invalid_sections(format!(
+                        "section on line {} is nested in the section starting on line {}",
+                        index + 1,
+                        start
+                    )));
+                }
+                open = Some((index + 1, agents.contains(&agent)));
+                continue;
+            }
+            if trimmed == SECTION_END {
+                if open.take().is_none() {
+                    return Err(invalid_sections(format!(
+                        "line {} closes a section that was never opened",
+                        index + 1
+                    )));
+                }
+                continue;
+            }
+        }
+
+        if open.is_none_or(|(_, keep)| keep) {
+            rendered.push_str(line);
+        }
+    }
+
+    if let Some((start, _)) = open {
+        return Err(invalid_sections(format!(
+            "section starting on line {} is never closed with {}",
+            start, SECTION_END
+        )));
+    }
+    Ok(rendered)
+}
+
+/// Checks whether a file's content is an alias rendered by known.
+pub fn is_rendered_alias(content: &str) -> bool {
+    content.starts_with(RENDERED_HEADER_PREFIX)
+}
+
+/// Removes the header line from a rendered alias, leaving a plain instruction
+/// file that known no longer recognizes as its own.
+pub fn strip_rendered_header(content: &str) -> &str {
+    if !is_rendered_alias(content) {
+        return content;
+    }
+    content.split_once('\n').map_or("", |(_, rest)| rest)
+}
+
+/// Returns what a symlinked alias should contain when AGENTS.md has conditional sections.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project or package directory
+/// * `alias` - File name of the alias (for example `CLAUDE.md`)
+///
+/// # Returns
+///
+/// Returns the rendered content, or `None` if AGENTS.md is missing, has no
+/// conditional section or the alias belongs to no known agent.
+///
+/// # Errors
+///
+/// Returns an error if AGENTS.md cannot be read or its sections are malformed.
+pub fn rendered_alias_content(dir: &Path, alias: &str) -> io::Result<Option<String>> {
+    let agent = match alias_agent(alias) {
+        Some(agent) => agent,
+        None => return Ok(None),
+    };
+    let agents_path = dir.join(AGENTS_FILENAME);
+    if !agents_path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&agents_path)?;
+    if !has_conditional_sections(&content) {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "{} for {}; edit {} instead -->\n{}",
+        RENDERED_HEADER_PREFIX,
+        agent,
+        AGENTS_FILENAME,
+        render_for_agent(&content, agent)?
+    )))
+}
+
+/// Points an alias using the `symlink` strategy at AGENTS.md.
+///
+/// The alias becomes a symlink to AGENTS.md, or a copy rendered for its agent
+/// when AGENTS.md has conditional sections. Whatever was at the alias path is
+/// replaced, and nothing is written when the alias is already up to date.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project or package directory
+/// * `alias` - File name of the alias (for example `CLAUDE.md`)
+///
+/// # Returns
+///
+/// Returns `true` if the alias was changed.
+///
+/// # Errors
+///
+/// Returns an error if AGENTS.md cannot be rendered or the alias cannot be
+/// replaced.
+pub fn sync_symlinked_alias(dir: &Path, alias: &str) -> io::Result<bool> {
+    let alias_path = dir.join(alias);
+    let metadata = fs::symlink_metadata(&alias_path).ok();
+
+    match rendered_alias_content(dir, alias)? {
+        Some(rendered) => {
+            let current = match &metadata {
+                Some(metadata) if metadata.file_type().is_file() => {
+                    fs::read_to_string(&alias_path).ok()
+                }
+                _ => None,
+            };
+            if current.as_deref() == Some(rendered.as_str()) {
+                return Ok(false);
+            }
+            if metadata.is_some() {
+                fs::remove_file(&alias_path)?;
+            }
+            fs::write(&alias_path, rendered)?;
+        }
+        None => {
+            if fs::read_link(&alias_path).is_ok_and(|target| target == Path::new(AGENTS_FILENAME)) {
+                return Ok(false);
+            }
+            if metadata.is_some() {
+                fs::remove_file(&alias_path)?;
+            }
+            create_platform_symlink(Path::new(AGENTS_FILENAME), &alias_path)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Checks whether a trimmed line opens or closes a fenced code block.
+fn is_fence(line: &str) -> bool {
+    line.starts_with("```") || line.starts_with("~~~")
+}
+
+/// Returns the agents named by a trimmed line that opens a conditional section.
+fn section_agents(line: &str) -> Option<Vec<&str>> {
+    let names = line
+        .strip_prefix(SECTION_START_PREFIX)?
+        .strip_suffix("-->")?;
+    let agents: Vec<&str> = names
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!agents.is_empty()).then_some(agents)
+}
+
+/// Builds the error returned for malformed conditional sections.
+fn invalid_sections(detail: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Invalid conditional section in {}: {}",
+            AGENTS_FILENAME, detail
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const AGENTS: &str = "# Project\n\nShared.\n\n<!-- known:only claude -->\nUse /review.\n<!-- known:end -->\n<!-- known:only gemini, codex -->\nUse the shell tool.\n<!-- known:end -->\n\n```md\n<!-- known:only gemini -->\n```\n";
+
+    #[test]
+    fn test_render_for_agent_filters_sections() {
+        assert!(has_conditional_sections(AGENTS));
+        assert!(!has_conditional_sections(
+            "```\n<!-- known:only claude -->\n```\n"
+        ));
+
+        assert_eq!(
+            render_for_agent(AGENTS, "claude").unwrap(),
+            "# Project\n\nShared.\n\nUse /review.\n\n```md\n<!-- known:only gemini -->\n```\n"
+        );
+        assert_eq!(
+            render_for_agent(AGENTS, "gemini").unwrap(),
+            "# Project\n\nShared.\n\nUse the shell tool.\n\n```md\n<!-- known:only gemini -->\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_render_for_agent_rejects_malformed_sections() {
+        let unclosed = render_for_agent("<!-- known:only claude -->\nText\n", "claude");
+        assert_eq!(unclosed.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(render_for_agent("Text\n<!-- known:end -->\n", "claude").is_err());
+        assert!(render_for_agent(
+            "<!-- known:only claude -->\n<!-- known:only gemini -->\n<!-- known:end -->\n",
+            "claude"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sync_symlinked_alias_switches_between_link_and_rendered_copy() {
+        let dir = tempdir().unwrap();
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        fs::write(dir.path().join(AGENTS_FILENAME), AGENTS).unwrap();
+
+        assert!(sync_symlinked_alias(dir.path(), CLAUDE_FILENAME).unwrap());
+        let rendered = fs::read_to_string(&claude_path).unwrap();
+        assert!(is_rendered_alias(&rendered));
+        assert!(rendered.contains("Use /review."));
+        assert!(!rendered.contains("Use the shell tool."));
+        assert!(!sync_symlinked_alias(dir.path(), CLAUDE_FILENAME).unwrap());
+
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Project\n").unwrap();
+        assert!(sync_symlinked_alias(dir.path(), CLAUDE_FILENAME).unwrap());
+        assert_eq!(
+            fs::read_link(&claude_path).unwrap(),
+            Path::new(AGENTS_FILENAME)
+        );
+    }
+}
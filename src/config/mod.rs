@@ -6,6 +6,7 @@
 
 pub mod io;
 pub mod path;
+pub mod project;
 pub mod structure;
 
 pub use io::{
@@ -13,14 +14,22 @@ pub use io::{
     remove_directory_from_config, remove_directory_from_config_file, save_config,
     save_config_to_file,
 };
-pub use path::get_config_file_path;
+pub use path::{
+    get_config_file_path, get_global_agents_path, get_global_rules_dir, get_templates_dir,
+};
+pub use project::{
+    load_project_config, save_project_config, AliasConfig, BudgetConfig, ClaudeStrategy,
+    GeminiStrategy, LegacyCleanup, LegacyConfig, LegacyImport, LintConfig, ProjectConfig,
+    RuleProfile,
+};
 pub use structure::Config;
 
 #[cfg(test)]
 mod tests {
     use super::{
-        add_directory_to_config_file, load_config_from_file, remove_directory_from_config_file,
-        save_config_to_file, Config,
+        add_directory_to_config_file, load_config_from_file, load_project_config,
+        remove_directory_from_config_file, save_config_to_file, save_project_config,
+        ClaudeStrategy, Config, GeminiStrategy, LegacyCleanup, LegacyImport, ProjectConfig,
     };
     use std::fs;
     use std::path::Path;
@@ -221,11 +230,8 @@ mod tests {
         let config = Config::new();
         let result = save_config_to_file(&config, &config_path);
 
-        if result.is_err() {
-            assert_eq!(
-                result.unwrap_err().kind(),
-                std::io::ErrorKind::PermissionDenied
-            );
+        if let Err(e) = result {
+            assert_eq!(e.kind(), std::io::ErrorKind::PermissionDenied);
         }
     }
 
@@ -337,4 +343,96 @@ mod tests {
         let config_after_remove = load_config_from_file(&config_path).unwrap();
         assert_eq!(config_after_remove.directory_count(), 0);
     }
+
+    #[test]
+    fn test_project_config_defaults_when_missing() {
+        let project_dir = tempdir().unwrap();
+
+        let config = load_project_config(project_dir.path()).unwrap();
+        assert_eq!(config, ProjectConfig::default());
+        assert!(!config.has_target("aider"));
+    }
+
+    #[test]
+    fn test_save_and_load_project_config() {
+        let project_dir = tempdir().unwrap();
+
+        let mut config = ProjectConfig::new();
+        assert!(config.enable_target("aider"));
+        assert!(!config.enable_target("Aider"));
+        save_project_config(project_dir.path(), &config).unwrap();
+
+        let loaded = load_project_config(project_dir.path()).unwrap();
+        assert_eq!(loaded, config);
+        assert!(loaded.has_target("AIDER"));
+    }
+
+    #[test]
+    fn test_load_project_config_malformed_file() {
+        let project_dir = tempdir().unwrap();
+        fs::write(
+            project_dir
+                .path()
+                .join(crate::constants::PROJECT_CONFIG_FILENAME),
+            "{ not json",
+        )
+        .unwrap();
+
+        let result = load_project_config(project_dir.path());
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_project_config_alias_strategies() {
+        let project_dir = tempdir().unwrap();
+        let config_path = project_dir
+            .path()
+            .join(crate::constants::PROJECT_CONFIG_FILENAME);
+
+        // Default strategies are not written to the file
+        save_project_config(project_dir.path(), &ProjectConfig::new()).unwrap();
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), "{}\n");
+
+        fs::write(&config_path, r#"{"aliases": {"gemini": "settings"}}"#).unwrap();
+        let config = load_project_config(project_dir.path()).unwrap();
+        assert_eq!(config.aliases.gemini, GeminiStrategy::Settings);
+        assert_eq!(config.aliases.claude, ClaudeStrategy::Symlink);
+
+        assert_eq!("Symlink".parse(), Ok(GeminiStrategy::Symlink));
+        assert_eq!("import".parse(), Ok(ClaudeStrategy::Import));
+        assert!("hardlink".parse::<GeminiStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_project_config_legacy_settings() {
+        let project_dir = tempdir().unwrap();
+        let config_path = project_dir
+            .path()
+            .join(crate::constants::PROJECT_CONFIG_FILENAME);
+
+        fs::write(&config_path, r#"{"legacy": {"import": "rule"}}"#).unwrap();
+        let config = load_project_config(project_dir.path()).unwrap();
+        assert_eq!(config.legacy.import, LegacyImport::Rule);
+        assert_eq!(config.legacy.cleanup, LegacyCleanup::Link);
+
+        assert_eq!("Remove".parse(), Ok(LegacyCleanup::Remove));
+        assert!("merge".parse::<LegacyImport>().is_err());
+    }
+
+    #[test]
+    fn test_project_config_recursive_flag() {
+        let project_dir = tempdir().unwrap();
+        let config_path = project_dir
+            .path()
+            .join(crate::constants::PROJECT_CONFIG_FILENAME);
+
+        let mut config = ProjectConfig::new();
+        assert!(!config.recursive);
+        config.recursive = true;
+        save_project_config(project_dir.path(), &config).unwrap();
+        assert!(fs::read_to_string(&config_path)
+            .unwrap()
+            .contains("\"recursive\": true"));
+        assert!(load_project_config(project_dir.path()).unwrap().recursive);
+    }
 }
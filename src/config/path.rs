@@ -1,5 +1,6 @@
 //! Handles the logic for determining the configuration file path.
 
+use crate::constants::AGENTS_FILENAME;
 use directories::ProjectDirs;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -7,6 +8,12 @@ use std::path::{Path, PathBuf};
 /// Configuration file name
 const CONFIG_FILE_NAME: &str = "config.json";
 
+/// Name of the directory next to the configuration file holding user templates
+const TEMPLATES_DIR_NAME: &str = "templates";
+
+/// Name of the directory next to the configuration file holding global rules
+const GLOBAL_RULES_DIR_NAME: &str = "rules";
+
 /// Returns the path to the configuration file
 ///
 /// Uses the platform-specific application configuration directory according to:
@@ -123,3 +130,53 @@ pub fn get_config_file_path() -> io::Result<PathBuf> {
 
     Ok(config_path)
 }
+
+/// Returns the directory holding user templates for `known init`
+///
+/// This is the `templates` directory next to the configuration file, for example
+/// `$HOME/.config/known/templates` on Linux.
+///
+/// # Errors
+///
+/// Returns an error if the configuration directory cannot be determined
+pub fn get_templates_dir() -> io::Result<PathBuf> {
+    let config_path = get_config_file_path()?;
+    let config_dir = config_path.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to determine configuration directory for templates",
+        )
+    })?;
+    Ok(config_dir.join(TEMPLATES_DIR_NAME))
+}
+
+/// Returns the directory holding the user's global rules
+///
+/// This is the `rules` directory next to the configuration file, for example
+/// `$HOME/.config/known/rules` on Linux.
+///
+/// # Errors
+///
+/// Returns an error if the configuration directory cannot be determined
+pub fn get_global_rules_dir() -> io::Result<PathBuf> {
+    let config_path = get_config_file_path()?;
+    let config_dir = config_path.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to determine configuration directory for global rules",
+        )
+    })?;
+    Ok(config_dir.join(GLOBAL_RULES_DIR_NAME))
+}
+
+/// Returns the path of the global AGENTS.md shared by the user-level agent files
+///
+/// This is `AGENTS.md` next to the configuration file, for example
+/// `$HOME/.config/known/AGENTS.md` on Linux.
+///
+/// # Errors
+///
+/// Returns an error if the configuration directory cannot be determined
+pub fn get_global_agents_path() -> io::Result<PathBuf> {
+    Ok(get_config_file_path()?.with_file_name(AGENTS_FILENAME))
+}
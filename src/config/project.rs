@@ -0,0 +1,477 @@
+//! Defines the per-project configuration stored alongside AGENTS.md.
+//!
+//! Unlike the system-wide configuration, which only tracks watched directories,
+//! the project configuration lives in the project itself so that settings can be
+//! committed and shared with the rest of a team.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::constants::PROJECT_CONFIG_FILENAME;
+
+/// Project-level configuration read from `.known.json`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectConfig {
+    /// Optional targets enabled in addition to the default `.cursor/rules`
+    /// and `.windsurf/rules` directories (for example `"aider"`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// How AGENTS.md is exposed under tool-specific file names
+    #[serde(default, skip_serializing_if = "AliasConfig::is_default")]
+    pub aliases: AliasConfig,
+    /// How `known init` imports legacy single-file rules such as `.cursorrules`
+    #[serde(default, skip_serializing_if = "LegacyConfig::is_default")]
+    pub legacy: LegacyConfig,
+    /// Whether nested packages with their own AGENTS.md are managed as well
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub recursive: bool,
+    /// Whether nested packages also get the rules of the `.rules` directories above them
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub inherit: bool,
+    /// File names of rules that are kept in `.rules` but not synced to any target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+    /// Named subsets of the rules, selected with `known profile use`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RuleProfile>,
+    /// Name of the active profile; every rule is synced when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Settings of `known lint`
+    #[serde(default, skip_serializing_if = "LintConfig::is_default")]
+    pub lint: LintConfig,
+    /// Token budgets checked by `known budget`
+    #[serde(default, skip_serializing_if = "BudgetConfig::is_default")]
+    pub budget: BudgetConfig,
+}
+
+/// Checks if a flag is unset, so that it is left out of `.known.json`
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// A named set of rules, selected by file name or by the `tags` in their frontmatter
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RuleProfile {
+    /// Rules in the profile, by file name with or without extension
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+    /// Tags whose rules are in the profile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl RuleProfile {
+    /// Checks if the profile selects a rule
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - File name of the rule (for example `style.md`)
+    /// * `tags` - Tags from the rule's frontmatter
+    pub fn selects(&self, file_name: &str, tags: &[String]) -> bool {
+        let stem = Path::new(file_name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        self.rules
+            .iter()
+            .any(|rule| rule == file_name || *rule == stem)
+            || self.tags.iter().any(|tag| {
+                tags.iter()
+                    .any(|rule_tag| rule_tag.eq_ignore_ascii_case(tag))
+            })
+    }
+}
+
+/// Settings of `known lint`
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LintConfig {
+    /// IDs of the checks that are skipped (for example `unmatched-glob`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disable: Vec<String>,
+}
+
+impl LintConfig {
+    /// Checks if the settings are the defaults, so that they are left out of `.known.json`
+    pub fn is_default(&self) -> bool {
+        self.disable.is_empty()
+    }
+
+    /// Checks if a check is enabled
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disable.iter().any(|disabled| disabled == id)
+    }
+}
+
+/// Token budgets checked by `known budget`
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BudgetConfig {
+    /// Tokens any agent may always load
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+    /// Budgets for individual agents, overriding `tokens`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agents: BTreeMap<String, usize>,
+}
+
+impl BudgetConfig {
+    /// Checks if no budget is set, so that the settings are left out of `.known.json`
+    pub fn is_default(&self) -> bool {
+        self.tokens.is_none() && self.agents.is_empty()
+    }
+
+    /// Returns the budget of an agent, if one is set
+    pub fn limit_for(&self, agent: &str) -> Option<usize> {
+        self.agents.get(agent).copied().or(self.tokens)
+    }
+}
+
+/// Per-tool strategies for exposing AGENTS.md under the names tools look for
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AliasConfig {
+    /// How Claude Code is pointed at AGENTS.md
+    #[serde(default)]
+    pub claude: ClaudeStrategy,
+    /// How Gemini CLI is pointed at AGENTS.md
+    #[serde(default)]
+    pub gemini: GeminiStrategy,
+}
+
+impl AliasConfig {
+    /// Checks if all aliases use their default strategy
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How Claude Code is pointed at AGENTS.md
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaudeStrategy {
+    /// Create a `CLAUDE.md` symlink to AGENTS.md
+    #[default]
+    Symlink,
+    /// Maintain a `CLAUDE.md` that imports AGENTS.md with `@AGENTS.md`
+    Import,
+}
+
+impl fmt::Display for ClaudeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaudeStrategy::Symlink => write!(f, "symlink"),
+            ClaudeStrategy::Import => write!(f, "import"),
+        }
+    }
+}
+
+impl FromStr for ClaudeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "symlink" => Ok(ClaudeStrategy::Symlink),
+            "import" => Ok(ClaudeStrategy::Import),
+            _ => Err(format!(
+                "Unknown Claude strategy '{}'. Expected 'symlink' or 'import'",
+                s
+            )),
+        }
+    }
+}
+
+/// How Gemini CLI is pointed at AGENTS.md
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeminiStrategy {
+    /// Create a `GEMINI.md` symlink to AGENTS.md
+    #[default]
+    Symlink,
+    /// Set `contextFileName` in `.gemini/settings.json` instead of creating a symlink
+    Settings,
+}
+
+impl fmt::Display for GeminiStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeminiStrategy::Symlink => write!(f, "symlink"),
+            GeminiStrategy::Settings => write!(f, "settings"),
+        }
+    }
+}
+
+impl FromStr for GeminiStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "symlink" => Ok(GeminiStrategy::Symlink),
+            "settings" => Ok(GeminiStrategy::Settings),
+            _ => Err(format!(
+                "Unknown Gemini strategy '{}'. Expected 'symlink' or 'settings'",
+                s
+            )),
+        }
+    }
+}
+
+/// How legacy single-file rules (`.cursorrules`, `.windsurfrules`, `.clinerules`) are imported
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LegacyConfig {
+    /// Where the content of a legacy file goes
+    #[serde(default)]
+    pub import: LegacyImport,
+    /// What happens to the legacy file once it was imported
+    #[serde(default)]
+    pub cleanup: LegacyCleanup,
+}
+
+impl LegacyConfig {
+    /// Checks if the default import settings are used
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Where the content of a legacy rules file is imported to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegacyImport {
+    /// Append the content to AGENTS.md under a heading naming the legacy file
+    #[default]
+    Section,
+    /// Convert the content into a rule file in `.rules`
+    Rule,
+}
+
+impl fmt::Display for LegacyImport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LegacyImport::Section => write!(f, "section"),
+            LegacyImport::Rule => write!(f, "rule"),
+        }
+    }
+}
+
+impl FromStr for LegacyImport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "section" => Ok(LegacyImport::Section),
+            "rule" => Ok(LegacyImport::Rule),
+            _ => Err(format!(
+                "Unknown legacy import mode '{}'. Expected 'section' or 'rule'",
+                s
+            )),
+        }
+    }
+}
+
+/// What happens to a legacy rules file after its content was imported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegacyCleanup {
+    /// Replace the file with a symlink to where its content was imported
+    #[default]
+    Link,
+    /// Delete the file
+    Remove,
+}
+
+impl fmt::Display for LegacyCleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LegacyCleanup::Link => write!(f, "link"),
+            LegacyCleanup::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+impl FromStr for LegacyCleanup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "link" => Ok(LegacyCleanup::Link),
+            "remove" => Ok(LegacyCleanup::Remove),
+            _ => Err(format!(
+                "Unknown legacy cleanup '{}'. Expected 'link' or 'remove'",
+                s
+            )),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Creates a new empty project configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks if an optional target is enabled for the project
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the target (case-insensitive)
+    pub fn has_target(&self, name: &str) -> bool {
+        self.targets.iter().any(|t| t.eq_ignore_ascii_case(name))
+    }
+
+    /// Enables an optional target for the project
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the target was added, `false` if it was already enabled
+    pub fn enable_target(&mut self, name: &str) -> bool {
+        if self.has_target(name) {
+            return false;
+        }
+        self.targets.push(name.to_string());
+        true
+    }
+
+    /// Checks if a rule is disabled for the project
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - File name of the rule (for example `style.md`)
+    pub fn is_rule_disabled(&self, name: &str) -> bool {
+        self.disabled.iter().any(|rule| rule == name)
+    }
+
+    /// Disables or re-enables a rule for the project
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the configuration changed
+    pub fn set_rule_disabled(&mut self, name: &str, disabled: bool) -> bool {
+        if self.is_rule_disabled(name) == disabled {
+            return false;
+        }
+        if disabled {
+            self.disabled.push(name.to_string());
+            self.disabled.sort();
+        } else {
+            self.disabled.retain(|rule| rule != name);
+        }
+        true
+    }
+
+    /// Renames a rule in the `disabled` list and the `rules` of every profile
+    ///
+    /// Profile entries naming the rule by its file stem are renamed to the new stem.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Old file name of the rule (for example `style.md`)
+    /// * `to` - New file name of the rule
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the configuration changed
+    pub fn rename_rule(&mut self, from: &str, to: &str) -> bool {
+        let stem = |name: &str| {
+            Path::new(name)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let (from_stem, to_stem) = (stem(from), stem(to));
+        let mut changed = false;
+
+        if self.is_rule_disabled(from) {
+            self.set_rule_disabled(from, false);
+            self.set_rule_disabled(to, true);
+            changed = true;
+        }
+        for profile in self.profiles.values_mut() {
+            for rule in &mut profile.rules {
+                if rule == from {
+                    *rule = to.to_string();
+                    changed = true;
+                } else if *rule == from_stem {
+                    *rule = to_stem.clone();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Returns the active profile, if one is set and defined
+    pub fn active_profile(&self) -> Option<&RuleProfile> {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+}
+
+/// Loads the project configuration from the given project directory
+///
+/// Returns the default configuration if the project has no `.known.json` file
+/// or if the file is empty.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if file reading or JSON parsing fails
+pub fn load_project_config<P: AsRef<Path>>(dir: P) -> io::Result<ProjectConfig> {
+    let config_path = dir.as_ref().join(PROJECT_CONFIG_FILENAME);
+
+    if !config_path.exists() {
+        return Ok(ProjectConfig::default());
+    }
+
+    let config_content = fs::read_to_string(&config_path)?;
+    let trimmed_content = config_content.trim();
+
+    if trimmed_content.is_empty() {
+        return Ok(ProjectConfig::default());
+    }
+
+    serde_json::from_str(trimmed_content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Failed to parse project configuration file at {}: {}",
+                config_path.display(),
+                e
+            ),
+        )
+    })
+}
+
+/// Saves the project configuration to the given project directory
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `config` - The project configuration to save
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization or file writing fails
+pub fn save_project_config<P: AsRef<Path>>(dir: P, config: &ProjectConfig) -> io::Result<()> {
+    let config_path = dir.as_ref().join(PROJECT_CONFIG_FILENAME);
+
+    let mut config_content = serde_json::to_string_pretty(config).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to serialize project configuration: {}", e),
+        )
+    })?;
+
+    // Ensure the file ends with a newline
+    if !config_content.ends_with('\n') {
+        config_content.push('\n');
+    }
+
+    fs::write(config_path, config_content)
+}
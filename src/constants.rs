@@ -3,20 +3,101 @@
 /// The directory name for rules files
 pub const RULES_DIR: &str = ".rules";
 
+/// The directory name for local rules files that are never committed
+pub const RULES_LOCAL_DIR: &str = ".rules.local";
+
+/// Extensions of the rule files adopted from the tool-specific rules directories
+pub const RULE_EXTENSIONS: [&str; 2] = ["md", "mdc"];
+
 /// The directory name for cursor rules files
 pub const CURSOR_RULES_DIR: &str = ".cursor/rules";
 
 /// The directory name for windsurf rules files
 pub const WINDSURF_RULES_DIR: &str = ".windsurf/rules";
 
+/// The legacy single-file Cursor rules file
+pub const CURSORRULES_FILENAME: &str = ".cursorrules";
+
+/// The legacy single-file Windsurf rules file
+pub const WINDSURFRULES_FILENAME: &str = ".windsurfrules";
+
+/// The legacy single-file Cline rules file
+pub const CLINERULES_FILENAME: &str = ".clinerules";
+
 /// The filename for the agents instruction file (uppercase)
 pub const AGENTS_FILENAME: &str = "AGENTS.md";
 
 /// Default content for a new AGENTS.md file
 pub const AGENTS_CONTENTS: &str = "# AGENTS\nThis file provides guidance to agentic coding agents like [Claude Code](https://claude.ai/code), [Gemini CLI](https://github.com/google-gemini/gemini-cli), and [Codex CLI](https://github.com/openai/codex) when working with code in this repository.";
 
+/// Default content for a new global AGENTS.md file
+pub const GLOBAL_AGENTS_CONTENTS: &str = "# AGENTS\nPersonal instructions for agentic coding agents, shared by every project. known links the user-level CLAUDE.md, GEMINI.md and Codex AGENTS.md to this file.\n";
+
+/// The filename for local additions to AGENTS.md that are never committed
+pub const AGENTS_LOCAL_FILENAME: &str = "AGENTS.local.md";
+
 /// The filename for the claude instruction file (uppercase)
 pub const CLAUDE_FILENAME: &str = "CLAUDE.md";
 
+/// The filename of the local instruction file Claude Code reads next to CLAUDE.md
+pub const CLAUDE_LOCAL_FILENAME: &str = "CLAUDE.local.md";
+
 /// The filename for the gemini instruction file (uppercase)
 pub const GEMINI_FILENAME: &str = "GEMINI.md";
+
+/// The prefix of the entries global rules get in target directories
+pub const GLOBAL_RULE_PREFIX: &str = "global-";
+
+/// The filename for the per-project configuration file
+pub const PROJECT_CONFIG_FILENAME: &str = ".known.json";
+
+/// The name of the optional Aider target
+pub const AIDER_TARGET: &str = "aider";
+
+/// The filename for the Aider configuration file
+pub const AIDER_CONFIG_FILENAME: &str = ".aider.conf.yml";
+
+/// The name of the Cursor target
+pub const CURSOR_TARGET: &str = "cursor";
+
+/// The name of the Windsurf target
+pub const WINDSURF_TARGET: &str = "windsurf";
+
+/// The name of the optional Kiro steering target
+pub const KIRO_TARGET: &str = "kiro";
+
+/// The directory name for Kiro steering files
+pub const KIRO_STEERING_DIR: &str = ".kiro/steering";
+
+/// The name of the optional Amazon Q Developer target
+pub const AMAZONQ_TARGET: &str = "amazonq";
+
+/// The directory name for Amazon Q Developer rules files
+pub const AMAZONQ_RULES_DIR: &str = ".amazonq/rules";
+
+/// The path of the Gemini CLI project settings file, relative to the project root
+pub const GEMINI_SETTINGS_PATH: &str = ".gemini/settings.json";
+
+/// The Gemini CLI setting that names the project's context file
+pub const GEMINI_CONTEXT_FILE_KEY: &str = "contextFileName";
+
+/// The directory name for state kept by known inside a project
+pub const KNOWN_STATE_DIR: &str = ".known";
+
+/// The directory name for backups of replaced files, inside the state directory
+pub const BACKUPS_DIR: &str = "backups";
+
+/// The directory name for backups of aliases repaired by the daemon, inside the backups directory
+pub const REPAIRS_DIR: &str = "repairs";
+
+/// The directory name for the journal of a run in progress, inside the state directory
+pub const PENDING_JOURNAL_DIR: &str = "transaction";
+
+/// The directory name for the journal of the last completed run, inside the state directory
+pub const LAST_RUN_JOURNAL_DIR: &str = "last-run";
+
+/// The file name of a run's journal, inside its journal directory
+pub const JOURNAL_FILENAME: &str = "journal.json";
+
+/// The file name of the record of links in target directories, inside the state directory
+pub const LINKS_FILENAME: &str = "links.json";
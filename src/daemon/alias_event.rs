@@ -0,0 +1,304 @@
+//! Handles file system events for the instruction files at a project's root.
+
+use crate::aider::sync_aider_config_if_enabled;
+use crate::conditional::{is_rendered_alias, sync_symlinked_alias};
+use crate::constants::{AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME};
+use crate::repair::{repair_alias, symlinked_aliases};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Handles a file system event for AGENTS.md or one of its aliases.
+///
+/// When CLAUDE.md or GEMINI.md is expected to be a symlink but was replaced by a
+/// regular file, its content is preserved, merged into AGENTS.md for review and the
+/// symlink is restored. When AGENTS.md is added or removed, the Aider configuration
+/// is refreshed, and when it changes while it has conditional sections, the
+/// aliases are rendered again. Events for other files are ignored. Every action is
+/// logged, and repairs leave a backup that `known status` reports, since the
+/// daemon's output usually goes nowhere.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if the project configuration cannot be loaded or a repair fails
+///
+pub fn handle_instruction_file_event(
+    event: &Event,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    for path in &event.paths {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+        if ![AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME].contains(&file_name) {
+            continue;
+        }
+
+        // Find the project whose root contains this file
+        let parent_dir = match rules_paths
+            .iter()
+            .find(|(rules_path, _)| rules_path.parent() == path.parent())
+        {
+            Some((_, parent_dir)) => parent_dir,
+            None => continue,
+        };
+
+        let added_or_removed = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+        );
+
+        if file_name == AGENTS_FILENAME {
+            if added_or_removed {
+                if !path.exists() {
+                    println!(
+                        "Warning: {} was removed from {}; its aliases are broken until it is restored",
+                        AGENTS_FILENAME,
+                        parent_dir.display()
+                    );
+                }
+                sync_aider_config_if_enabled(parent_dir)?;
+            }
+            if path.is_file() && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                render_aliases(parent_dir)?;
+            }
+            continue;
+        }
+
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+            && symlinked_aliases(parent_dir)?.contains(&file_name)
+        {
+            repair_replaced_alias(parent_dir, file_name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Brings the symlinked aliases in line with AGENTS.md after it changed.
+///
+/// While AGENTS.md has conditional sections, each alias is rendered again for its
+/// agent; once they are gone, the aliases become symlinks again. Aliases that a
+/// tool replaced with its own file are left to the repair.
+fn render_aliases(dir: &Path) -> io::Result<()> {
+    for alias in symlinked_aliases(dir)? {
+        let alias_path = dir.join(alias);
+        let replaced = fs::symlink_metadata(&alias_path)
+            .is_ok_and(|metadata| metadata.file_type().is_file())
+            && !fs::read_to_string(&alias_path).is_ok_and(|content| is_rendered_alias(&content));
+        if replaced || !sync_symlinked_alias(dir, alias)? {
+            continue;
+        }
+        if fs::symlink_metadata(&alias_path).is_ok_and(|metadata| metadata.file_type().is_file()) {
+            println!(
+                "Rendered {} from {} in {}",
+                alias,
+                AGENTS_FILENAME,
+                dir.display()
+            );
+        } else {
+            println!(
+                "Restored {} symlink to {} in {}",
+                alias,
+                AGENTS_FILENAME,
+                dir.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Repairs an alias that was replaced by a regular file and logs every action.
+fn repair_replaced_alias(dir: &Path, alias: &str) -> io::Result<()> {
+    let repair = match repair_alias(dir, alias)? {
+        Some(repair) => repair,
+        None => return Ok(()),
+    };
+
+    println!(
+        "{} in {} was replaced by a regular file",
+        alias,
+        dir.display()
+    );
+    println!("Preserved its content in {}", repair.backup_path.display());
+    if repair.merged {
+        println!(
+            "Appended new content from {} to {} between review markers",
+            alias, AGENTS_FILENAME
+        );
+    } else {
+        println!("No new content to merge into {}", AGENTS_FILENAME);
+    }
+    println!("Restored {} symlink to {}", alias, AGENTS_FILENAME);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::RULES_DIR;
+    use notify::event::{CreateKind, DataChange};
+    use tempfile::tempdir;
+
+    fn rules_paths_for(dir: &Path) -> HashMap<PathBuf, PathBuf> {
+        let rules_path = dir.join(RULES_DIR);
+        fs::create_dir_all(&rules_path).unwrap();
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(rules_path.canonicalize().unwrap(), dir.to_path_buf());
+        rules_paths
+    }
+
+    #[test]
+    fn test_replaced_alias_is_repaired() {
+        let dir = tempdir().unwrap();
+        let rules_paths = rules_paths_for(dir.path());
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Agents\n").unwrap();
+        fs::write(root.join(CLAUDE_FILENAME), "# Agents\nClaude notes\n").unwrap();
+
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![root.join(CLAUDE_FILENAME)],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+
+        assert!(fs::symlink_metadata(root.join(CLAUDE_FILENAME))
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(fs::read_to_string(root.join(AGENTS_FILENAME))
+            .unwrap()
+            .contains("Claude notes"));
+    }
+
+    #[test]
+    fn test_alias_created_and_written_in_two_steps_is_repaired() {
+        let dir = tempdir().unwrap();
+        let rules_paths = rules_paths_for(dir.path());
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Agents\n").unwrap();
+        let claude_path = root.join(CLAUDE_FILENAME);
+
+        // The tool creates the file first and writes its content afterwards
+        fs::write(&claude_path, "").unwrap();
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![claude_path.clone()],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+        assert!(fs::symlink_metadata(&claude_path).unwrap().is_file());
+        assert!(!root.join(crate::constants::KNOWN_STATE_DIR).exists());
+
+        fs::write(&claude_path, "written by tool\n").unwrap();
+        let event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            paths: vec![claude_path.clone()],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+        assert!(fs::symlink_metadata(&claude_path)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert!(fs::read_to_string(root.join(AGENTS_FILENAME))
+            .unwrap()
+            .contains("written by tool"));
+        let backups = crate::repair::alias_backups(&root, CLAUDE_FILENAME);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&backups[0]).unwrap(),
+            "written by tool\n"
+        );
+    }
+
+    #[test]
+    fn test_alias_with_other_strategy_is_left_alone() {
+        let dir = tempdir().unwrap();
+        let rules_paths = rules_paths_for(dir.path());
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Agents\n").unwrap();
+        fs::write(
+            root.join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"aliases": {"gemini": "settings"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join(GEMINI_FILENAME), "Gemini only").unwrap();
+
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![root.join(GEMINI_FILENAME), root.join("README.md")],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join(GEMINI_FILENAME)).unwrap(),
+            "Gemini only"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join(AGENTS_FILENAME)).unwrap(),
+            "# Agents\n"
+        );
+    }
+
+    #[test]
+    fn test_aliases_are_rendered_when_agents_file_changes() {
+        let dir = tempdir().unwrap();
+        let rules_paths = rules_paths_for(dir.path());
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(
+            root.join(AGENTS_FILENAME),
+            "# Agents
+<!-- known:only gemini -->
+Gemini only
+<!-- known:end -->
+",
+        )
+        .unwrap();
+        fs::write(root.join(CLAUDE_FILENAME), "# Notes from a tool\n").unwrap();
+        crate::symlinks::create_platform_symlink(
+            Path::new(AGENTS_FILENAME),
+            &root.join(GEMINI_FILENAME),
+        )
+        .unwrap();
+
+        let event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            paths: vec![root.join(AGENTS_FILENAME)],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+
+        let gemini = fs::read_to_string(root.join(GEMINI_FILENAME)).unwrap();
+        assert!(is_rendered_alias(&gemini));
+        assert!(gemini.ends_with("# Agents\nGemini only\n"));
+        // A file a tool wrote over the alias is left to the repair
+        assert_eq!(
+            fs::read_to_string(root.join(CLAUDE_FILENAME)).unwrap(),
+            "# Notes from a tool\n"
+        );
+
+        // The rendered copy is not mistaken for a replaced alias
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![root.join(GEMINI_FILENAME)],
+            attrs: Default::default(),
+        };
+        handle_instruction_file_event(&event, &rules_paths).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(GEMINI_FILENAME)).unwrap(),
+            gemini
+        );
+        assert!(!root.join(crate::constants::KNOWN_STATE_DIR).exists());
+    }
+}
@@ -1,15 +1,14 @@
 //! Configuration file change handling for the daemon.
 
+use std::collections::HashMap;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 
 use crate::config::load_config;
 #[cfg(test)]
 use crate::config::load_config_from_file;
 use crate::constants::RULES_DIR;
-#[cfg(test)]
-use std::collections::HashMap;
 
 use super::symlinks::remove_symlinks_from_directory;
 use super::watchers::{setup_directory_watchers, WatcherSetup};
@@ -116,6 +115,17 @@ pub fn handle_config_file_change_internal(
             } else {
                 println!("Removed symlinks from {}", removed_dir.display());
             }
+
+            // Nested packages of a recursive project are released with it
+            for package in remove_packages(removed_dir, &mut watcher_setup.rules_paths) {
+                if let Err(e) = remove_symlinks_from_directory(&package) {
+                    eprintln!(
+                        "Failed to remove symlinks from {}: {}",
+                        package.display(),
+                        e
+                    );
+                }
+            }
         }
     }
 
@@ -130,6 +140,24 @@ pub fn handle_config_file_change_internal(
     Ok(())
 }
 
+/// Stops tracking the nested packages of a removed project.
+///
+/// # Returns
+///
+/// Returns the package directories that were tracked.
+fn remove_packages(dir: &Path, rules_paths: &mut HashMap<PathBuf, PathBuf>) -> Vec<PathBuf> {
+    let root = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let mut packages = Vec::new();
+    rules_paths.retain(|rules_path, package| {
+        let inside = rules_path.starts_with(&root) && rules_path.parent() != Some(root.as_path());
+        if inside {
+            packages.push(package.clone());
+        }
+        !inside
+    });
+    packages
+}
+
 /// Test version of handle_config_file_change_internal that loads from a specific config file
 #[cfg(test)]
 pub fn handle_config_file_change_with_file(
@@ -210,6 +238,17 @@ pub fn handle_config_file_change_with_file(
             } else {
                 println!("Removed symlinks from {}", removed_dir.display());
             }
+
+            // Nested packages of a recursive project are released with it
+            for package in remove_packages(removed_dir, &mut watcher_setup.rules_paths) {
+                if let Err(e) = remove_symlinks_from_directory(&package) {
+                    eprintln!(
+                        "Failed to remove symlinks from {}: {}",
+                        package.display(),
+                        e
+                    );
+                }
+            }
         }
     }
 
@@ -249,7 +288,7 @@ mod tests {
 
         // Set up initial config and watcher setup
         let mut config = crate::config::Config::new();
-        config.add_directory(initial_dir.path().to_path_buf());
+        config.add_directory(initial_dir.path());
 
         let mut watched_directories = std::collections::HashSet::new();
         watched_directories.insert(initial_dir.path().to_path_buf());
@@ -259,6 +298,7 @@ mod tests {
             rules_paths: HashMap::new(),
             event_receiver: mpsc::channel().1,
             config_file_path: std::env::temp_dir().join("test_config.json"),
+            global_rules_path: None,
         };
 
         // Add the initial directory to rules_paths
@@ -268,7 +308,7 @@ mod tests {
         );
 
         // Mock the new config by adding the new directory
-        config.add_directory(new_dir.path().to_path_buf());
+        config.add_directory(new_dir.path());
 
         // Temporarily save the new config to a file
         let temp_config_file = tempdir().unwrap();
@@ -290,7 +330,7 @@ mod tests {
         let new_dir_canonical = new_dir.path().canonicalize().unwrap();
         let contains_new_dir = watched_directories.iter().any(|dir| {
             dir.canonicalize()
-                .map_or(false, |canonical| canonical == new_dir_canonical)
+                .is_ok_and(|canonical| canonical == new_dir_canonical)
         });
         assert!(
             contains_new_dir,
@@ -328,8 +368,8 @@ mod tests {
 
         // Set up initial config with both directories
         let mut config = crate::config::Config::new();
-        config.add_directory(dir1.path().to_path_buf());
-        config.add_directory(dir2.path().to_path_buf());
+        config.add_directory(dir1.path());
+        config.add_directory(dir2.path());
 
         let mut watched_directories = std::collections::HashSet::new();
         watched_directories.insert(dir1.path().to_path_buf());
@@ -340,6 +380,7 @@ mod tests {
             rules_paths: HashMap::new(),
             event_receiver: mpsc::channel().1,
             config_file_path: std::env::temp_dir().join("test_config.json"),
+            global_rules_path: None,
         };
 
         // Add both directories to rules_paths
@@ -358,7 +399,7 @@ mod tests {
 
         // Create new config with only dir2 (removing dir1)
         let mut new_config = crate::config::Config::new();
-        new_config.add_directory(dir2.path().to_path_buf());
+        new_config.add_directory(dir2.path());
 
         // Temporarily save the new config to a file
         let temp_config_file = tempdir().unwrap();
@@ -382,11 +423,11 @@ mod tests {
 
         let contains_dir1 = watched_directories.iter().any(|dir| {
             dir.canonicalize()
-                .map_or(false, |canonical| canonical == dir1_canonical)
+                .is_ok_and(|canonical| canonical == dir1_canonical)
         });
         let contains_dir2 = watched_directories.iter().any(|dir| {
             dir.canonicalize()
-                .map_or(false, |canonical| canonical == dir2_canonical)
+                .is_ok_and(|canonical| canonical == dir2_canonical)
         });
 
         assert!(
@@ -414,7 +455,7 @@ mod tests {
         fs::create_dir(&rules_path).unwrap();
 
         let mut config = crate::config::Config::new();
-        config.add_directory(dir.path().to_path_buf());
+        config.add_directory(dir.path());
 
         let mut watched_directories = std::collections::HashSet::new();
         watched_directories.insert(dir.path().to_path_buf());
@@ -424,6 +465,7 @@ mod tests {
             rules_paths: HashMap::new(),
             event_receiver: mpsc::channel().1,
             config_file_path: std::env::temp_dir().join("test_config.json"),
+            global_rules_path: None,
         };
 
         // Create an invalid config file to trigger load_config failure
@@ -448,7 +490,7 @@ mod tests {
         let contains_dir = watched_directories.iter().any(|watched_dir| {
             watched_dir
                 .canonicalize()
-                .map_or(false, |canonical| canonical == dir_canonical)
+                .is_ok_and(|canonical| canonical == dir_canonical)
         });
         assert!(
             contains_dir,
@@ -0,0 +1,142 @@
+//! Handles file system events that create directories the daemon has to watch.
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use super::package_event::recursive_root;
+use super::symlinks::{project_targets, sync_project_targets};
+use super::watchers::watch_target_dirs;
+use crate::monorepo::{is_ignored, list_project_dirs};
+use crate::rules::TargetFormat;
+
+/// Handles a file system event by watching directories created after setup.
+///
+/// Watches are not recursive, so directories created later are not watched on
+/// their own:
+///
+/// - In a project in recursive mode, a new directory that Git doesn't ignore is
+///   watched along with its subdirectories, so packages created inside it are noticed
+/// - A symlink target directory (or one of its parents) created in a tracked
+///   project is watched, and rules already in it are adopted
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `watchers` - The daemon's watchers; the first one is used to watch new directories
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be watched or a project cannot be synced
+///
+pub fn handle_directory_event(
+    event: &Event,
+    watchers: &mut [RecommendedWatcher],
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+    ) {
+        return Ok(());
+    }
+    let watcher = match watchers.first_mut() {
+        Some(watcher) => watcher,
+        None => return Ok(()),
+    };
+
+    for path in &event.paths {
+        if !path.is_dir() {
+            continue;
+        }
+
+        if let Some(root) = recursive_root(path, rules_paths) {
+            if !is_ignored(&root, path) {
+                watcher
+                    .watch(path, RecursiveMode::NonRecursive)
+                    .map_err(io::Error::other)?;
+                for dir in list_project_dirs(path) {
+                    if !is_ignored(&root, &dir) {
+                        watcher
+                            .watch(&dir, RecursiveMode::NonRecursive)
+                            .map_err(io::Error::other)?;
+                    }
+                }
+            }
+        }
+
+        for (rules_path, project_dir) in rules_paths {
+            let project_root = match rules_path.parent() {
+                Some(root) => root,
+                None => continue,
+            };
+            if path == project_root || !path.starts_with(project_root) {
+                continue;
+            }
+            let creates_target = project_targets(project_dir).iter().any(|target| {
+                target.format == TargetFormat::Symlink
+                    && target.path(project_root).starts_with(path)
+            });
+            if creates_target {
+                watch_target_dirs(watcher, project_dir)?;
+                sync_project_targets(project_dir)?;
+                println!(
+                    "Watching new target directory {}",
+                    path.strip_prefix(project_root).unwrap_or(path).display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{CURSOR_RULES_DIR, RULES_DIR};
+    use notify::event::CreateKind;
+    use notify::Config;
+    use std::fs;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_target_directory_created_later_is_watched() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+
+        let (tx, rx) = mpsc::channel();
+        let mut watchers = vec![RecommendedWatcher::new(tx, Config::default()).unwrap()];
+
+        // Cursor creates its rules directory, with a rule in it, after setup
+        let cursor_rules_path = root.join(CURSOR_RULES_DIR);
+        fs::create_dir_all(&cursor_rules_path).unwrap();
+        fs::write(cursor_rules_path.join("style.mdc"), "Use tabs").unwrap();
+        let event = Event {
+            kind: EventKind::Create(CreateKind::Folder),
+            paths: vec![root.join(".cursor")],
+            attrs: Default::default(),
+        };
+        handle_directory_event(&event, &mut watchers, &rules_paths).unwrap();
+
+        // The rule already there is adopted
+        assert!(root.join(RULES_DIR).join("style.mdc").is_file());
+
+        // Rules created from then on are seen
+        while rx.try_recv().is_ok() {}
+        fs::write(cursor_rules_path.join("new.mdc"), "New rule").unwrap();
+        let seen = rx
+            .recv_timeout(Duration::from_secs(2))
+            .unwrap()
+            .unwrap()
+            .paths;
+        assert_eq!(seen, vec![cursor_rules_path.join("new.mdc")]);
+    }
+}
@@ -1,8 +1,16 @@
 //! File system event handling for the daemon.
 
+use super::alias_event::handle_instruction_file_event;
 use super::config_handler::handle_config_file_change_internal;
+use super::directory_event::handle_directory_event;
 use super::file_event::handle_file_event;
+use super::global_event::{handle_global_agents_event, handle_global_rules_event};
+use super::local_event::handle_local_event;
+use super::package_event::handle_package_event;
+use super::project_event::handle_project_config_event;
+use super::target_event::handle_target_event;
 use super::watchers::WatcherSetup;
+use crate::constants::AGENTS_FILENAME;
 use crate::daemon::config_event::is_config_file_event;
 use std::io;
 use std::path::PathBuf;
@@ -39,8 +47,58 @@ pub fn run_daemon_event_loop(
                     ) {
                         eprintln!("Error handling config file change: {}", e);
                     }
-                } else if let Err(e) = handle_file_event(&event, &watcher_setup.rules_paths) {
-                    eprintln!("Error handling file event: {}", e);
+                } else {
+                    if let Err(e) = handle_file_event(&event, &watcher_setup.rules_paths) {
+                        eprintln!("Error handling file event: {}", e);
+                    }
+                    if let Err(e) = handle_target_event(&event, &watcher_setup.rules_paths) {
+                        eprintln!("Error handling target directory event: {}", e);
+                    }
+                    if let Err(e) =
+                        handle_instruction_file_event(&event, &watcher_setup.rules_paths)
+                    {
+                        eprintln!("Error handling instruction file event: {}", e);
+                    }
+                    if let Err(e) = handle_directory_event(
+                        &event,
+                        &mut watcher_setup.watchers,
+                        &watcher_setup.rules_paths,
+                    ) {
+                        eprintln!("Error handling new directory event: {}", e);
+                    }
+                    if let Err(e) = handle_package_event(
+                        &event,
+                        &mut watcher_setup.watchers,
+                        &mut watcher_setup.rules_paths,
+                    ) {
+                        eprintln!("Error handling package event: {}", e);
+                    }
+                    if let Err(e) = handle_project_config_event(&event, &watcher_setup.rules_paths)
+                    {
+                        eprintln!("Error handling project configuration event: {}", e);
+                    }
+                    if let Err(e) = handle_local_event(
+                        &event,
+                        &mut watcher_setup.watchers,
+                        &watcher_setup.rules_paths,
+                    ) {
+                        eprintln!("Error handling local override event: {}", e);
+                    }
+                    let global_agents_path = watcher_setup
+                        .config_file_path
+                        .with_file_name(AGENTS_FILENAME);
+                    if let Err(e) = handle_global_agents_event(&event, &global_agents_path) {
+                        eprintln!("Error handling global AGENTS.md event: {}", e);
+                    }
+                    if let Some(global_rules_path) = &watcher_setup.global_rules_path {
+                        if let Err(e) = handle_global_rules_event(
+                            &event,
+                            global_rules_path,
+                            &watcher_setup.rules_paths,
+                        ) {
+                            eprintln!("Error handling global rules event: {}", e);
+                        }
+                    }
                 }
             }
             Ok(Err(e)) => {
@@ -77,7 +135,7 @@ mod tests {
         fs::create_dir(&rules_path).unwrap();
 
         let mut config = crate::config::Config::new();
-        config.add_directory(dir.path().to_path_buf());
+        config.add_directory(dir.path());
 
         let mut watched_directories = config.get_watched_directories().clone();
 
@@ -91,6 +149,7 @@ mod tests {
             rules_paths,
             event_receiver: rx,
             config_file_path: std::env::temp_dir().join("test_config.json"),
+            global_rules_path: None,
         };
 
         // Create shutdown channel and immediately send shutdown signal
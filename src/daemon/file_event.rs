@@ -1,16 +1,31 @@
 //! Handles file system events for the daemon.
 
-use crate::constants::{CURSOR_RULES_DIR, WINDSURF_RULES_DIR};
-use crate::symlinks::create_symlink_to_file;
+use crate::aider::sync_aider_config_if_enabled;
+use crate::constants::RULES_DIR;
+use crate::daemon::symlinks::{project_targets, sync_project_targets};
+use crate::monorepo::{inheriting_root, rules_chain};
+use crate::rules::RulesTarget;
 use notify::event::{ModifyKind, RenameMode};
 use notify::{Event, EventKind};
 use std::collections::HashMap;
-use std::fs;
+use std::ffi::OsStr;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Handles a file system event by updating symlinks in target directories.
 ///
+/// Every target enabled for the affected project is updated: symlink targets
+/// get a link to the rule, and Kiro steering files are re-rendered. The entries
+/// always come from the rule that wins in the project's [`rules_chain`], so a
+/// rule in `.rules.local` keeps precedence when the team rule changes.
+///
+/// When files are added, removed or renamed, the Aider configuration of the
+/// affected project is also refreshed if the Aider target is enabled.
+///
+/// In a project that inherits rules, a change to any `.rules` directory (the
+/// root's, a package's or one in between) resyncs every tracked project below
+/// it, so that overrides and fallbacks to farther rules are picked up.
+///
 /// # Arguments
 ///
 /// * `event` - The file system event to handle
@@ -22,10 +37,31 @@ use std::path::PathBuf;
 ///
 pub fn handle_file_event(event: &Event, rules_paths: &HashMap<PathBuf, PathBuf>) -> io::Result<()> {
     for path in &event.paths {
-        // Find which rules directory this event belongs to
+        if let Some(projects) = inheriting_projects(path, rules_paths) {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                for project in &projects {
+                    sync_project_targets(project)?;
+                    sync_aider_config_if_enabled(project)?;
+                }
+                if let Some(file_name) = path.file_name() {
+                    println!(
+                        "Resynced {} project(s) inheriting {}",
+                        projects.len(),
+                        file_name.to_string_lossy()
+                    );
+                }
+            }
+            continue;
+        }
+
+        // Find which rules directory this event belongs to; files in its
+        // subdirectories (seen by recursive watchers) are not rules
         let (_rules_path, parent_dir) = match rules_paths
             .iter()
-            .find(|(rules_path, _)| path.starts_with(rules_path))
+            .find(|(rules_path, _)| path.parent() == Some(rules_path.as_path()))
         {
             Some((rules_path, parent_dir)) => (rules_path, parent_dir),
             None => continue, // Event not in any watched directory
@@ -36,70 +72,49 @@ pub fn handle_file_event(event: &Event, rules_paths: &HashMap<PathBuf, PathBuf>)
             None => continue,
         };
 
-        let cursor_rules_path = parent_dir.join(CURSOR_RULES_DIR);
-        let windsurf_rules_path = parent_dir.join(WINDSURF_RULES_DIR);
-        let cursor_target = cursor_rules_path.join(file_name);
-        let windsurf_target = windsurf_rules_path.join(file_name);
+        let targets = project_targets(parent_dir);
 
         match event.kind {
-            EventKind::Create(_) => {
-                // Create symlinks for new files
-                if path.is_file() {
-                    create_symlink_to_file(path, &cursor_target)?;
-                    create_symlink_to_file(path, &windsurf_target)?;
-                    println!(
-                        "Created symlinks for {} in {}",
-                        file_name.to_string_lossy(),
-                        parent_dir.display()
-                    );
-                }
+            EventKind::Create(_) if path.is_file() => {
+                // Create entries for new files
+                sync_winning_rule(parent_dir, &targets, file_name, false)?;
+                println!(
+                    "Created symlinks for {} in {}",
+                    file_name.to_string_lossy(),
+                    parent_dir.display()
+                );
             }
             EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
-                // File is being renamed FROM this name - remove old symlinks
-                if cursor_target.exists() {
-                    fs::remove_file(&cursor_target)?;
-                }
-                if windsurf_target.exists() {
-                    fs::remove_file(&windsurf_target)?;
-                }
+                // File is being renamed FROM this name - remove old entries
+                sync_winning_rule(parent_dir, &targets, file_name, true)?;
                 println!(
                     "Removed symlinks for renamed file {} in {}",
                     file_name.to_string_lossy(),
                     parent_dir.display()
                 );
             }
-            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
-                // File is being renamed TO this name - create new symlinks
-                if path.is_file() {
-                    create_symlink_to_file(path, &cursor_target)?;
-                    create_symlink_to_file(path, &windsurf_target)?;
-                    println!(
-                        "Created symlinks for renamed file {} in {}",
-                        file_name.to_string_lossy(),
-                        parent_dir.display()
-                    );
-                }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) if path.is_file() => {
+                // File is being renamed TO this name - create new entries
+                sync_winning_rule(parent_dir, &targets, file_name, false)?;
+                println!(
+                    "Created symlinks for renamed file {} in {}",
+                    file_name.to_string_lossy(),
+                    parent_dir.display()
+                );
             }
-            EventKind::Modify(_) => {
-                // Other modifications (content changes, metadata) - update symlinks if file exists
-                if path.is_file() {
-                    create_symlink_to_file(path, &cursor_target)?;
-                    create_symlink_to_file(path, &windsurf_target)?;
-                    println!(
-                        "Updated symlinks for {} in {}",
-                        file_name.to_string_lossy(),
-                        parent_dir.display()
-                    );
-                }
+            EventKind::Modify(_) if path.is_file() => {
+                // Other modifications (content changes, metadata) - update entries if file exists.
+                // Rendered targets such as Kiro steering files pick up the new content here.
+                sync_winning_rule(parent_dir, &targets, file_name, false)?;
+                println!(
+                    "Updated symlinks for {} in {}",
+                    file_name.to_string_lossy(),
+                    parent_dir.display()
+                );
             }
             EventKind::Remove(_) => {
-                // Remove symlinks
-                if cursor_target.exists() {
-                    fs::remove_file(&cursor_target)?;
-                }
-                if windsurf_target.exists() {
-                    fs::remove_file(&windsurf_target)?;
-                }
+                // Remove entries
+                sync_winning_rule(parent_dir, &targets, file_name, true)?;
                 println!(
                     "Removed symlinks for {} in {}",
                     file_name.to_string_lossy(),
@@ -110,14 +125,81 @@ pub fn handle_file_event(event: &Event, rules_paths: &HashMap<PathBuf, PathBuf>)
                 // Ignore other event types
             }
         }
+
+        // Files were added, removed, renamed or retargeted through their
+        // frontmatter - keep the Aider read list in sync
+        if matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_)
+        ) {
+            sync_aider_config_if_enabled(parent_dir)?;
+        }
     }
     Ok(())
 }
 
+/// Updates the entries for a rule name from the rule that wins in the project's
+/// [`rules_chain`], so that a rule in `.rules.local` keeps overriding the team rule
+/// of the same name.
+///
+/// When the event removed the rule from the project's `.rules`, that directory is
+/// skipped, and the entries are removed if no other rules directory has the name.
+fn sync_winning_rule(
+    parent_dir: &Path,
+    targets: &[&RulesTarget],
+    file_name: &OsStr,
+    removed: bool,
+) -> io::Result<()> {
+    let own_rules_path = parent_dir.join(RULES_DIR);
+    let winning_rule = rules_chain(parent_dir)
+        .into_iter()
+        .filter(|rules_path| !(removed && *rules_path == own_rules_path))
+        .map(|rules_path| rules_path.join(file_name))
+        .find(|rule| rule.is_file());
+    for target in targets {
+        match &winning_rule {
+            Some(rule) => target.sync_rule(parent_dir, rule)?,
+            None => target.remove_rule(parent_dir, file_name)?,
+        }
+    }
+    Ok(())
+}
+
+/// Finds the tracked projects that inherit the rules directory a path is in.
+///
+/// Returns `None` if the path isn't directly in a `.rules` directory of a project
+/// that inherits rules, and the projects at or below that directory otherwise.
+fn inheriting_projects(
+    path: &Path,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> Option<Vec<PathBuf>> {
+    let rules_dir = path.parent()?;
+    if rules_dir.file_name()? != RULES_DIR {
+        return None;
+    }
+    let owner = rules_dir.parent()?;
+    inheriting_root(owner)?;
+
+    let mut projects: Vec<PathBuf> = rules_paths
+        .iter()
+        .filter(|(rules_path, _)| {
+            rules_path
+                .parent()
+                .is_some_and(|project| project.starts_with(owner))
+        })
+        .map(|(_, project_dir)| project_dir.clone())
+        .collect();
+    projects.sort();
+    Some(projects)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{CURSOR_RULES_DIR, RULES_DIR, WINDSURF_RULES_DIR};
+    use crate::constants::{
+        AMAZONQ_RULES_DIR, CURSOR_RULES_DIR, KIRO_STEERING_DIR, PROJECT_CONFIG_FILENAME, RULES_DIR,
+        WINDSURF_RULES_DIR,
+    };
     use crate::symlinks::create_symlink_to_file;
     use notify::{
         event::{ModifyKind, RenameMode},
@@ -466,4 +548,173 @@ mod tests {
         let result = handle_file_event(&access_event, &rules_paths);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_handle_file_event_updates_optional_targets() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(
+            dir.path().join(PROJECT_CONFIG_FILENAME),
+            r#"{"targets": ["kiro", "amazonq"]}"#,
+        )
+        .unwrap();
+
+        let test_file = rules_path.join("python.mdc");
+        fs::write(&test_file, "---\nglobs: \"*.py\"\n---\nUse type hints.\n").unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(rules_path.canonicalize().unwrap(), dir.path().to_path_buf());
+
+        let create_event = Event {
+            kind: EventKind::Create(notify::event::CreateKind::File),
+            paths: vec![test_file.canonicalize().unwrap()],
+            attrs: Default::default(),
+        };
+        handle_file_event(&create_event, &rules_paths).unwrap();
+
+        let steering_file = dir.path().join(KIRO_STEERING_DIR).join("python.md");
+        let amazonq_link = dir.path().join(AMAZONQ_RULES_DIR).join("python.mdc");
+        assert!(fs::read_to_string(&steering_file)
+            .unwrap()
+            .contains("fileMatchPattern: \"*.py\""));
+        assert!(fs::symlink_metadata(&amazonq_link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        // Content changes are re-rendered into the steering file
+        fs::write(&test_file, "Always use type hints.\n").unwrap();
+        let modify_event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(notify::event::DataChange::Content)),
+            paths: vec![test_file.canonicalize().unwrap()],
+            attrs: Default::default(),
+        };
+        handle_file_event(&modify_event, &rules_paths).unwrap();
+        let content = fs::read_to_string(&steering_file).unwrap();
+        assert!(content.starts_with("---\ninclusion: always\n"));
+        assert!(content.ends_with("Always use type hints.\n"));
+
+        let remove_path = test_file.canonicalize().unwrap();
+        fs::remove_file(&test_file).unwrap();
+        let remove_event = Event {
+            kind: EventKind::Remove(notify::event::RemoveKind::File),
+            paths: vec![remove_path],
+            attrs: Default::default(),
+        };
+        handle_file_event(&remove_event, &rules_paths).unwrap();
+        assert!(!steering_file.exists());
+        assert!(fs::symlink_metadata(&amazonq_link).is_err());
+    }
+
+    #[test]
+    fn test_handle_file_event_with_inherited_rules() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let package = root.join("packages/web");
+        fs::create_dir_all(root.join(RULES_DIR)).unwrap();
+        fs::create_dir_all(package.join(RULES_DIR)).unwrap();
+        fs::write(
+            root.join(PROJECT_CONFIG_FILENAME),
+            r#"{"recursive": true, "inherit": true}"#,
+        )
+        .unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+        rules_paths.insert(package.join(RULES_DIR), package.clone());
+
+        let handle = |kind: EventKind, path: PathBuf| {
+            let event = Event {
+                kind,
+                paths: vec![path],
+                attrs: Default::default(),
+            };
+            handle_file_event(&event, &rules_paths).unwrap();
+        };
+        let package_link = package.join(CURSOR_RULES_DIR).join("style.md");
+
+        // A root rule reaches the package
+        let root_rule = root.join(RULES_DIR).join("style.md");
+        fs::write(&root_rule, "Use tabs.").unwrap();
+        handle(
+            EventKind::Create(notify::event::CreateKind::File),
+            root_rule.clone(),
+        );
+        assert_eq!(fs::read_link(&package_link).unwrap(), root_rule);
+        assert!(root.join(CURSOR_RULES_DIR).join("style.md").exists());
+
+        // A package rule with the same name overrides it
+        let package_rule = package.join(RULES_DIR).join("style.md");
+        fs::write(&package_rule, "Use spaces.").unwrap();
+        handle(
+            EventKind::Create(notify::event::CreateKind::File),
+            package_rule.clone(),
+        );
+        assert_eq!(fs::read_to_string(&package_link).unwrap(), "Use spaces.");
+        assert_eq!(
+            fs::read_to_string(root.join(CURSOR_RULES_DIR).join("style.md")).unwrap(),
+            "Use tabs."
+        );
+
+        // Removing the override falls back to the root rule
+        fs::remove_file(&package_rule).unwrap();
+        handle(
+            EventKind::Remove(notify::event::RemoveKind::File),
+            package_rule,
+        );
+        assert_eq!(fs::read_to_string(&package_link).unwrap(), "Use tabs.");
+
+        // Removing the root rule removes it everywhere
+        fs::remove_file(&root_rule).unwrap();
+        handle(
+            EventKind::Remove(notify::event::RemoveKind::File),
+            root_rule,
+        );
+        assert!(fs::symlink_metadata(&package_link).is_err());
+    }
+
+    #[test]
+    fn test_team_rule_events_keep_local_override() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let team_rule = root.join(RULES_DIR).join("style.md");
+        let local_rule = root
+            .join(crate::constants::RULES_LOCAL_DIR)
+            .join("style.md");
+        fs::create_dir_all(team_rule.parent().unwrap()).unwrap();
+        fs::create_dir_all(local_rule.parent().unwrap()).unwrap();
+        fs::write(&team_rule, "Team style").unwrap();
+        fs::write(&local_rule, "My style").unwrap();
+        sync_project_targets(&root).unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+        let handle = |kind, path: PathBuf| {
+            let event = Event {
+                kind,
+                paths: vec![path],
+                attrs: Default::default(),
+            };
+            handle_file_event(&event, &rules_paths).unwrap();
+        };
+        let link = root.join(CURSOR_RULES_DIR).join("style.md");
+        assert_eq!(fs::read_link(&link).unwrap(), local_rule);
+
+        // Editing the team rule doesn't take over the local override
+        fs::write(&team_rule, "Team style, edited").unwrap();
+        handle(
+            EventKind::Modify(ModifyKind::Data(notify::event::DataChange::Content)),
+            team_rule.clone(),
+        );
+        assert_eq!(fs::read_link(&link).unwrap(), local_rule);
+
+        // Neither does removing it
+        fs::remove_file(&team_rule).unwrap();
+        handle(
+            EventKind::Remove(notify::event::RemoveKind::File),
+            team_rule,
+        );
+        assert_eq!(fs::read_link(&link).unwrap(), local_rule);
+    }
 }
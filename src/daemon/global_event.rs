@@ -0,0 +1,148 @@
+//! Handles file system events for the global AGENTS.md and rules directory.
+
+use notify::{Event, EventKind};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::symlinks::sync_project_targets_with_global_rules;
+use crate::aider::sync_aider_config_if_enabled;
+use crate::global::sync_user_agent_files;
+
+/// Handles a file system event by syncing a changed global rule to every project.
+///
+/// Adding, changing, renaming or removing a file in the global rules directory
+/// resyncs the targets of every tracked project and package, which links new
+/// global rules, re-renders changed ones and prunes removed ones. The Aider read
+/// list of projects with the Aider target is refreshed as well.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `global_rules_path` - Canonical path of the global rules directory
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if the targets of a project cannot be synced
+///
+pub fn handle_global_rules_event(
+    event: &Event,
+    global_rules_path: &Path,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return Ok(());
+    }
+    let file_name = match event
+        .paths
+        .iter()
+        .find(|path| path.parent() == Some(global_rules_path))
+        .and_then(|path| path.file_name())
+    {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+
+    let mut projects: Vec<&PathBuf> = rules_paths.values().collect();
+    projects.sort();
+    projects.dedup();
+    for project in &projects {
+        sync_project_targets_with_global_rules(project, global_rules_path)?;
+        sync_aider_config_if_enabled(project)?;
+    }
+    println!(
+        "Synced global rule {} to {} project(s)",
+        file_name.to_string_lossy(),
+        projects.len()
+    );
+    Ok(())
+}
+
+/// Handles a file system event by refreshing the user-level agent files.
+///
+/// When the global AGENTS.md is created or changed, copies generated in place of
+/// user-level agent files (see [`crate::global`]) are rewritten. Symlinked files
+/// need no update.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `global_agents_path` - Path of the global AGENTS.md
+///
+/// # Errors
+///
+/// Returns an error if a generated copy cannot be written
+///
+pub fn handle_global_agents_event(event: &Event, global_agents_path: &Path) -> io::Result<()> {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return Ok(());
+    }
+    if event.paths.iter().any(|path| {
+        path.file_name() == global_agents_path.file_name()
+            && path.parent() == global_agents_path.parent()
+    }) {
+        sync_user_agent_files()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{CURSOR_RULES_DIR, RULES_DIR};
+    use notify::event::{CreateKind, RemoveKind};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_global_rule_is_synced_to_every_project() {
+        let global_dir = tempdir().unwrap();
+        let global_rules_path = global_dir.path().canonicalize().unwrap();
+        let project1 = tempdir().unwrap();
+        let project2 = tempdir().unwrap();
+        let mut rules_paths = HashMap::new();
+        for project in [project1.path(), project2.path()] {
+            fs::create_dir(project.join(RULES_DIR)).unwrap();
+            rules_paths.insert(
+                project.join(RULES_DIR).canonicalize().unwrap(),
+                project.to_path_buf(),
+            );
+        }
+
+        let rule_path = global_rules_path.join("commits.md");
+        fs::write(&rule_path, "Use conventional commits.").unwrap();
+        let create_event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![rule_path.clone()],
+            attrs: Default::default(),
+        };
+        handle_global_rules_event(&create_event, &global_rules_path, &rules_paths).unwrap();
+
+        for project in [project1.path(), project2.path()] {
+            assert_eq!(
+                fs::read_to_string(project.join(CURSOR_RULES_DIR).join("global-commits.md"))
+                    .unwrap(),
+                "Use conventional commits."
+            );
+        }
+
+        fs::remove_file(&rule_path).unwrap();
+        let remove_event = Event {
+            kind: EventKind::Remove(RemoveKind::File),
+            paths: vec![rule_path],
+            attrs: Default::default(),
+        };
+        handle_global_rules_event(&remove_event, &global_rules_path, &rules_paths).unwrap();
+        assert!(fs::symlink_metadata(
+            project1
+                .path()
+                .join(CURSOR_RULES_DIR)
+                .join("global-commits.md")
+        )
+        .is_err());
+    }
+}
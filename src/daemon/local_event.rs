@@ -0,0 +1,181 @@
+//! Handles file system events for a project's local override layer.
+
+use crate::config::load_project_config;
+use crate::constants::{AGENTS_LOCAL_FILENAME, RULES_DIR, RULES_LOCAL_DIR};
+use crate::symlinks::relink_aliases;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::symlinks::sync_project_targets;
+
+/// Handles a file system event for AGENTS.local.md or the .rules.local directory.
+///
+/// Changes to the files in .rules.local resync the project's targets so local rules
+/// override team rules of the same name. A .rules.local directory created while the
+/// daemon runs is watched from then on. When AGENTS.local.md is added or removed,
+/// the project's aliases are linked again so they import it or stop doing so.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `watchers` - The daemon's watchers; the first one is used to watch new directories
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if a new directory cannot be watched or the project cannot be synced
+///
+pub fn handle_local_event(
+    event: &Event,
+    watchers: &mut [RecommendedWatcher],
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    let added_or_removed = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    );
+
+    for path in &event.paths {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if file_name == AGENTS_LOCAL_FILENAME {
+            let project = match path
+                .parent()
+                .and_then(|dir| tracked_project(dir, rules_paths))
+            {
+                Some(project) => project,
+                None => continue,
+            };
+            if added_or_removed {
+                relink_aliases(project)?;
+                println!(
+                    "{} {} in {}",
+                    if path.exists() { "Imported" } else { "Dropped" },
+                    AGENTS_LOCAL_FILENAME,
+                    project.display()
+                );
+            }
+        } else if file_name == RULES_LOCAL_DIR {
+            let project = match path
+                .parent()
+                .and_then(|dir| tracked_project(dir, rules_paths))
+            {
+                Some(project) => project,
+                None => continue,
+            };
+            if !added_or_removed {
+                continue;
+            }
+            if path.is_dir() && !covered_by_recursive_watch(path, rules_paths) {
+                if let Some(watcher) = watchers.first_mut() {
+                    watcher
+                        .watch(path, RecursiveMode::NonRecursive)
+                        .map_err(io::Error::other)?;
+                }
+            }
+            sync_project_targets(project)?;
+            println!("Synced local rules in {}", project.display());
+        } else if path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .is_some_and(|name| name == RULES_LOCAL_DIR)
+        {
+            let project = match path
+                .parent()
+                .and_then(|dir| dir.parent())
+                .and_then(|dir| tracked_project(dir, rules_paths))
+            {
+                Some(project) => project,
+                None => continue,
+            };
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                sync_project_targets(project)?;
+                println!("Synced local rule {} in {}", file_name, project.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the tracked project or package whose root is a directory.
+fn tracked_project<'a>(
+    dir: &Path,
+    rules_paths: &'a HashMap<PathBuf, PathBuf>,
+) -> Option<&'a PathBuf> {
+    rules_paths.get(&dir.join(RULES_DIR))
+}
+
+/// Checks whether a path is already inside a project watched in recursive mode.
+fn covered_by_recursive_watch(path: &Path, rules_paths: &HashMap<PathBuf, PathBuf>) -> bool {
+    rules_paths.iter().any(|(rules_path, project_dir)| {
+        rules_path
+            .parent()
+            .is_some_and(|root| path.starts_with(root))
+            && load_project_config(project_dir)
+                .map(|config| config.recursive)
+                .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        AGENTS_FILENAME, CLAUDE_FILENAME, CLAUDE_LOCAL_FILENAME, CURSOR_RULES_DIR,
+    };
+    use notify::event::CreateKind;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_local_rules_and_instructions_are_synced() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Team").unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(RULES_DIR).join("style.md"), "Use tabs.").unwrap();
+        crate::symlinks::create_symlinks_in_dir(&root).unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+
+        let local_rules = root.join(RULES_LOCAL_DIR);
+        fs::create_dir(&local_rules).unwrap();
+        fs::write(local_rules.join("style.md"), "Use spaces.").unwrap();
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![local_rules.join("style.md")],
+            attrs: Default::default(),
+        };
+        handle_local_event(&event, &mut [], &rules_paths).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(CURSOR_RULES_DIR).join("style.md")).unwrap(),
+            "Use spaces."
+        );
+
+        fs::write(root.join(AGENTS_LOCAL_FILENAME), "# Mine").unwrap();
+        let event = Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![root.join(AGENTS_LOCAL_FILENAME)],
+            attrs: Default::default(),
+        };
+        handle_local_event(&event, &mut [], &rules_paths).unwrap();
+        assert_eq!(
+            fs::read_link(root.join(CLAUDE_LOCAL_FILENAME)).unwrap(),
+            Path::new(AGENTS_LOCAL_FILENAME)
+        );
+        assert_eq!(
+            fs::read_link(root.join(CLAUDE_FILENAME)).unwrap(),
+            Path::new(AGENTS_FILENAME)
+        );
+    }
+}
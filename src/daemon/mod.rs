@@ -1,10 +1,17 @@
 //! File watching daemon functionality for managing symlinks in rules directories.
 
+pub mod alias_event;
 pub mod config_event;
 pub mod config_handler;
+pub mod directory_event;
 pub mod events;
 pub mod file_event;
+pub mod global_event;
+pub mod local_event;
+pub mod package_event;
+pub mod project_event;
 pub mod symlinks;
+pub mod target_event;
 pub mod watchers;
 
 use std::io;
@@ -39,6 +46,20 @@ pub use watchers::*;
 /// - Watches each directory's .rules subdirectory for file system events
 /// - Creates symlinks in .cursor/rules and .windsurf/rules for each file in .rules
 /// - Removes symlinks when files are deleted from .rules
+/// - Moves rules created directly in a target directory (for example from Cursor's UI)
+///   into .rules and replaces them with managed links
+/// - Watches each project's root and restores CLAUDE.md and GEMINI.md symlinks that a
+///   tool replaced with a regular file, merging the new content into AGENTS.md
+/// - For projects in recursive mode, also watches every directory of the tree that
+///   Git doesn't ignore, maintains every nested package and links packages added
+///   while the daemon runs
+/// - Watches target directories created after a project was set up
+/// - Resyncs a project's targets when its .known.json changes, for example when
+///   another profile is activated or a rule is disabled
+/// - Mirrors rules from a project's .rules.local directory alongside .rules and links
+///   the aliases again when AGENTS.local.md is added or removed
+/// - Watches the global rules directory next to the configuration file and links its
+///   rules into every watched project as `global-<name>`, excluded from Git
 /// - Runs indefinitely until the receiver channel is closed
 /// - Prints status messages to stdout for user feedback
 /// - Automatically releases the lock when the daemon stops
@@ -210,7 +231,7 @@ mod tests {
         fs::write(rules_path.join("test.md"), "test content").unwrap();
 
         let mut config = crate::config::Config::new();
-        config.add_directory(temp_dir.path().to_path_buf());
+        config.add_directory(temp_dir.path());
 
         let (shutdown_tx, shutdown_rx) = mpsc::channel();
 
@@ -285,7 +306,7 @@ mod tests {
         let config_path = config_dir.path().join("config.json");
 
         let mut config = crate::config::Config::new();
-        config.add_directory(temp_dir.path().to_path_buf());
+        config.add_directory(temp_dir.path());
         crate::config::save_config_to_file(&config, &config_path).unwrap();
 
         let (shutdown_tx, shutdown_rx) = mpsc::channel();
@@ -344,9 +365,9 @@ mod tests {
         fs::write(rules_path3.join("test3.md"), "content3").unwrap();
 
         let mut config = crate::config::Config::new();
-        config.add_directory(temp_dir1.path().to_path_buf());
-        config.add_directory(temp_dir2.path().to_path_buf());
-        config.add_directory(temp_dir3.path().to_path_buf());
+        config.add_directory(temp_dir1.path());
+        config.add_directory(temp_dir2.path());
+        config.add_directory(temp_dir3.path());
 
         let (shutdown_tx, shutdown_rx) = mpsc::channel();
 
@@ -0,0 +1,186 @@
+//! Handles file system events that add packages to a recursive project.
+
+use crate::config::load_project_config;
+use crate::constants::{AGENTS_FILENAME, RULES_DIR};
+use crate::monorepo::{discover_packages, is_ignored};
+use crate::symlinks::link_package;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::watchers::{register_project, watch_project};
+
+/// Handles a file system event by setting up packages added to a recursive project.
+///
+/// When an AGENTS.md file appears in a directory below a project in recursive
+/// mode, or a directory containing packages is created or moved there, each new
+/// package gets its .rules directory and alias links, and is tracked like any
+/// other project from then on. Directories excluded by the project's .gitignore
+/// files are ignored.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `watchers` - The daemon's watchers; the first one is used to watch new packages
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if a package cannot be linked, its targets cannot be synced
+/// or its directories cannot be watched
+///
+pub fn handle_package_event(
+    event: &Event,
+    watchers: &mut [RecommendedWatcher],
+    rules_paths: &mut HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+    ) {
+        return Ok(());
+    }
+
+    for path in &event.paths {
+        let candidates = if path.is_dir() {
+            let mut candidates = discover_packages(path)?;
+            if path.join(AGENTS_FILENAME).is_file() {
+                candidates.insert(0, path.clone());
+            }
+            candidates
+        } else if path.file_name().is_some_and(|name| name == AGENTS_FILENAME) {
+            match path.parent() {
+                Some(parent) => vec![parent.to_path_buf()],
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+
+        for package in candidates {
+            if is_tracked(&package, rules_paths) {
+                continue;
+            }
+            let root = match recursive_root(&package, rules_paths) {
+                Some(root) => root,
+                None => continue,
+            };
+            if is_ignored(&root, &package) {
+                continue;
+            }
+
+            link_package(&package)?;
+            register_project(&package, rules_paths)?;
+            if let Some(watcher) = watchers.first_mut() {
+                watch_project(watcher, &package)?;
+            }
+            println!(
+                "Linked new package {} in {}",
+                package.strip_prefix(&root).unwrap_or(&package).display(),
+                root.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether a directory is already tracked as a project or package.
+fn is_tracked(dir: &Path, rules_paths: &HashMap<PathBuf, PathBuf>) -> bool {
+    rules_paths.contains_key(&dir.join(RULES_DIR))
+        || dir
+            .join(RULES_DIR)
+            .canonicalize()
+            .is_ok_and(|rules_path| rules_paths.contains_key(&rules_path))
+}
+
+/// Finds the outermost tracked project in recursive mode that contains a directory.
+pub(crate) fn recursive_root(
+    dir: &Path,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> Option<PathBuf> {
+    rules_paths
+        .iter()
+        .filter_map(|(rules_path, project_dir)| {
+            let root = rules_path.parent()?;
+            let recursive = load_project_config(project_dir)
+                .map(|config| config.recursive)
+                .unwrap_or(false);
+            (recursive && dir != root && dir.starts_with(root)).then(|| root.to_path_buf())
+        })
+        .min_by_key(|root| root.components().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{save_project_config, ProjectConfig};
+    use crate::constants::{CLAUDE_FILENAME, CURSOR_RULES_DIR};
+    use notify::event::CreateKind;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_new_package_in_recursive_project_is_linked() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Root").unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(".gitignore"), "vendor/\n").unwrap();
+        let mut config = ProjectConfig::new();
+        config.recursive = true;
+        save_project_config(&root, &config).unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+
+        let package = root.join("packages/web");
+        fs::create_dir_all(package.join(RULES_DIR)).unwrap();
+        fs::write(package.join(RULES_DIR).join("style.md"), "Use tabs.").unwrap();
+        fs::write(package.join(AGENTS_FILENAME), "# Web").unwrap();
+        let ignored = root.join("vendor/lib");
+        fs::create_dir_all(&ignored).unwrap();
+        fs::write(ignored.join(AGENTS_FILENAME), "# Vendored").unwrap();
+
+        for path in [package.join(AGENTS_FILENAME), ignored.join(AGENTS_FILENAME)] {
+            let event = Event {
+                kind: EventKind::Create(CreateKind::File),
+                paths: vec![path],
+                attrs: Default::default(),
+            };
+            handle_package_event(&event, &mut [], &mut rules_paths).unwrap();
+        }
+
+        assert_eq!(rules_paths.len(), 2);
+        assert_eq!(
+            fs::read_link(package.join(CLAUDE_FILENAME)).unwrap(),
+            Path::new(AGENTS_FILENAME)
+        );
+        assert!(package.join(CURSOR_RULES_DIR).join("style.md").exists());
+        assert!(!ignored.join(CLAUDE_FILENAME).exists());
+    }
+
+    #[test]
+    fn test_package_outside_recursive_project_is_ignored() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+
+        let package = root.join("packages/web");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join(AGENTS_FILENAME), "# Web").unwrap();
+
+        let event = Event {
+            kind: EventKind::Create(CreateKind::Folder),
+            paths: vec![root.join("packages")],
+            attrs: Default::default(),
+        };
+        handle_package_event(&event, &mut [], &mut rules_paths).unwrap();
+
+        assert_eq!(rules_paths.len(), 1);
+        assert!(!package.join(CLAUDE_FILENAME).exists());
+    }
+}
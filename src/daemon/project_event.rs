@@ -0,0 +1,105 @@
+//! Handles file system events for the `.known.json` of a watched project.
+
+use crate::aider::sync_aider_config_if_enabled;
+use crate::constants::{PROJECT_CONFIG_FILENAME, RULES_DIR};
+use notify::{Event, EventKind};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use super::symlinks::sync_project_targets;
+
+/// Handles a file system event by resyncing a project whose `.known.json` changed.
+///
+/// Switching profiles or editing the `disabled` list changes which rules a
+/// project's targets get, so the targets and the Aider read list are synced
+/// again in a single pass.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if the project's targets cannot be synced
+///
+pub fn handle_project_config_event(
+    event: &Event,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return Ok(());
+    }
+
+    for path in &event.paths {
+        if path
+            .file_name()
+            .is_none_or(|name| name != PROJECT_CONFIG_FILENAME)
+            || !path.is_file()
+        {
+            continue;
+        }
+        let project = match path
+            .parent()
+            .and_then(|dir| rules_paths.get(&dir.join(RULES_DIR)))
+        {
+            Some(project) => project,
+            None => continue,
+        };
+
+        sync_project_targets(project)?;
+        sync_aider_config_if_enabled(project)?;
+        println!(
+            "Resynced rules in {} after {} changed",
+            project.display(),
+            PROJECT_CONFIG_FILENAME
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{load_project_config, save_project_config};
+    use crate::constants::CURSOR_RULES_DIR;
+    use notify::event::{DataChange, ModifyKind};
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_profile_change_relinks_targets() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(RULES_DIR).join("style.md"), "Use tabs.").unwrap();
+        fs::write(root.join(RULES_DIR).join("incident.md"), "Page on-call.").unwrap();
+        sync_project_targets(&root).unwrap();
+
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(root.join(RULES_DIR), root.clone());
+
+        let mut project_config = load_project_config(&root).unwrap();
+        project_config.profiles.insert(
+            "incident".to_string(),
+            crate::config::RuleProfile {
+                rules: vec!["incident".to_string()],
+                tags: Vec::new(),
+            },
+        );
+        project_config.profile = Some("incident".to_string());
+        save_project_config(&root, &project_config).unwrap();
+
+        let event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            paths: vec![root.join(PROJECT_CONFIG_FILENAME)],
+            attrs: Default::default(),
+        };
+        handle_project_config_event(&event, &rules_paths).unwrap();
+
+        let cursor_rules = root.join(CURSOR_RULES_DIR);
+        assert!(fs::symlink_metadata(cursor_rules.join("incident.md")).is_ok());
+        assert!(fs::symlink_metadata(cursor_rules.join("style.md")).is_err());
+    }
+}
@@ -1,17 +1,469 @@
 //! Symlink management operations for the daemon.
 
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::io;
-use std::path::Path;
-
-use crate::constants::{CURSOR_RULES_DIR, WINDSURF_RULES_DIR};
+use std::path::{Path, PathBuf};
+
+use crate::aider::remove_aider_config_block;
+use crate::config::{get_global_rules_dir, load_project_config};
+use crate::constants::{
+    CURSOR_TARGET, GLOBAL_RULE_PREFIX, RULES_DIR, RULES_LOCAL_DIR, RULE_EXTENSIONS, WINDSURF_TARGET,
+};
+use crate::global::{global_file_name, update_git_exclude};
+use crate::monorepo::{inherited_rule_files, rules_chain};
+use crate::repair::{backup_file, read_settled};
+use crate::rules::links::{forget_link, recorded_link};
+use crate::rules::{
+    enabled_targets, is_rule_in_profile, list_rule_files, rule_applies_to, RulesTarget,
+    TargetFormat, BUILTIN_TARGETS,
+};
 use crate::symlinks::create_symlink_to_file;
+use crate::transaction::move_path;
+
+/// Returns the targets enabled for a project.
+///
+/// If the project configuration cannot be loaded, a warning is printed and
+/// only the default targets are returned so that syncing keeps working.
+pub fn project_targets(dir: &Path) -> Vec<&'static RulesTarget> {
+    let config = load_project_config(dir).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Failed to load project configuration in {}: {}",
+            dir.display(),
+            e
+        );
+        Default::default()
+    });
+    enabled_targets(&config)
+}
+
+/// Synchronizes all files in a project's .rules directory to its enabled targets.
+///
+/// Regular files found in symlink target directories are first adopted into
+/// .rules (see [`adopt_target_file`]). Every rule is then linked (or rendered,
+/// for Kiro) into each enabled target directory, and entries managed by known
+/// that no longer have a source file in .rules are removed. Packages that inherit
+/// rules also get the rules of the `.rules` directories above them.
+///
+/// The user's global rules are synced as well, as `global-<name>` entries that are
+/// excluded from Git (see [`crate::global`]).
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory containing the .rules directory
+///
+/// # Errors
+///
+/// Returns an error if the .rules directory cannot be read or a target directory
+/// cannot be created. Failures for individual rules are logged and skipped.
+pub fn sync_project_targets(dir: &Path) -> io::Result<()> {
+    let global_rules_dir = get_global_rules_dir().unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: Failed to locate the global rules directory: {}",
+            e
+        );
+        PathBuf::new()
+    });
+    sync_project_targets_with_global_rules(dir, &global_rules_dir)
+}
+
+/// Synchronizes a project's targets, taking global rules from the given directory.
+pub(crate) fn sync_project_targets_with_global_rules(
+    dir: &Path,
+    global_rules_dir: &Path,
+) -> io::Result<()> {
+    let targets = project_targets(dir);
+
+    // Rules created in a tool's directory while the daemon wasn't running
+    for target in &targets {
+        adopt_unmanaged_files(dir, target)?;
+    }
+
+    let rule_files = inherited_rule_files(dir)?;
+    let global_rules = if global_rules_dir.as_os_str().is_empty() {
+        Vec::new()
+    } else {
+        list_rule_files(global_rules_dir)?
+    };
+    for target in targets {
+        fs::create_dir_all(target.path(dir))?;
+
+        for rule_path in &rule_files {
+            // A single unreadable rule shouldn't prevent the others from syncing
+            if let Err(e) = target.sync_rule(dir, rule_path) {
+                eprintln!(
+                    "Failed to sync {} to {}: {}",
+                    rule_path.display(),
+                    target.dir,
+                    e
+                );
+            }
+        }
+
+        for rule_path in &global_rules {
+            let file_name = match rule_path.file_name() {
+                Some(name) => global_file_name(name),
+                None => continue,
+            };
+            let result = if is_rule_in_profile(dir, rule_path) {
+                let source = rule_path.display().to_string();
+                target.sync_rule_as(dir, rule_path, &file_name, &source)
+            } else {
+                target.remove_rule(dir, &file_name)
+            };
+            if let Err(e) = result {
+                eprintln!(
+                    "Failed to sync global rule {} to {}: {}",
+                    rule_path.display(),
+                    target.dir,
+                    e
+                );
+            }
+        }
+
+        prune_stale_entries(dir, target, &rule_files, global_rules_dir, &global_rules)?;
+    }
+
+    update_git_exclude(dir, !global_rules.is_empty())?;
+    Ok(())
+}
+
+/// Adopts every regular file in a symlink target directory into .rules.
+fn adopt_unmanaged_files(dir: &Path, target: &RulesTarget) -> io::Result<()> {
+    let target_path = target.path(dir);
+    if target.format != TargetFormat::Symlink || !target_path.exists() {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(&target_path)? {
+        let path = entry?.path();
+        if let Err(e) = adopt_target_file(dir, target, &path) {
+            eprintln!("Failed to adopt {} into .rules: {}", path.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether a file in a target directory is a rule rather than a file an
+/// editor keeps next to it.
+///
+/// Dotfiles (such as Vim's `.style.md.swp` or Emacs' `.#style.md`), backups
+/// ending in `~`, `.tmp` files and JetBrains' `___jb_tmp___` files are skipped,
+/// and rules must have one of the [`RULE_EXTENSIONS`].
+fn is_rule_file_name(file_name: &OsStr) -> bool {
+    let name = file_name.to_string_lossy();
+    if name.starts_with('.')
+        || name.ends_with('~')
+        || name.ends_with(".tmp")
+        || name.contains("___jb_")
+    {
+        return false;
+    }
+    Path::new(file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| RULE_EXTENSIONS.contains(&extension))
+}
+
+/// Moves a rule that was created directly in a target directory into .rules.
+///
+/// Tools such as Cursor create rules as regular files in their own directory.
+/// To keep .rules the single source of truth, the file is moved into .rules and
+/// replaced with a managed link in every enabled target.
+///
+/// Editors that save by writing a new file and renaming it over the old one
+/// replace a managed link with a regular file. When the link record shows that
+/// known had linked the file's entry to a rule in the project's own .rules or
+/// .rules.local (see [`crate::rules::links`]), the file's content is written back
+/// to that rule, after backing up the previous content to `.known/backups/`, and
+/// the link is restored. Inherited and global rules are shared with other
+/// projects and are never written back to. Otherwise, if the file has the name of
+/// a rule the project already has:
+///
+/// - identical content is deduplicated, and the link points to the existing rule
+/// - different content is kept in .rules under a name with the target as suffix
+///   (for example `style-cursor.mdc`), so neither version is lost
+///
+/// The file is read once writes to it have stopped, so a tool that deletes the
+/// file and writes a new one isn't caught between the two steps. Empty files are
+/// left in place until they get content; an empty file is never written over a
+/// rule.
+///
+/// Only symlink targets are adopted from, and only `.md` and `.mdc` files; see
+/// [`is_rule_file_name`]. Symlinks and subdirectories are ignored.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `target` - The target whose directory contains the file
+/// * `path` - Path to the file in the target directory
+///
+/// # Returns
+///
+/// Returns the path of the rule the file was adopted as or written back to, or
+/// `None` if the file didn't need adopting.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or moved, or the links cannot be created.
+pub fn adopt_target_file(
+    dir: &Path,
+    target: &RulesTarget,
+    path: &Path,
+) -> io::Result<Option<PathBuf>> {
+    if target.format != TargetFormat::Symlink {
+        return Ok(None);
+    }
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(None),
+    }
+    let file_name = match path.file_name() {
+        Some(name) if is_rule_file_name(name) => name,
+        _ => return Ok(None),
+    };
+
+    let content = match read_settled(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if content.is_empty() {
+        // Written by a later event, or left for the user to fill in or delete
+        return Ok(None);
+    }
+
+    let linked = linked_rule(dir, target, file_name)?;
+    if let Some((source, _)) = &linked {
+        if replaced_managed_link(dir, &target.path(dir).join(file_name), source) {
+            let previous = fs::read(source)?;
+            if content != previous {
+                let backup_path = backup_file(dir, &file_name.to_string_lossy(), &previous)?;
+                fs::write(source, &content)?;
+                println!(
+                    "Wrote {} from {} back to {} (previous content saved to {})",
+                    file_name.to_string_lossy(),
+                    target.dir,
+                    source.display(),
+                    backup_path.display()
+                );
+            }
+            fs::remove_file(path)?;
+            target.sync_rule(dir, source)?;
+            return Ok(Some(source.clone()));
+        }
+    }
+
+    let rules_path = dir.join(RULES_DIR);
+    fs::create_dir_all(&rules_path)?;
+    let (existing_rule, existing_entry) = match linked {
+        Some(linked) => linked,
+        None => (rules_path.join(file_name), file_name.to_os_string()),
+    };
+
+    let adopted_rule = if !existing_rule.exists() {
+        move_path(path, &existing_rule)?;
+        println!(
+            "Moved {} from {} into {}",
+            file_name.to_string_lossy(),
+            target.dir,
+            RULES_DIR
+        );
+        existing_rule.clone()
+    } else if content == fs::read(&existing_rule)? {
+        fs::remove_file(path)?;
+        println!(
+            "{} in {} is identical to {}; replaced it with a link",
+            file_name.to_string_lossy(),
+            target.dir,
+            existing_rule.display()
+        );
+        existing_rule.clone()
+    } else {
+        let renamed_rule = rules_path.join(suffixed_file_name(&rules_path, file_name, target.name));
+        move_path(path, &renamed_rule)?;
+        println!(
+            "{} in {} conflicts with {}; kept it as {}",
+            file_name.to_string_lossy(),
+            target.dir,
+            existing_rule.display(),
+            renamed_rule.display()
+        );
+        renamed_rule
+    };
+
+    for target in project_targets(dir) {
+        if existing_rule.file_name() == Some(existing_entry.as_os_str()) {
+            target.sync_rule(dir, &existing_rule)?;
+        } else {
+            let source = existing_rule.display().to_string();
+            target.sync_rule_as(dir, &existing_rule, &existing_entry, &source)?;
+        }
+        if adopted_rule != existing_rule {
+            target.sync_rule(dir, &adopted_rule)?;
+        }
+    }
+
+    Ok(Some(adopted_rule))
+}
+
+/// Checks whether a regular file at `entry_path` took the place of a managed link
+/// to `rule` that may be written back to.
+///
+/// The link record must show that known linked the entry to this rule, and the
+/// rule must be in the project's own .rules or .rules.local.
+fn replaced_managed_link(dir: &Path, entry_path: &Path, rule: &Path) -> bool {
+    let is_own_rule = rule
+        .parent()
+        .is_some_and(|parent| parent == dir.join(RULES_DIR) || parent == dir.join(RULES_LOCAL_DIR));
+    if !is_own_rule {
+        return false;
+    }
+    match recorded_link(dir, entry_path) {
+        Some(recorded) => {
+            recorded == rule
+                || matches!(
+                    (recorded.canonicalize(), rule.canonicalize()),
+                    (Ok(recorded), Ok(rule)) if recorded == rule
+                )
+        }
+        None => false,
+    }
+}
+
+/// Finds the rule a managed link with the given file name points to in a target.
+///
+/// Returns the rule's path along with the file name its entry is derived from,
+/// or `None` if the target gets no link with that name.
+fn linked_rule(
+    dir: &Path,
+    target: &RulesTarget,
+    file_name: &OsStr,
+) -> io::Result<Option<(PathBuf, OsString)>> {
+    let project_rule = inherited_rule_files(dir)?
+        .into_iter()
+        .find(|rule| rule.file_name() == Some(file_name) && rule_applies_to(rule, target.name));
+    if let Some(rule) = project_rule {
+        return Ok(Some((rule, file_name.to_os_string())));
+    }
+
+    let global_name = match file_name.to_string_lossy().strip_prefix(GLOBAL_RULE_PREFIX) {
+        Some(name) => name.to_string(),
+        None => return Ok(None),
+    };
+    let global_rule = match get_global_rules_dir() {
+        Ok(global_rules_dir) => global_rules_dir.join(&global_name),
+        Err(_) => return Ok(None),
+    };
+    if global_rule.is_file() && rule_applies_to(&global_rule, target.name) {
+        return Ok(Some((global_rule, file_name.to_os_string())));
+    }
+    Ok(None)
+}
+
+/// Builds a file name that doesn't exist yet in `directory` by adding a suffix to the stem.
+///
+/// `style.mdc` with suffix `cursor` becomes `style-cursor.mdc`, then `style-cursor-2.mdc`.
+fn suffixed_file_name(directory: &Path, file_name: &OsStr, suffix: &str) -> OsString {
+    let original = Path::new(file_name);
+    let stem = original
+        .file_stem()
+        .unwrap_or(file_name)
+        .to_string_lossy()
+        .into_owned();
+    let extension = original
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut counter = 1;
+    loop {
+        let candidate = if counter == 1 {
+            format!("{}-{}{}", stem, suffix, extension)
+        } else {
+            format!("{}-{}-{}{}", stem, suffix, counter, extension)
+        };
+        if !directory.join(&candidate).exists() {
+            return OsString::from(candidate);
+        }
+        counter += 1;
+    }
+}
+
+/// Removes managed entries from a target directory whose source rule no longer exists.
+fn prune_stale_entries(
+    dir: &Path,
+    target: &RulesTarget,
+    rule_files: &[PathBuf],
+    global_rules_dir: &Path,
+    global_rules: &[PathBuf],
+) -> io::Result<()> {
+    let expected: std::collections::HashSet<_> = rule_files
+        .iter()
+        .filter_map(|path| path.file_name())
+        .map(|name| target.entry_name(name))
+        .chain(
+            global_rules
+                .iter()
+                .filter_map(|path| path.file_name())
+                .map(|name| target.entry_name(&global_file_name(name))),
+        )
+        .collect();
+    let mut rules_paths: Vec<PathBuf> = rules_chain(dir)
+        .iter()
+        .filter_map(|rules_path| rules_path.canonicalize().ok())
+        .collect();
+    if let Ok(global_rules_dir) = global_rules_dir.canonicalize() {
+        rules_paths.push(global_rules_dir);
+    }
+
+    for entry in fs::read_dir(target.path(dir))? {
+        let path = entry?.path();
+        let name = match path.file_name() {
+            Some(name) => name.to_os_string(),
+            None => continue,
+        };
+        if expected.contains(&name) || !target.is_managed_entry(&path) {
+            continue;
+        }
+        // Only prune symlinks that point into a rules directory this project uses
+        if fs::symlink_metadata(&path)?.file_type().is_symlink()
+            && !rules_paths
+                .iter()
+                .any(|rules_path| links_into_directory(&path, rules_path))
+        {
+            continue;
+        }
+        fs::remove_file(&path)?;
+        forget_link(dir, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Checks whether a symlink points to a file inside the given (canonical) directory.
+fn links_into_directory(link: &Path, directory: &Path) -> bool {
+    let destination = match fs::read_link(link) {
+        Ok(destination) => destination,
+        Err(_) => return false,
+    };
+    let destination = match link.parent() {
+        Some(parent) if destination.is_relative() => parent.join(destination),
+        _ => destination,
+    };
+    destination
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .map(|parent| parent == directory)
+        .unwrap_or(false)
+}
 
 /// Creates symlinks for all files in a rules directory to the target directories.
 ///
 /// This function synchronizes the contents of a .rules directory with the target
 /// directories (.cursor/rules and .windsurf/rules) by creating symlinks for all
-/// files found in the source directory.
+/// files found in the source directory. Rules whose `agents` or `exclude_agents`
+/// frontmatter leaves a target out are not linked there.
 ///
 /// # Arguments
 ///
@@ -38,11 +490,12 @@ pub fn sync_rules_directory(
 
         if path.is_file() {
             let file_name = path.file_name().unwrap();
-            let cursor_target = cursor_rules_path.join(file_name);
-            let windsurf_target = windsurf_rules_path.join(file_name);
-
-            create_symlink_to_file(&path, &cursor_target)?;
-            create_symlink_to_file(&path, &windsurf_target)?;
+            if rule_applies_to(&path, CURSOR_TARGET) {
+                create_symlink_to_file(&path, &cursor_rules_path.join(file_name))?;
+            }
+            if rule_applies_to(&path, WINDSURF_TARGET) {
+                create_symlink_to_file(&path, &windsurf_rules_path.join(file_name))?;
+            }
         }
     }
 
@@ -51,9 +504,12 @@ pub fn sync_rules_directory(
 
 /// Removes all symlinks from the target directories for a given project directory.
 ///
-/// This function removes all symlinks in both .cursor/rules and .windsurf/rules
-/// directories for the specified project directory. It only removes the symlinks,
-/// not the original files in .rules.
+/// This function removes the entries managed by known from every target
+/// directory (.cursor/rules, .windsurf/rules and the optional .kiro/steering and
+/// .amazonq/rules) for the specified project directory, as well as the sections of
+/// .aider.conf.yml and .git/info/exclude managed by known. Regular files in the target directories, such
+/// as rules materialized by `known eject`, and the original files in .rules are
+/// left alone.
 ///
 /// # Arguments
 ///
@@ -63,38 +519,30 @@ pub fn sync_rules_directory(
 ///
 /// Returns an error if directory operations or file removal fails
 pub fn remove_symlinks_from_directory(dir: &Path) -> io::Result<()> {
-    let cursor_rules_path = dir.join(CURSOR_RULES_DIR);
-    let windsurf_rules_path = dir.join(WINDSURF_RULES_DIR);
-
-    // Remove all files from .cursor/rules if it exists
-    if cursor_rules_path.exists() {
-        for entry in fs::read_dir(&cursor_rules_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                fs::remove_file(&path)?;
-            }
+    for target in BUILTIN_TARGETS.iter() {
+        let target_path = target.path(dir);
+        if !target_path.exists() {
+            continue;
         }
-    }
-
-    // Remove all files from .windsurf/rules if it exists
-    if windsurf_rules_path.exists() {
-        for entry in fs::read_dir(&windsurf_rules_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
+        for entry in fs::read_dir(&target_path)? {
+            let path = entry?.path();
+            if target.is_managed_entry(&path) {
                 fs::remove_file(&path)?;
+                forget_link(dir, &path)?;
             }
         }
     }
 
+    remove_aider_config_block(dir)?;
+    update_git_exclude(dir, false)?;
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::RULES_DIR;
+    use crate::constants::{CURSOR_RULES_DIR, WINDSURF_RULES_DIR};
     use std::fs;
     use tempfile::tempdir;
 
@@ -180,6 +628,151 @@ mod tests {
         assert!(rules_path.join("test2.md").exists());
     }
 
+    #[test]
+    fn test_sync_project_targets_adopts_files_created_in_targets() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        let cursor_rules_path = dir.path().join(CURSOR_RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::create_dir_all(&cursor_rules_path).unwrap();
+        fs::write(rules_path.join("existing.md"), "existing").unwrap();
+        fs::write(
+            rules_path.join("windsurf.md"),
+            "---\nagents: [windsurf]\n---\nOnly windsurf",
+        )
+        .unwrap();
+        fs::write(cursor_rules_path.join("new.mdc"), "from cursor").unwrap();
+        fs::write(cursor_rules_path.join("existing.md"), "edited in cursor").unwrap();
+        fs::write(cursor_rules_path.join("windsurf.md"), "made in cursor").unwrap();
+
+        sync_project_targets(dir.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(rules_path.join("new.mdc")).unwrap(),
+            "from cursor"
+        );
+        // A file that never replaced a link doesn't overwrite the rule with its name
+        assert_eq!(
+            fs::read_to_string(rules_path.join("existing.md")).unwrap(),
+            "existing"
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("existing-cursor.md")).unwrap(),
+            "edited in cursor"
+        );
+        assert!(!dir.path().join(".known").join("backups").exists());
+        // A rule Cursor doesn't get a link for is kept under a new name
+        assert_eq!(
+            fs::read_to_string(rules_path.join("windsurf-cursor.md")).unwrap(),
+            "made in cursor"
+        );
+        for name in [
+            "new.mdc",
+            "existing.md",
+            "existing-cursor.md",
+            "windsurf-cursor.md",
+        ] {
+            let link = cursor_rules_path.join(name);
+            assert!(fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink());
+            assert!(dir.path().join(WINDSURF_RULES_DIR).join(name).exists());
+        }
+    }
+
+    #[test]
+    fn test_sync_project_targets_writes_back_only_over_recorded_links() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use spaces").unwrap();
+        sync_project_targets(dir.path()).unwrap();
+
+        // An editor saves by renaming a new file over the link known created
+        let cursor_rule = dir.path().join(CURSOR_RULES_DIR).join("style.md");
+        fs::remove_file(&cursor_rule).unwrap();
+        fs::write(&cursor_rule, "Use tabs").unwrap();
+        sync_project_targets(dir.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style.md")).unwrap(),
+            "Use tabs"
+        );
+        assert!(!rules_path.join("style-cursor.md").exists());
+        assert!(fs::symlink_metadata(&cursor_rule)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+    }
+
+    #[test]
+    fn test_sync_package_targets_never_write_back_to_inherited_rules() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"recursive": true, "inherit": true}"#,
+        )
+        .unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(RULES_DIR).join("org.md"), "Org-wide rule").unwrap();
+        let package = root.join("packages").join("x");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join("AGENTS.md"), "# Package").unwrap();
+        sync_project_targets(&package).unwrap();
+
+        // The package's link to the inherited rule is replaced with a regular file
+        let cursor_rule = package.join(CURSOR_RULES_DIR).join("org.md");
+        fs::remove_file(&cursor_rule).unwrap();
+        fs::write(&cursor_rule, "Package tweak").unwrap();
+        sync_project_targets(&package).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join(RULES_DIR).join("org.md")).unwrap(),
+            "Org-wide rule"
+        );
+        assert_eq!(
+            fs::read_to_string(package.join(RULES_DIR).join("org-cursor.md")).unwrap(),
+            "Package tweak"
+        );
+        assert_eq!(fs::read_to_string(&cursor_rule).unwrap(), "Org-wide rule");
+    }
+
+    #[test]
+    fn test_sync_project_targets_links_global_rules() {
+        let dir = tempdir().unwrap();
+        let global_dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git").join("info")).unwrap();
+        fs::create_dir(dir.path().join(RULES_DIR)).unwrap();
+        fs::write(dir.path().join(RULES_DIR).join("style.md"), "Project style").unwrap();
+        let global_rule = global_dir.path().join("style.md");
+        fs::write(&global_rule, "Personal style").unwrap();
+
+        sync_project_targets_with_global_rules(dir.path(), global_dir.path()).unwrap();
+
+        let cursor_rules_path = dir.path().join(CURSOR_RULES_DIR);
+        assert_eq!(
+            fs::read_to_string(cursor_rules_path.join("style.md")).unwrap(),
+            "Project style"
+        );
+        assert_eq!(
+            fs::read_link(cursor_rules_path.join("global-style.md")).unwrap(),
+            global_rule
+        );
+        let exclude = dir.path().join(".git").join("info").join("exclude");
+        assert!(fs::read_to_string(&exclude)
+            .unwrap()
+            .contains("**/.windsurf/rules/global-*"));
+
+        // Deleted global rules are pruned and the exclude section goes away
+        fs::remove_file(&global_rule).unwrap();
+        sync_project_targets_with_global_rules(dir.path(), global_dir.path()).unwrap();
+        assert!(fs::symlink_metadata(cursor_rules_path.join("global-style.md")).is_err());
+        assert!(cursor_rules_path.join("style.md").exists());
+        assert!(!fs::read_to_string(&exclude).unwrap().contains("global-"));
+    }
+
     #[test]
     fn test_remove_symlinks_from_directory_nonexistent_directories() {
         let dir = tempdir().unwrap();
@@ -241,9 +834,9 @@ mod tests {
         // Call remove_symlinks_from_directory
         remove_symlinks_from_directory(dir.path()).unwrap();
 
-        // Verify only files (not subdirectories) were removed
-        assert!(!cursor_rules_path.join("file1.md").exists());
-        assert!(!windsurf_rules_path.join("file1.md").exists());
+        // Verify regular files and subdirectories were left alone
+        assert!(cursor_rules_path.join("file1.md").exists());
+        assert!(windsurf_rules_path.join("file1.md").exists());
         assert!(cursor_rules_path.join("subdir").exists());
         assert!(windsurf_rules_path.join("subdir").exists());
         assert!(cursor_rules_path.join("subdir").join("file2.md").exists());
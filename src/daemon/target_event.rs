@@ -0,0 +1,239 @@
+//! Handles file system events in the tool-specific rules directories.
+
+use crate::aider::sync_aider_config_if_enabled;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind};
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use super::symlinks::{adopt_target_file, project_targets};
+
+/// Handles a file system event in a target directory by adopting new rules into .rules.
+///
+/// When a tool creates a rule as a regular file in its own directory (for example
+/// from Cursor's UI), the file is moved into .rules and replaced with managed links
+/// so that .rules stays the single source of truth.
+///
+/// # Arguments
+///
+/// * `event` - The file system event to handle
+/// * `rules_paths` - Map of canonical rules paths to their parent directories
+///
+/// # Errors
+///
+/// Returns an error if adopting the file fails
+///
+pub fn handle_target_event(
+    event: &Event,
+    rules_paths: &HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_)
+            | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+            | EventKind::Modify(ModifyKind::Data(_))
+    ) {
+        return Ok(());
+    }
+
+    for path in &event.paths {
+        let target_dir = match path.parent() {
+            Some(parent) => parent,
+            None => continue,
+        };
+
+        // Find the project and target whose directory contains this file
+        for (rules_path, parent_dir) in rules_paths {
+            let project_root = match rules_path.parent() {
+                Some(root) => root,
+                None => continue,
+            };
+            let target = project_targets(parent_dir)
+                .into_iter()
+                .find(|target| target.path(project_root) == target_dir);
+
+            if let Some(target) = target {
+                if adopt_target_file(parent_dir, target, path)?.is_some() {
+                    sync_aider_config_if_enabled(parent_dir)?;
+                }
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{CURSOR_RULES_DIR, RULES_DIR, WINDSURF_RULES_DIR};
+    use notify::event::{CreateKind, DataChange};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn setup_project(dir: &Path) -> (PathBuf, HashMap<PathBuf, PathBuf>) {
+        let rules_path = dir.join(RULES_DIR);
+        fs::create_dir_all(&rules_path).unwrap();
+        fs::create_dir_all(dir.join(CURSOR_RULES_DIR)).unwrap();
+        let mut rules_paths = HashMap::new();
+        rules_paths.insert(rules_path.canonicalize().unwrap(), dir.to_path_buf());
+        (dir.canonicalize().unwrap(), rules_paths)
+    }
+
+    fn create_event(path: PathBuf) -> Event {
+        Event {
+            kind: EventKind::Create(CreateKind::File),
+            paths: vec![path],
+            attrs: Default::default(),
+        }
+    }
+
+    fn is_symlink(path: &Path) -> bool {
+        fs::symlink_metadata(path)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_new_rule_in_target_is_adopted() {
+        let dir = tempdir().unwrap();
+        let (root, rules_paths) = setup_project(dir.path());
+        let cursor_rule = root.join(CURSOR_RULES_DIR).join("style.mdc");
+        fs::write(&cursor_rule, "Use tabs").unwrap();
+
+        handle_target_event(&create_event(cursor_rule.clone()), &rules_paths).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join(RULES_DIR).join("style.mdc")).unwrap(),
+            "Use tabs"
+        );
+        assert!(is_symlink(&cursor_rule));
+        assert!(is_symlink(&root.join(WINDSURF_RULES_DIR).join("style.mdc")));
+
+        // The link created by the adoption is ignored
+        handle_target_event(&create_event(cursor_rule.clone()), &rules_paths).unwrap();
+        assert!(!root.join(RULES_DIR).join("style-cursor.mdc").exists());
+    }
+
+    #[test]
+    fn test_file_saved_over_link_is_written_back() {
+        let dir = tempdir().unwrap();
+        let (root, rules_paths) = setup_project(dir.path());
+        let rule = root.join(RULES_DIR).join("style.mdc");
+        fs::write(&rule, "Use spaces").unwrap();
+        let cursor_rule = root.join(CURSOR_RULES_DIR).join("style.mdc");
+        crate::rules::find_target(crate::constants::CURSOR_TARGET)
+            .unwrap()
+            .sync_rule(&root, &rule)
+            .unwrap();
+
+        // An editor saves by renaming a new file over the link
+        let temporary = root.join(CURSOR_RULES_DIR).join(".style.mdc.tmp");
+        fs::write(&temporary, "Use tabs").unwrap();
+        handle_target_event(&create_event(temporary.clone()), &rules_paths).unwrap();
+        assert!(!root.join(RULES_DIR).join(".style.mdc.tmp").exists());
+        fs::rename(&temporary, &cursor_rule).unwrap();
+        handle_target_event(&create_event(cursor_rule.clone()), &rules_paths).unwrap();
+
+        assert_eq!(fs::read_to_string(&rule).unwrap(), "Use tabs");
+        assert!(is_symlink(&cursor_rule));
+        assert!(!root.join(RULES_DIR).join("style-cursor.mdc").exists());
+        let backup = fs::read_dir(root.join(".known").join("backups"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read_to_string(backup.path()).unwrap(), "Use spaces");
+    }
+
+    #[test]
+    fn test_editor_files_in_target_are_ignored() {
+        let dir = tempdir().unwrap();
+        let (root, rules_paths) = setup_project(dir.path());
+        let cursor_rules = root.join(CURSOR_RULES_DIR);
+
+        for name in [
+            ".style.md.swp",
+            "style.md~",
+            "style.md.tmp",
+            "style.md___jb_tmp___",
+            ".#style.md",
+            "notes.txt",
+        ] {
+            let path = cursor_rules.join(name);
+            fs::write(&path, "x").unwrap();
+            handle_target_event(&create_event(path.clone()), &rules_paths).unwrap();
+            assert!(path.is_file(), "{} was moved", name);
+            assert!(!root.join(RULES_DIR).join(name).exists());
+        }
+    }
+
+    #[test]
+    fn test_empty_file_created_over_link_is_not_written_back() {
+        let dir = tempdir().unwrap();
+        let (root, rules_paths) = setup_project(dir.path());
+        let rule = root.join(RULES_DIR).join("style.mdc");
+        fs::write(&rule, "Use spaces").unwrap();
+        let cursor_rule = root.join(CURSOR_RULES_DIR).join("style.mdc");
+        crate::rules::find_target(crate::constants::CURSOR_TARGET)
+            .unwrap()
+            .sync_rule(&root, &rule)
+            .unwrap();
+
+        // A tool deletes the link, creates an empty file and writes to it later
+        fs::remove_file(&cursor_rule).unwrap();
+        fs::write(&cursor_rule, "").unwrap();
+        handle_target_event(&create_event(cursor_rule.clone()), &rules_paths).unwrap();
+        assert_eq!(fs::read_to_string(&rule).unwrap(), "Use spaces");
+        assert!(!is_symlink(&cursor_rule));
+
+        fs::write(&cursor_rule, "Use tabs").unwrap();
+        let event = Event {
+            kind: EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            paths: vec![cursor_rule.clone()],
+            attrs: Default::default(),
+        };
+        handle_target_event(&event, &rules_paths).unwrap();
+        assert_eq!(fs::read_to_string(&rule).unwrap(), "Use tabs");
+        assert!(is_symlink(&cursor_rule));
+    }
+
+    #[test]
+    fn test_conflicting_rule_in_target_is_kept_under_new_name() {
+        let dir = tempdir().unwrap();
+        let (root, rules_paths) = setup_project(dir.path());
+        fs::write(
+            root.join(RULES_DIR).join("style.mdc"),
+            "---\nagents: [windsurf]\n---\nUse spaces",
+        )
+        .unwrap();
+        fs::write(root.join(RULES_DIR).join("same.mdc"), "Same").unwrap();
+
+        let conflicting = root.join(CURSOR_RULES_DIR).join("style.mdc");
+        let identical = root.join(CURSOR_RULES_DIR).join("same.mdc");
+        fs::write(&conflicting, "Use tabs").unwrap();
+        fs::write(&identical, "Same").unwrap();
+
+        handle_target_event(&create_event(conflicting.clone()), &rules_paths).unwrap();
+        handle_target_event(&create_event(identical.clone()), &rules_paths).unwrap();
+
+        let rules_path = root.join(RULES_DIR);
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style.mdc")).unwrap(),
+            "---\nagents: [windsurf]\n---\nUse spaces"
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style-cursor.mdc")).unwrap(),
+            "Use tabs"
+        );
+        assert!(!rules_path.join("same-cursor.mdc").exists());
+        // Cursor gets no link for a rule limited to Windsurf
+        assert!(fs::symlink_metadata(&conflicting).is_err());
+        assert!(is_symlink(&identical));
+        assert!(is_symlink(
+            &root.join(CURSOR_RULES_DIR).join("style-cursor.mdc")
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+//! Ejecting a project from known's management.
+//!
+//! `known eject` leaves a project the way it would look if it had never used
+//! known: every link known maintains (CLAUDE.md, GEMINI.md, CLAUDE.local.md, the
+//! links left by legacy imports and the entries of the tool-specific rules
+//! directories, including those of nested packages) is replaced by a regular file
+//! with the same content, generated files and managed sections lose their
+//! markers, and the directory is no longer watched by the daemon.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::aider::unmanage_aider_config;
+use crate::claude::unmanage_claude_import;
+use crate::conditional::{is_rendered_alias, strip_rendered_header};
+use crate::config::{
+    get_global_rules_dir, load_config, load_project_config, remove_directory_from_config,
+};
+use crate::constants::{
+    AGENTS_FILENAME, CLAUDE_FILENAME, CLAUDE_LOCAL_FILENAME, GEMINI_FILENAME, KNOWN_STATE_DIR,
+    LAST_RUN_JOURNAL_DIR, RULES_DIR,
+};
+use crate::git::{find_git_dir, update_exclude_section};
+use crate::global::update_git_exclude;
+use crate::legacy::LEGACY_RULE_FILES;
+use crate::monorepo::{discover_packages, rules_chain};
+use crate::rules::kiro::strip_generated_marker;
+use crate::rules::links::{discard_record, recorded_link};
+use crate::rules::{TargetFormat, BUILTIN_TARGETS};
+use crate::symlinks::LOCAL_LAYER_EXCLUDE_SECTION;
+use crate::transaction::STATE_EXCLUDE_SECTION;
+
+/// Options for [`eject_project`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EjectOptions {
+    /// Remove the `.rules` directories after their files were materialized
+    pub remove_rules: bool,
+}
+
+/// What `known eject` changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EjectReport {
+    /// Links and generated files that were replaced by regular files
+    pub materialized: Vec<PathBuf>,
+    /// Whether the directory was removed from the watched directories
+    pub unregistered: bool,
+    /// Whether the `.rules` directory was removed
+    pub rules_removed: bool,
+}
+
+/// Stops managing a project with known, leaving regular files behind.
+///
+/// The daemon must not be running while a project is ejected, or it would react
+/// to the links being replaced; `known eject` stops it first and restarts it
+/// afterwards (see [`crate::single_instance::stop_daemon_and_wait`]).
+///
+/// # Behavior
+///
+/// - CLAUDE.md and GEMINI.md symlinks become copies of AGENTS.md, rendered
+///   aliases lose their generated header, and a CLAUDE.local.md symlink becomes
+///   a copy of AGENTS.local.md
+/// - An importing CLAUDE.md keeps a plain `@AGENTS.md` import without markers
+/// - Legacy rules files (.cursorrules, .windsurfrules, .clinerules) left as links
+///   by their import become copies of what they link to
+/// - Rule symlinks in .cursor/rules, .windsurf/rules and .amazonq/rules become
+///   copies of the rules, wherever they come from (.rules, .rules.local, an
+///   inherited .rules or the global rules), and generated Kiro steering files
+///   lose their marker
+/// - The managed section of .aider.conf.yml becomes a plain `read:` list
+/// - In recursive mode, every nested package is ejected the same way
+/// - The directory is removed from the watched directories
+/// - The sections known manages in .git/info/exclude are removed, unless another
+///   watched directory shares the repository; the one excluding .known/ stays
+///   as long as backups are left there
+/// - With `options.remove_rules`, the .rules directories are deleted
+/// - The journal for `known undo` and the record of managed links are discarded,
+///   since they no longer apply
+///
+/// AGENTS.md, .known.json and the backups in .known/ are left in place.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `options` - Whether to remove the .rules directories
+///
+/// # Returns
+///
+/// Returns a description of what was changed.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if the directory has no AGENTS.md, or an error if
+/// a file cannot be read, written or removed.
+pub fn eject_project(dir: &Path, options: &EjectOptions) -> io::Result<EjectReport> {
+    if !dir.join(AGENTS_FILENAME).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "AGENTS.md file not found. This directory is not managed by known.",
+        ));
+    }
+
+    let mut report = EjectReport::default();
+    let mut project_dirs = vec![dir.to_path_buf()];
+    if load_project_config(dir)?.recursive {
+        project_dirs.extend(discover_packages(dir)?);
+    }
+
+    // Read everything that will be materialized while the links still resolve
+    let mut materialized = Vec::new();
+    for project_dir in &project_dirs {
+        materialized.extend(collect_managed_files(project_dir)?);
+    }
+
+    for project_dir in &project_dirs {
+        if unmanage_claude_import(project_dir)? {
+            report.materialized.push(project_dir.join(CLAUDE_FILENAME));
+        }
+        unmanage_aider_config(project_dir, !options.remove_rules)?;
+    }
+    report.unregistered = remove_directory_from_config(dir)?;
+
+    for (path, content) in materialized {
+        if fs::symlink_metadata(&path).is_ok() {
+            fs::remove_file(&path)?;
+        }
+        fs::write(&path, content)?;
+        report.materialized.push(path);
+    }
+
+    for project_dir in &project_dirs {
+        let rules_path = project_dir.join(RULES_DIR);
+        if options.remove_rules && rules_path.exists() {
+            fs::remove_dir_all(&rules_path)?;
+            report.rules_removed = true;
+        }
+
+        let journal_dir = project_dir.join(KNOWN_STATE_DIR).join(LAST_RUN_JOURNAL_DIR);
+        if journal_dir.exists() {
+            fs::remove_dir_all(&journal_dir)?;
+        }
+        discard_record(project_dir)?;
+        let _ = fs::remove_dir(project_dir.join(KNOWN_STATE_DIR));
+    }
+
+    remove_exclude_sections(dir, &project_dirs)?;
+
+    Ok(report)
+}
+
+/// Collects the content of every link and generated file known maintains in a
+/// project or package directory.
+fn collect_managed_files(dir: &Path) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut files = Vec::new();
+
+    for alias in [CLAUDE_FILENAME, GEMINI_FILENAME] {
+        let alias_path = dir.join(alias);
+        if is_symlink(&alias_path) {
+            let content = fs::read(&alias_path).or_else(|_| fs::read(dir.join(AGENTS_FILENAME)))?;
+            files.push((alias_path, content));
+        } else if let Ok(content) = fs::read_to_string(&alias_path) {
+            if is_rendered_alias(&content) {
+                let plain = strip_rendered_header(&content).as_bytes().to_vec();
+                files.push((alias_path, plain));
+            }
+        }
+    }
+    let claude_local_path = dir.join(CLAUDE_LOCAL_FILENAME);
+    if is_symlink(&claude_local_path) {
+        if let Ok(content) = fs::read(&claude_local_path) {
+            files.push((claude_local_path, content));
+        }
+    }
+    // Legacy rules files imported with the `link` cleanup point to AGENTS.md or a rule
+    for name in LEGACY_RULE_FILES {
+        let legacy_path = dir.join(name);
+        if is_symlink(&legacy_path) && points_into(&legacy_path, dir) {
+            if let Ok(content) = fs::read(&legacy_path) {
+                files.push((legacy_path, content));
+            }
+        }
+    }
+
+    // Every rules directory the project's entries can link to
+    let mut rules_paths = rules_chain(dir);
+    if let Ok(global_rules_dir) = get_global_rules_dir() {
+        rules_paths.push(global_rules_dir);
+    }
+
+    for target in BUILTIN_TARGETS.iter() {
+        let target_path = target.path(dir);
+        if !target_path.exists() {
+            continue;
+        }
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&target_path)? {
+            entries.push(entry?.path());
+        }
+        entries.sort();
+
+        for path in entries {
+            if !target.is_managed_entry(&path) {
+                continue;
+            }
+            match target.format {
+                // Links known didn't create aren't known's to replace
+                TargetFormat::Symlink
+                    if recorded_link(dir, &path).is_some()
+                        || rules_paths
+                            .iter()
+                            .any(|rules_path| points_into(&path, rules_path)) =>
+                {
+                    if let Ok(content) = fs::read(&path) {
+                        files.push((path, content));
+                    }
+                }
+                TargetFormat::Symlink => {}
+                TargetFormat::KiroSteering => {
+                    let content = fs::read_to_string(&path)?;
+                    files.push((path, strip_generated_marker(&content).into_bytes()));
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Removes the sections known manages in the exclude file of the project's repository.
+///
+/// Sections are shared by every project in the repository, so they are kept if
+/// another watched directory is in the same repository. The section excluding
+/// .known/ also stays while any ejected directory still has backups there.
+fn remove_exclude_sections(dir: &Path, project_dirs: &[PathBuf]) -> io::Result<()> {
+    let git_dir = match find_git_dir(dir) {
+        Some(git_dir) => git_dir,
+        None => return Ok(()),
+    };
+    let shared = load_config()?
+        .get_watched_directories()
+        .iter()
+        .any(|watched| find_git_dir(watched).as_ref() == Some(&git_dir));
+    if shared {
+        return Ok(());
+    }
+
+    update_git_exclude(dir, false)?;
+    update_exclude_section(dir, LOCAL_LAYER_EXCLUDE_SECTION, &[])?;
+    if !project_dirs
+        .iter()
+        .any(|project_dir| project_dir.join(KNOWN_STATE_DIR).exists())
+    {
+        update_exclude_section(dir, STATE_EXCLUDE_SECTION, &[])?;
+    }
+    Ok(())
+}
+
+/// Checks whether a path is a symlink, including a dangling one.
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Checks whether a link resolves to a file inside a directory.
+fn points_into(link: &Path, directory: &Path) -> bool {
+    match (fs::canonicalize(link), fs::canonicalize(directory)) {
+        (Ok(resolved), Ok(directory)) => resolved.starts_with(directory),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{save_project_config, ProjectConfig};
+    use crate::constants::{
+        CLINERULES_FILENAME, CURSORRULES_FILENAME, CURSOR_RULES_DIR, KIRO_STEERING_DIR, KIRO_TARGET,
+    };
+    use crate::daemon::symlinks::sync_project_targets;
+    use crate::symlinks::create_platform_symlink;
+    use crate::symlinks::create_symlinks_in_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_eject_materializes_links_and_keeps_rules() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.\n").unwrap();
+        let mut project_config = ProjectConfig::new();
+        project_config.enable_target(KIRO_TARGET);
+        save_project_config(dir.path(), &project_config).unwrap();
+        create_symlinks_in_dir(dir.path()).unwrap();
+        sync_project_targets(dir.path()).unwrap();
+        // Legacy files imported with the `link` cleanup
+        create_platform_symlink(
+            Path::new(AGENTS_FILENAME),
+            &dir.path().join(CURSORRULES_FILENAME),
+        )
+        .unwrap();
+        create_platform_symlink(
+            &Path::new(RULES_DIR).join("style.md"),
+            &dir.path().join(CLINERULES_FILENAME),
+        )
+        .unwrap();
+
+        let report = eject_project(dir.path(), &EjectOptions::default()).unwrap();
+        assert!(!report.rules_removed);
+
+        let cursorrules_path = dir.path().join(CURSORRULES_FILENAME);
+        assert!(!is_symlink(&cursorrules_path));
+        assert_eq!(fs::read_to_string(&cursorrules_path).unwrap(), "# Agents");
+        let clinerules_path = dir.path().join(CLINERULES_FILENAME);
+        assert!(!is_symlink(&clinerules_path));
+        assert_eq!(fs::read_to_string(&clinerules_path).unwrap(), "Use tabs.\n");
+
+        for alias in [CLAUDE_FILENAME, GEMINI_FILENAME] {
+            let alias_path = dir.path().join(alias);
+            assert!(!is_symlink(&alias_path));
+            assert_eq!(fs::read_to_string(&alias_path).unwrap(), "# Agents");
+        }
+        let cursor_rule = dir.path().join(CURSOR_RULES_DIR).join("style.md");
+        assert!(!is_symlink(&cursor_rule));
+        assert_eq!(fs::read_to_string(&cursor_rule).unwrap(), "Use tabs.\n");
+        let steering =
+            fs::read_to_string(dir.path().join(KIRO_STEERING_DIR).join("style.md")).unwrap();
+        assert!(!steering.contains("Generated by known"));
+        assert!(rules_path.join("style.md").exists());
+    }
+
+    #[test]
+    fn test_eject_removes_rules_directory() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let cursor_rules_path = dir.path().join(CURSOR_RULES_DIR);
+        fs::create_dir_all(&cursor_rules_path).unwrap();
+        fs::write(cursor_rules_path.join("style.md"), "Use tabs.\n").unwrap();
+        create_symlinks_in_dir(dir.path()).unwrap();
+        sync_project_targets(dir.path()).unwrap();
+        assert!(is_symlink(&cursor_rules_path.join("style.md")));
+
+        let report = eject_project(dir.path(), &EjectOptions { remove_rules: true }).unwrap();
+        assert!(report.rules_removed);
+        assert!(!dir.path().join(RULES_DIR).exists());
+        assert_eq!(
+            fs::read_to_string(cursor_rules_path.join("style.md")).unwrap(),
+            "Use tabs.\n"
+        );
+        assert!(!dir
+            .path()
+            .join(KNOWN_STATE_DIR)
+            .join(LAST_RUN_JOURNAL_DIR)
+            .exists());
+    }
+
+    #[test]
+    fn test_eject_materializes_package_local_and_rendered_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git").join("info")).unwrap();
+        fs::write(
+            root.join(AGENTS_FILENAME),
+            "# Agents\n<!-- known:only claude -->\nClaude only\n<!-- known:end -->\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"recursive": true, "inherit": true}"#,
+        )
+        .unwrap();
+        fs::create_dir(root.join(RULES_DIR)).unwrap();
+        fs::write(root.join(RULES_DIR).join("org.md"), "Org rule").unwrap();
+        let local_rules = root.join(crate::constants::RULES_LOCAL_DIR);
+        fs::create_dir(&local_rules).unwrap();
+        fs::write(local_rules.join("mine.md"), "My rule").unwrap();
+        let package = root.join("packages").join("x");
+        fs::create_dir_all(&package).unwrap();
+        fs::write(package.join(AGENTS_FILENAME), "# Package").unwrap();
+        create_symlinks_in_dir(root).unwrap();
+        sync_project_targets(root).unwrap();
+        sync_project_targets(&package).unwrap();
+
+        eject_project(root, &EjectOptions::default()).unwrap();
+
+        let claude = fs::read_to_string(root.join(CLAUDE_FILENAME)).unwrap();
+        assert_eq!(claude, "# Agents\nClaude only\n");
+        let local_entry = root.join(CURSOR_RULES_DIR).join("mine.md");
+        assert!(!is_symlink(&local_entry));
+        assert_eq!(fs::read_to_string(&local_entry).unwrap(), "My rule");
+        let inherited_entry = package.join(CURSOR_RULES_DIR).join("org.md");
+        assert!(!is_symlink(&inherited_entry));
+        assert_eq!(fs::read_to_string(&inherited_entry).unwrap(), "Org rule");
+        assert!(!is_symlink(&package.join(CLAUDE_FILENAME)));
+        assert!(!root.join(KNOWN_STATE_DIR).exists());
+        assert!(!package.join(KNOWN_STATE_DIR).exists());
+        let exclude =
+            fs::read_to_string(root.join(".git").join("info").join("exclude")).unwrap_or_default();
+        assert!(!exclude.contains("known"), "{}", exclude);
+    }
+}
@@ -0,0 +1,323 @@
+//! Explains which instructions and rules each agent applies to a file, reported by
+//! `known explain`.
+
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::budget::project_rules;
+use crate::config::{load_project_config, GeminiStrategy};
+use crate::constants::{
+    AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, AIDER_TARGET, AMAZONQ_TARGET, RULES_DIR,
+};
+use crate::daemon::symlinks::project_targets;
+use crate::monorepo::{glob_matches, is_ignored};
+use crate::rules::Activation;
+
+/// Why an agent applies a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// An instructions file in the file's directory or above it
+    Instructions,
+    /// A rule that is always applied
+    AlwaysOn,
+    /// A rule with a glob that matches the file
+    Glob(String),
+    /// A rule that is only applied when requested
+    Manual,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Instructions => write!(f, "instructions"),
+            Reason::AlwaysOn => write!(f, "always-on"),
+            Reason::Glob(glob) => write!(f, "glob {}", glob),
+            Reason::Manual => write!(f, "manual, only when requested"),
+        }
+    }
+}
+
+/// An instructions file or rule an agent applies to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedFile {
+    /// Path of the instructions file or rule, relative to the project if it is inside it
+    pub path: String,
+    /// Why the agent applies it
+    pub reason: Reason,
+}
+
+/// What one agent applies to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentExplanation {
+    /// Name of the agent or target
+    pub agent: String,
+    /// The instructions files and rules, nearest to the project root first
+    pub files: Vec<AppliedFile>,
+}
+
+/// Explains which instructions files and rules each agent applies to a file.
+///
+/// Claude Code, Gemini CLI and Codex CLI read every AGENTS.md from the project
+/// root down to the file's directory, and Claude Code also reads the
+/// AGENTS.local.md files next to them. Each enabled target applies the rules
+/// synced to it: always-on rules, rules with a glob matching the file, and rules
+/// that are only applied on request. In recursive mode, the rules of every
+/// package the file is in apply as well, with globs relative to the package.
+///
+/// The file doesn't need to exist yet.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `path` - Path of the file, absolute or relative to the project directory
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if the file is outside the project, or an
+/// error if the project configuration or its rules cannot be read.
+pub fn explain_path(dir: &Path, path: &Path) -> io::Result<Vec<AgentExplanation>> {
+    let root = dir.canonicalize()?;
+    let file = match dir.join(path).canonicalize() {
+        Ok(file) => file,
+        Err(_) => normalize(&root.join(path)),
+    };
+    let relative = file.strip_prefix(&root).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is outside {}", path.display(), root.display()),
+        )
+    })?;
+
+    // The project root and every directory on the way down to the file
+    let mut dirs = vec![root.clone()];
+    if let Some(parent) = relative.parent() {
+        for component in parent.components() {
+            let next = dirs[dirs.len() - 1].join(component);
+            dirs.push(next);
+        }
+    }
+
+    let project_config = load_project_config(&root)?;
+    let mut explanations: Vec<AgentExplanation> = Vec::new();
+    let shown = |path: &Path| match path.strip_prefix(&root) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => path.display().to_string(),
+    };
+
+    for (agent, reads_local) in [
+        ("claude", true),
+        (
+            "gemini",
+            project_config.aliases.gemini == GeminiStrategy::Settings,
+        ),
+        ("codex", false),
+    ] {
+        let mut files = Vec::new();
+        for instructions_dir in &dirs {
+            for (file_name, applies) in [
+                (AGENTS_FILENAME, true),
+                (AGENTS_LOCAL_FILENAME, reads_local),
+            ] {
+                let instructions = instructions_dir.join(file_name);
+                if applies && instructions.is_file() {
+                    files.push(AppliedFile {
+                        path: shown(&instructions),
+                        reason: Reason::Instructions,
+                    });
+                }
+            }
+        }
+        if !files.is_empty() {
+            push_files(&mut explanations, agent, files);
+        }
+    }
+
+    // Directories whose targets get rules: the root, and packages in recursive mode
+    let owners = dirs.iter().filter(|owner| {
+        **owner == root
+            || (project_config.recursive
+                && owner.join(AGENTS_FILENAME).is_file()
+                && !is_ignored(&root, owner))
+    });
+    for owner in owners {
+        let file_in_owner = file
+            .strip_prefix(owner)
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        let rules = project_rules(owner)?;
+
+        for target in project_targets(owner) {
+            let files = rules
+                .iter()
+                .filter(|rule| rule.is_synced_to(owner, target.name))
+                .filter_map(|rule| {
+                    let reason = match &rule.budget.activation {
+                        // Amazon Q ignores frontmatter and loads every rule
+                        _ if target.name == AMAZONQ_TARGET => Reason::AlwaysOn,
+                        Activation::Always => Reason::AlwaysOn,
+                        Activation::FileMatch(globs) => Reason::Glob(
+                            globs
+                                .iter()
+                                .find(|glob| glob_matches(glob, &file_in_owner))?
+                                .clone(),
+                        ),
+                        Activation::Manual => Reason::Manual,
+                    };
+                    Some(AppliedFile {
+                        path: shown(&rule.path),
+                        reason,
+                    })
+                })
+                .collect();
+            push_files(&mut explanations, target.name, files);
+        }
+
+        if load_project_config(owner)?.has_target(AIDER_TARGET) {
+            let mut files = Vec::new();
+            if owner.join(AGENTS_FILENAME).is_file() {
+                files.push(AppliedFile {
+                    path: shown(&owner.join(AGENTS_FILENAME)),
+                    reason: Reason::Instructions,
+                });
+            }
+            // Aider's read list only has the rules in the project's own .rules
+            let rules_path = owner.join(RULES_DIR);
+            files.extend(
+                rules
+                    .iter()
+                    .filter(|rule| rule.path.parent() == Some(rules_path.as_path()))
+                    .filter(|rule| rule.is_synced_to(owner, AIDER_TARGET))
+                    .map(|rule| AppliedFile {
+                        path: shown(&rule.path),
+                        reason: Reason::AlwaysOn,
+                    }),
+            );
+            push_files(&mut explanations, AIDER_TARGET, files);
+        }
+    }
+
+    Ok(explanations)
+}
+
+/// Adds files to an agent's explanation, skipping files it already applies.
+fn push_files(explanations: &mut Vec<AgentExplanation>, agent: &str, files: Vec<AppliedFile>) {
+    let index = match explanations
+        .iter()
+        .position(|explanation| explanation.agent == agent)
+    {
+        Some(index) => index,
+        None => {
+            explanations.push(AgentExplanation {
+                agent: agent.to_string(),
+                files: Vec::new(),
+            });
+            explanations.len() - 1
+        }
+    };
+    let explanation = &mut explanations[index];
+    for file in files {
+        if !explanation
+            .files
+            .iter()
+            .any(|applied| applied.path == file.path)
+        {
+            explanation.files.push(file);
+        }
+    }
+}
+
+/// Resolves `.` and `..` in a path without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::save_project_config;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn files<'a>(explanations: &'a [AgentExplanation], agent: &str) -> Vec<(&'a str, String)> {
+        explanations
+            .iter()
+            .find(|explanation| explanation.agent == agent)
+            .map(|explanation| {
+                explanation
+                    .files
+                    .iter()
+                    .map(|file| (file.path.as_str(), file.reason.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_explain_path_lists_instructions_and_rules_per_agent() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(AGENTS_FILENAME), "# Project").unwrap();
+        fs::create_dir_all(root.join("src/daemon")).unwrap();
+        fs::write(root.join("src/daemon/AGENTS.md"), "# Daemon").unwrap();
+        fs::write(root.join(AGENTS_LOCAL_FILENAME), "# Mine").unwrap();
+        let rules_path = root.join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        fs::write(
+            rules_path.join("rust.md"),
+            "---\nglobs: \"src/**/*.rs\"\n---\nRun clippy.",
+        )
+        .unwrap();
+        fs::write(
+            rules_path.join("web.md"),
+            "---\nglobs: \"*.{ts,tsx}\"\n---\nUse strict mode.",
+        )
+        .unwrap();
+        fs::write(
+            rules_path.join("release.md"),
+            "---\ndescription: How to release\nexclude_agents: [windsurf]\n---\nTag it.",
+        )
+        .unwrap();
+        let mut project_config = load_project_config(root).unwrap();
+        project_config.enable_target(AMAZONQ_TARGET);
+        save_project_config(root, &project_config).unwrap();
+
+        let explanations = explain_path(root, Path::new("./src/daemon/mod.rs")).unwrap();
+        assert_eq!(
+            files(&explanations, "claude"),
+            vec![
+                ("AGENTS.md", "instructions".to_string()),
+                ("AGENTS.local.md", "instructions".to_string()),
+                ("src/daemon/AGENTS.md", "instructions".to_string()),
+            ]
+        );
+        assert_eq!(files(&explanations, "codex").len(), 2);
+        assert_eq!(
+            files(&explanations, "cursor"),
+            vec![
+                (
+                    ".rules/release.md",
+                    "manual, only when requested".to_string()
+                ),
+                (".rules/rust.md", "glob src/**/*.rs".to_string()),
+                (".rules/style.md", "always-on".to_string()),
+            ]
+        );
+        assert_eq!(files(&explanations, "windsurf").len(), 2);
+        assert_eq!(files(&explanations, AMAZONQ_TARGET).len(), 4);
+
+        let outside = explain_path(root, Path::new("../elsewhere.rs")).unwrap_err();
+        assert_eq!(outside.kind(), io::ErrorKind::InvalidInput);
+    }
+}
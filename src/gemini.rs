@@ -0,0 +1,321 @@
+//! Gemini CLI integration through `.gemini/settings.json`.
+//!
+//! Instead of a `GEMINI.md` symlink, Gemini CLI can be told to read AGENTS.md
+//! directly by setting `contextFileName` in the project's settings file. This
+//! avoids symlinks entirely, which helps on file systems that don't support them.
+//! All other settings are preserved when the file is updated. A project's
+//! `AGENTS.local.md` can be added to the context files as well.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::constants::{
+    AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, GEMINI_CONTEXT_FILE_KEY, GEMINI_FILENAME,
+    GEMINI_SETTINGS_PATH,
+};
+
+/// Points Gemini CLI at AGENTS.md by setting `contextFileName` in `.gemini/settings.json`.
+///
+/// A `contextFileName` of `GEMINI.md` is replaced. Any other configured context
+/// files are kept and AGENTS.md is added to the list. The settings file and the
+/// `.gemini` directory are created if they don't exist.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if the settings file was modified.
+///
+/// # Errors
+///
+/// Returns an error if the settings file cannot be read, isn't a JSON object,
+/// or cannot be written.
+pub fn set_gemini_context_file<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let settings_path = dir.as_ref().join(GEMINI_SETTINGS_PATH);
+    let mut settings = read_settings(&settings_path)?.unwrap_or_default();
+    let agents = Value::String(AGENTS_FILENAME.to_string());
+
+    let updated = match settings.get(GEMINI_CONTEXT_FILE_KEY) {
+        None => agents,
+        Some(Value::String(name)) if name == AGENTS_FILENAME => return Ok(false),
+        Some(Value::String(name)) if name == GEMINI_FILENAME => agents,
+        Some(Value::String(name)) => Value::Array(vec![Value::String(name.clone()), agents]),
+        Some(Value::Array(names)) if names.contains(&agents) => return Ok(false),
+        Some(Value::Array(names)) => {
+            let mut names = names.clone();
+            names.push(agents);
+            Value::Array(names)
+        }
+        Some(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "'{}' in {} must be a string or a list of strings",
+                    GEMINI_CONTEXT_FILE_KEY,
+                    settings_path.display()
+                ),
+            ))
+        }
+    };
+
+    settings.insert(GEMINI_CONTEXT_FILE_KEY.to_string(), updated);
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_settings(&settings_path, &settings)?;
+    Ok(true)
+}
+
+/// Reverts [`set_gemini_context_file`] by removing AGENTS.md from `contextFileName`.
+///
+/// The setting is removed entirely if AGENTS.md was the only context file. If the
+/// settings file ends up empty, it is deleted along with the `.gemini` directory
+/// when that directory is empty as well.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns `true` if the settings file was modified or removed.
+///
+/// # Errors
+///
+/// Returns an error if the settings file cannot be read, isn't a JSON object,
+/// or cannot be written or removed.
+pub fn unset_gemini_context_file<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let settings_path = dir.as_ref().join(GEMINI_SETTINGS_PATH);
+    let mut settings = match read_settings(&settings_path)? {
+        Some(settings) => settings,
+        None => return Ok(false),
+    };
+
+    match settings.get_mut(GEMINI_CONTEXT_FILE_KEY) {
+        Some(Value::String(name)) if name == AGENTS_FILENAME => {
+            settings.remove(GEMINI_CONTEXT_FILE_KEY);
+        }
+        Some(Value::Array(names)) if names.iter().any(|name| name == AGENTS_FILENAME) => {
+            names.retain(|name| name != AGENTS_FILENAME);
+            if names.is_empty() {
+                settings.remove(GEMINI_CONTEXT_FILE_KEY);
+            }
+        }
+        _ => return Ok(false),
+    }
+
+    if settings.is_empty() {
+        fs::remove_file(&settings_path)?;
+        if let Some(parent) = settings_path.parent() {
+            // Only succeeds if the directory is empty
+            let _ = fs::remove_dir(parent);
+        }
+    } else {
+        write_settings(&settings_path, &settings)?;
+    }
+    Ok(true)
+}
+
+/// Adds AGENTS.local.md to, or removes it from, `contextFileName`.
+///
+/// Only a settings file that already configures context files is changed, so
+/// this is meant to follow [`set_gemini_context_file`]. AGENTS.local.md is listed
+/// after the other context files.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `enabled` - Whether AGENTS.local.md should be a context file
+///
+/// # Returns
+///
+/// Returns `true` if the settings file was modified.
+///
+/// # Errors
+///
+/// Returns an error if the settings file cannot be read, isn't a JSON object,
+/// or cannot be written.
+pub fn set_gemini_local_context_file<P: AsRef<Path>>(dir: P, enabled: bool) -> io::Result<bool> {
+    let settings_path = dir.as_ref().join(GEMINI_SETTINGS_PATH);
+    let mut settings = match read_settings(&settings_path)? {
+        Some(settings) => settings,
+        None => return Ok(false),
+    };
+    let local = Value::String(AGENTS_LOCAL_FILENAME.to_string());
+
+    let mut names = match settings.get(GEMINI_CONTEXT_FILE_KEY) {
+        Some(Value::String(name)) => vec![Value::String(name.clone())],
+        Some(Value::Array(names)) => names.clone(),
+        _ => return Ok(false),
+    };
+    if names.contains(&local) == enabled {
+        return Ok(false);
+    }
+    if enabled {
+        names.push(local);
+    } else {
+        names.retain(|name| *name != local);
+    }
+
+    match names.len() {
+        0 => {
+            settings.remove(GEMINI_CONTEXT_FILE_KEY);
+        }
+        1 => {
+            settings.insert(GEMINI_CONTEXT_FILE_KEY.to_string(), names.remove(0));
+        }
+        _ => {
+            settings.insert(GEMINI_CONTEXT_FILE_KEY.to_string(), Value::Array(names));
+        }
+    }
+    write_settings(&settings_path, &settings)?;
+    Ok(true)
+}
+
+/// Checks whether `.gemini/settings.json` points Gemini CLI at AGENTS.md.
+///
+/// Returns `false` if the settings file is missing or cannot be parsed.
+pub fn gemini_uses_agents_file<P: AsRef<Path>>(dir: P) -> bool {
+    let settings_path = dir.as_ref().join(GEMINI_SETTINGS_PATH);
+    match read_settings(&settings_path) {
+        Ok(Some(settings)) => match settings.get(GEMINI_CONTEXT_FILE_KEY) {
+            Some(Value::String(name)) => name == AGENTS_FILENAME,
+            Some(Value::Array(names)) => names.iter().any(|name| name == AGENTS_FILENAME),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Reads the settings file as a JSON object, returning `None` if it doesn't exist.
+fn read_settings(settings_path: &Path) -> io::Result<Option<Map<String, Value>>> {
+    if !settings_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(settings_path)?;
+    if content.trim().is_empty() {
+        return Ok(Some(Map::new()));
+    }
+
+    match serde_json::from_str(&content) {
+        Ok(Value::Object(settings)) => Ok(Some(settings)),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} must contain a JSON object", settings_path.display()),
+        )),
+        Err(e) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse {}: {}", settings_path.display(), e),
+        )),
+    }
+}
+
+/// Writes the settings object as pretty-printed JSON with a trailing newline.
+fn write_settings(settings_path: &Path, settings: &Map<String, Value>) -> io::Result<()> {
+    let mut content = serde_json::to_string_pretty(settings).map_err(io::Error::other)?;
+    content.push('\n');
+    fs::write(settings_path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_set_and_unset_preserves_other_settings() {
+        let dir = tempdir().unwrap();
+        let settings_path = dir.path().join(GEMINI_SETTINGS_PATH);
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(&settings_path, r#"{"theme": "GitHub", "autoAccept": true}"#).unwrap();
+
+        assert!(set_gemini_context_file(dir.path()).unwrap());
+        assert!(!set_gemini_context_file(dir.path()).unwrap());
+        assert!(gemini_uses_agents_file(dir.path()));
+        assert_eq!(
+            fs::read_to_string(&settings_path).unwrap(),
+            "{\n  \"theme\": \"GitHub\",\n  \"autoAccept\": true,\n  \"contextFileName\": \"AGENTS.md\"\n}\n"
+        );
+
+        assert!(unset_gemini_context_file(dir.path()).unwrap());
+        assert!(!gemini_uses_agents_file(dir.path()));
+        assert_eq!(
+            fs::read_to_string(&settings_path).unwrap(),
+            "{\n  \"theme\": \"GitHub\",\n  \"autoAccept\": true\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_set_keeps_other_context_files() {
+        let dir = tempdir().unwrap();
+        let settings_path = dir.path().join(GEMINI_SETTINGS_PATH);
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(&settings_path, r#"{"contextFileName": "CONTEXT.md"}"#).unwrap();
+
+        set_gemini_context_file(dir.path()).unwrap();
+        let settings = read_settings(&settings_path).unwrap().unwrap();
+        assert_eq!(
+            settings[GEMINI_CONTEXT_FILE_KEY],
+            serde_json::json!(["CONTEXT.md", "AGENTS.md"])
+        );
+
+        unset_gemini_context_file(dir.path()).unwrap();
+        let settings = read_settings(&settings_path).unwrap().unwrap();
+        assert_eq!(
+            settings[GEMINI_CONTEXT_FILE_KEY],
+            serde_json::json!(["CONTEXT.md"])
+        );
+    }
+
+    #[test]
+    fn test_set_local_context_file() {
+        let dir = tempdir().unwrap();
+        let settings_path = dir.path().join(GEMINI_SETTINGS_PATH);
+
+        // Nothing to extend without configured context files
+        assert!(!set_gemini_local_context_file(dir.path(), true).unwrap());
+
+        set_gemini_context_file(dir.path()).unwrap();
+        assert!(set_gemini_local_context_file(dir.path(), true).unwrap());
+        assert!(!set_gemini_local_context_file(dir.path(), true).unwrap());
+        let settings = read_settings(&settings_path).unwrap().unwrap();
+        assert_eq!(
+            settings[GEMINI_CONTEXT_FILE_KEY],
+            serde_json::json!(["AGENTS.md", "AGENTS.local.md"])
+        );
+
+        assert!(set_gemini_local_context_file(dir.path(), false).unwrap());
+        let settings = read_settings(&settings_path).unwrap().unwrap();
+        assert_eq!(settings[GEMINI_CONTEXT_FILE_KEY], "AGENTS.md");
+    }
+
+    #[test]
+    fn test_unset_removes_generated_settings_file() {
+        let dir = tempdir().unwrap();
+
+        assert!(!unset_gemini_context_file(dir.path()).unwrap());
+        set_gemini_context_file(dir.path()).unwrap();
+        assert!(dir.path().join(GEMINI_SETTINGS_PATH).exists());
+
+        assert!(unset_gemini_context_file(dir.path()).unwrap());
+        assert!(!dir.path().join(".gemini").exists());
+    }
+
+    #[test]
+    fn test_invalid_settings_are_not_overwritten() {
+        let dir = tempdir().unwrap();
+        let settings_path = dir.path().join(GEMINI_SETTINGS_PATH);
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(&settings_path, "[1, 2]").unwrap();
+
+        let result = set_gemini_context_file(dir.path());
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read_to_string(&settings_path).unwrap(), "[1, 2]");
+    }
+}
@@ -0,0 +1,121 @@
+//! Sections of `.git/info/exclude` managed by known.
+//!
+//! Files that known creates for a single developer, such as links to global
+//! rules or the local override layer, must never be committed. Instead of
+//! touching the project's `.gitignore`, known keeps their patterns in labelled
+//! sections of the repository's `.git/info/exclude`, which is never shared.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::managed::{replace_section, Placement};
+
+/// Finds the `.git` directory of the repository a project belongs to.
+///
+/// Repositories whose `.git` is a file (worktrees and submodules) are not
+/// supported and return `None`.
+pub fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(".git"))
+        .find(|git_path| fs::symlink_metadata(git_path).is_ok())
+        .filter(|git_path| git_path.is_dir())
+}
+
+/// Returns the path of the exclude file of the repository a project belongs to.
+pub fn exclude_file_path(dir: &Path) -> Option<PathBuf> {
+    find_git_dir(dir).map(|git_dir| git_dir.join("info").join("exclude"))
+}
+
+/// Writes, updates or removes a section of `.git/info/exclude`.
+///
+/// The section is delimited by marker lines naming it, so several sections can
+/// coexist with the user's own patterns. With no patterns, the section is removed.
+/// Projects outside a Git repository are left alone, and the file is only
+/// rewritten when its content changes.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `section` - Name of the section (for example `global rules`)
+/// * `patterns` - The patterns the section should contain
+///
+/// # Errors
+///
+/// Returns an error if the exclude file cannot be read or written.
+pub fn update_exclude_section(dir: &Path, section: &str, patterns: &[String]) -> io::Result<()> {
+    let exclude_path = match exclude_file_path(dir) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let content = fs::read_to_string(&exclude_path).unwrap_or_default();
+
+    let (start_marker, end_marker) = section_markers(section);
+    let block = (!patterns.is_empty()).then(|| {
+        let mut block = format!("{}\n", start_marker);
+        for pattern in patterns {
+            block.push_str(pattern);
+            block.push('\n');
+        }
+        block.push_str(&end_marker);
+        block.push('\n');
+        block
+    });
+    let updated = replace_section(
+        &content,
+        &start_marker,
+        &end_marker,
+        block.as_deref(),
+        Placement::Bottom,
+    );
+    if updated != content {
+        if let Some(parent) = exclude_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&exclude_path, updated)?;
+    }
+    Ok(())
+}
+
+/// Returns the marker lines that open and close a section.
+fn section_markers(section: &str) -> (String, String) {
+    (
+        format!("# >>> known {} >>>", section),
+        format!("# <<< known {} <<<", section),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_update_exclude_section_keeps_other_sections() {
+        let dir = tempdir().unwrap();
+        let info = dir.path().join(".git").join("info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(info.join("exclude"), "*.log\n").unwrap();
+
+        update_exclude_section(dir.path(), "first", &["a".to_string()]).unwrap();
+        update_exclude_section(dir.path(), "second", &["b".to_string()]).unwrap();
+        update_exclude_section(dir.path(), "first", &["a".to_string(), "c".to_string()]).unwrap();
+        assert_eq!(
+            fs::read_to_string(info.join("exclude")).unwrap(),
+            "*.log\n# >>> known first >>>\na\nc\n# <<< known first <<<\n# >>> known second >>>\nb\n# <<< known second <<<\n"
+        );
+
+        update_exclude_section(dir.path(), "first", &[]).unwrap();
+        assert_eq!(
+            fs::read_to_string(info.join("exclude")).unwrap(),
+            "*.log\n# >>> known second >>>\nb\n# <<< known second <<<\n"
+        );
+    }
+
+    #[test]
+    fn test_update_exclude_section_outside_repository() {
+        let dir = tempdir().unwrap();
+        update_exclude_section(dir.path(), "first", &["a".to_string()]).unwrap();
+        assert!(!dir.path().join(".git").exists());
+    }
+}
@@ -0,0 +1,394 @@
+//! User-global instructions and rules shared by every project.
+//!
+//! Rules in the `rules` directory next to the known configuration file (for
+//! example `~/.config/known/rules`) are linked into the targets of every watched
+//! project. Their entries carry a `global-` prefix so they never collide with the
+//! project's own rules, and a managed section of `.git/info/exclude` keeps them
+//! out of version control.
+//!
+//! The `AGENTS.md` next to the configuration file holds personal instructions.
+//! `known global init` links each agent's user-level instruction file (such as
+//! `~/.claude/CLAUDE.md`) to it, or generates a copy where links aren't possible.
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::get_global_agents_path;
+use crate::constants::{GLOBAL_AGENTS_CONTENTS, GLOBAL_RULE_PREFIX};
+use crate::git::update_exclude_section;
+use crate::repair::backup_file;
+use crate::rules::BUILTIN_TARGETS;
+use crate::symlinks::create_symlink_to_file;
+
+/// Name of the section of `.git/info/exclude` holding the global rule patterns
+const EXCLUDE_SECTION: &str = "global rules";
+
+/// First line of a user-level agent file generated from the global AGENTS.md
+const GENERATED_HEADER: &str =
+    "<!-- Generated by known from the global AGENTS.md; edit that file instead -->";
+
+/// An agent's user-level instruction file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserAgentFile {
+    /// Name of the agent
+    pub agent: &'static str,
+    /// Path of the file relative to the home directory
+    pub path: &'static str,
+}
+
+/// The user-level instruction files kept in sync with the global AGENTS.md
+pub const USER_AGENT_FILES: [UserAgentFile; 3] = [
+    UserAgentFile {
+        agent: "claude",
+        path: ".claude/CLAUDE.md",
+    },
+    UserAgentFile {
+        agent: "gemini",
+        path: ".gemini/GEMINI.md",
+    },
+    UserAgentFile {
+        agent: "codex",
+        path: ".codex/AGENTS.md",
+    },
+];
+
+/// How a user-level agent file points at the global AGENTS.md
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserFileLink {
+    /// The file is a symlink to the global AGENTS.md
+    Symlink,
+    /// The file is a generated copy, because a symlink couldn't be created
+    Generated,
+}
+
+impl fmt::Display for UserFileLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserFileLink::Symlink => write!(f, "linked"),
+            UserFileLink::Generated => write!(f, "generated"),
+        }
+    }
+}
+
+/// The outcome of `known global init`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobalInitReport {
+    /// Path of the global AGENTS.md
+    pub agents_path: PathBuf,
+    /// Whether the global AGENTS.md was created by this run
+    pub created: bool,
+    /// The user-level file whose content the new global AGENTS.md started from
+    pub seeded_from: Option<PathBuf>,
+    /// Every user-level file and how it now points at the global AGENTS.md
+    pub files: Vec<(PathBuf, UserFileLink)>,
+    /// Backups of user-level files whose content differed from the global AGENTS.md
+    pub backups: Vec<PathBuf>,
+}
+
+/// Creates the global AGENTS.md and links every user-level agent file to it.
+///
+/// See [`init_global_agents_file_in`] for details.
+///
+/// # Errors
+///
+/// Returns an error if the home or configuration directory cannot be determined,
+/// or a file cannot be read, backed up or written.
+pub fn init_global_agents_file() -> io::Result<GlobalInitReport> {
+    init_global_agents_file_in(&home_dir()?, &get_global_agents_path()?)
+}
+
+/// Creates a global AGENTS.md and links the user-level agent files below a home directory to it.
+///
+/// If the global AGENTS.md doesn't exist yet, it starts from the first existing
+/// user-level file (in the order of [`USER_AGENT_FILES`]), or from a short default.
+/// Every user-level file is then replaced with a symlink to it; if creating the
+/// symlink fails, a generated copy is written instead. Existing files whose
+/// content differs from the global AGENTS.md are first backed up to `.known/backups`
+/// next to it, so nothing is lost. Running it again is safe.
+///
+/// # Arguments
+///
+/// * `home` - The home directory holding the user-level files
+/// * `agents_path` - Path of the global AGENTS.md
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read, backed up or written.
+pub fn init_global_agents_file_in(home: &Path, agents_path: &Path) -> io::Result<GlobalInitReport> {
+    let mut report = GlobalInitReport {
+        agents_path: agents_path.to_path_buf(),
+        ..GlobalInitReport::default()
+    };
+
+    if !agents_path.exists() {
+        let seed = USER_AGENT_FILES
+            .iter()
+            .map(|file| home.join(file.path))
+            .find_map(|path| user_file_content(&path).map(|content| (path, content)));
+        let content = match seed {
+            Some((path, content)) => {
+                report.seeded_from = Some(path);
+                content
+            }
+            None => GLOBAL_AGENTS_CONTENTS.to_string(),
+        };
+        if let Some(parent) = agents_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(agents_path, content)?;
+        report.created = true;
+    }
+
+    let agents_content = fs::read_to_string(agents_path)?;
+    let state_dir = agents_path.parent().unwrap_or(Path::new("."));
+    for file in USER_AGENT_FILES.iter() {
+        let path = home.join(file.path);
+        if let Some(content) = user_file_content(&path) {
+            if content.trim() != agents_content.trim() {
+                let name = format!(
+                    "{}-{}",
+                    file.agent,
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                );
+                report
+                    .backups
+                    .push(backup_file(state_dir, &name, content.as_bytes())?);
+            }
+        }
+        let link = link_user_agent_file(agents_path, &path)?;
+        report.files.push((path, link));
+    }
+
+    Ok(report)
+}
+
+/// Refreshes the generated copies of the global AGENTS.md below the home directory.
+///
+/// Symlinked user-level files follow the global AGENTS.md on their own; copies
+/// written because a symlink couldn't be created are rewritten when the content
+/// changed. Files not managed by known are left alone.
+///
+/// # Errors
+///
+/// Returns an error if the home or configuration directory cannot be determined,
+/// or a file cannot be read or written.
+pub fn sync_user_agent_files() -> io::Result<()> {
+    sync_user_agent_files_in(&home_dir()?, &get_global_agents_path()?)
+}
+
+/// Refreshes the generated copies of a global AGENTS.md below a home directory.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or written.
+pub fn sync_user_agent_files_in(home: &Path, agents_path: &Path) -> io::Result<()> {
+    let agents_content = match fs::read_to_string(agents_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let generated = render_generated_copy(&agents_content);
+
+    for file in USER_AGENT_FILES.iter() {
+        let path = home.join(file.path);
+        match fs::read_to_string(&path) {
+            Ok(content) if is_generated_copy(&content) && content != generated => {
+                fs::write(&path, &generated)?;
+                println!("Updated {} from the global AGENTS.md", path.display());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Points a user-level agent file at the global AGENTS.md, replacing what is there.
+fn link_user_agent_file(agents_path: &Path, path: &Path) -> io::Result<UserFileLink> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match create_symlink_to_file(agents_path, path) {
+        Ok(()) => Ok(UserFileLink::Symlink),
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to link {} ({}); writing a generated copy instead",
+                path.display(),
+                e
+            );
+            let content = fs::read_to_string(agents_path)?;
+            fs::write(path, render_generated_copy(&content))?;
+            Ok(UserFileLink::Generated)
+        }
+    }
+}
+
+/// Returns the content of a user-level file written by the user, if there is one.
+///
+/// Symlinks and copies generated by known don't count.
+fn user_file_content(path: &Path) -> Option<String> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if !metadata.file_type().is_file() {
+        return None;
+    }
+    fs::read_to_string(path)
+        .ok()
+        .filter(|content| !is_generated_copy(content))
+}
+
+/// Renders a generated copy of the global AGENTS.md.
+fn render_generated_copy(agents_content: &str) -> String {
+    format!(
+        "{}
+{}",
+        GENERATED_HEADER, agents_content
+    )
+}
+
+/// Checks whether a file's content is a copy generated by known.
+fn is_generated_copy(content: &str) -> bool {
+    content.starts_with(GENERATED_HEADER)
+}
+
+/// Returns the home directory of the user.
+fn home_dir() -> io::Result<PathBuf> {
+    std::env::var("HOME").map(PathBuf::from).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Unable to determine home directory for the user-level agent files",
+        )
+    })
+}
+
+/// Returns the file name a global rule's entries are derived from (`global-<name>`).
+pub fn global_file_name(rule_file_name: &OsStr) -> OsString {
+    let mut name = OsString::from(GLOBAL_RULE_PREFIX);
+    name.push(rule_file_name);
+    name
+}
+
+/// Adds or removes the patterns excluding global rule entries from Git.
+///
+/// The patterns live in a managed section of the repository's `.git/info/exclude`
+/// and match the global entries of every built-in target at any depth, so one
+/// section covers all packages of a monorepo. Projects outside a Git repository
+/// are left alone.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `enabled` - Whether the section should exist
+///
+/// # Errors
+///
+/// Returns an error if the exclude file cannot be read or written.
+pub fn update_git_exclude(dir: &Path, enabled: bool) -> io::Result<()> {
+    let patterns: Vec<String> = if enabled {
+        BUILTIN_TARGETS
+            .iter()
+            .map(|target| format!("**/{}/{}*", target.dir, GLOBAL_RULE_PREFIX))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    update_exclude_section(dir, EXCLUDE_SECTION, &patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_global_file_name() {
+        assert_eq!(
+            global_file_name(OsStr::new("style.md")),
+            OsString::from("global-style.md")
+        );
+    }
+
+    #[test]
+    fn test_init_global_agents_file() {
+        let home = tempdir().unwrap();
+        let agents_path = home.path().join(".config/known/AGENTS.md");
+        fs::create_dir_all(home.path().join(".claude")).unwrap();
+        fs::write(home.path().join(".claude/CLAUDE.md"), "Be brief.\n").unwrap();
+        fs::create_dir_all(home.path().join(".gemini")).unwrap();
+        fs::write(home.path().join(".gemini/GEMINI.md"), "Be thorough.\n").unwrap();
+
+        let report = init_global_agents_file_in(home.path(), &agents_path).unwrap();
+        assert!(report.created);
+        assert_eq!(
+            report.seeded_from,
+            Some(home.path().join(".claude/CLAUDE.md"))
+        );
+        assert_eq!(fs::read_to_string(&agents_path).unwrap(), "Be brief.\n");
+        // Only GEMINI.md differed from the new global file
+        assert_eq!(report.backups.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&report.backups[0]).unwrap(),
+            "Be thorough.\n"
+        );
+        for file in USER_AGENT_FILES.iter() {
+            let path = home.path().join(file.path);
+            assert_eq!(fs::read_link(&path).unwrap(), agents_path);
+        }
+        assert!(report
+            .files
+            .iter()
+            .all(|(_, link)| *link == UserFileLink::Symlink));
+
+        // A second run keeps everything as is
+        let report = init_global_agents_file_in(home.path(), &agents_path).unwrap();
+        assert!(!report.created);
+        assert!(report.backups.is_empty());
+    }
+
+    #[test]
+    fn test_sync_user_agent_files_refreshes_generated_copies() {
+        let home = tempdir().unwrap();
+        let agents_path = home.path().join("AGENTS.md");
+        fs::write(&agents_path, "New instructions\n").unwrap();
+        let codex_path = home.path().join(".codex/AGENTS.md");
+        fs::create_dir_all(codex_path.parent().unwrap()).unwrap();
+        fs::write(&codex_path, render_generated_copy("Old instructions\n")).unwrap();
+        let gemini_path = home.path().join(".gemini/GEMINI.md");
+        fs::create_dir_all(gemini_path.parent().unwrap()).unwrap();
+        fs::write(&gemini_path, "Hand-written").unwrap();
+
+        sync_user_agent_files_in(home.path(), &agents_path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&codex_path).unwrap(),
+            render_generated_copy("New instructions\n")
+        );
+        assert_eq!(fs::read_to_string(&gemini_path).unwrap(), "Hand-written");
+    }
+
+    #[test]
+    fn test_update_git_exclude() {
+        let dir = tempdir().unwrap();
+        let info = dir.path().join(".git").join("info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(info.join("exclude"), "# git ls-files --others\n*.log").unwrap();
+        let package = dir.path().join("packages").join("web");
+        fs::create_dir_all(&package).unwrap();
+
+        update_git_exclude(&package, true).unwrap();
+        update_git_exclude(&package, true).unwrap();
+        let content = fs::read_to_string(info.join("exclude")).unwrap();
+        assert!(
+            content.starts_with("# git ls-files --others\n*.log\n# >>> known global rules >>>\n")
+        );
+        assert!(content.contains("**/.cursor/rules/global-*\n"));
+        assert_eq!(content.matches("# >>> known global rules >>>").count(), 1);
+
+        update_git_exclude(dir.path(), false).unwrap();
+        assert_eq!(
+            fs::read_to_string(info.join("exclude")).unwrap(),
+            "# git ls-files --others\n*.log\n"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Import of legacy single-file rules into AGENTS.md or `.rules`.
+//!
+//! Before rules directories existed, Cursor, Windsurf and Cline read a single
+//! file at the project root (`.cursorrules`, `.windsurfrules`, `.clinerules`).
+//! `known init` imports such files, either as a labelled section of AGENTS.md or
+//! as a rule file, and then replaces them with a link or removes them, as set in
+//! the `legacy` section of `.known.json`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::{LegacyCleanup, LegacyConfig, LegacyImport};
+use crate::constants::{
+    AGENTS_FILENAME, CLINERULES_FILENAME, CURSORRULES_FILENAME, RULES_DIR, WINDSURFRULES_FILENAME,
+};
+use crate::symlinks::create_platform_symlink;
+use crate::transaction::Transaction;
+
+/// The legacy single-file rules files that are imported
+pub const LEGACY_RULE_FILES: [&str; 3] = [
+    CURSORRULES_FILENAME,
+    WINDSURFRULES_FILENAME,
+    CLINERULES_FILENAME,
+];
+
+/// A legacy rules file that was imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedLegacyFile {
+    /// File name of the legacy file (for example `.cursorrules`)
+    pub name: &'static str,
+    /// Project-relative path its content was imported to
+    pub destination: PathBuf,
+    /// What happened to the legacy file afterwards
+    pub cleanup: LegacyCleanup,
+}
+
+/// Finds the legacy rules files in a project.
+///
+/// Only regular files are returned; a legacy file that is already a link (for
+/// example from an earlier import) or a directory is not imported again.
+pub fn find_legacy_rule_files(dir: &Path) -> Vec<&'static str> {
+    LEGACY_RULE_FILES
+        .iter()
+        .copied()
+        .filter(|name| {
+            fs::symlink_metadata(dir.join(name))
+                .map(|metadata| metadata.file_type().is_file())
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Imports every legacy rules file of the transaction's project.
+///
+/// With [`LegacyImport::Section`], the content is appended to AGENTS.md under an
+/// `## Imported from <file>` heading. With [`LegacyImport::Rule`], it is written
+/// to `.rules/<file name without the dot>.md`, using a numbered name if a rule
+/// with different content already has that name. The legacy file is then
+/// replaced with a symlink to where its content went, or removed.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction recording the changes to the project directory
+/// * `config` - Import mode and cleanup from the project configuration
+///
+/// # Returns
+///
+/// Returns the imported files in the order they were processed.
+///
+/// # Errors
+///
+/// Returns an error if a legacy file cannot be read, or if AGENTS.md, the rule
+/// file or the link cannot be written.
+pub fn import_legacy_rule_files(
+    tx: &mut Transaction,
+    config: &LegacyConfig,
+) -> io::Result<Vec<ImportedLegacyFile>> {
+    let dir = tx.project_dir().to_path_buf();
+    let mut imported = Vec::new();
+
+    for name in find_legacy_rule_files(&dir) {
+        let legacy_path = dir.join(name);
+        let content = fs::read_to_string(&legacy_path)?;
+
+        let destination = match config.import {
+            LegacyImport::Section => {
+                append_section(tx, &dir.join(AGENTS_FILENAME), name, &content)?;
+                PathBuf::from(AGENTS_FILENAME)
+            }
+            LegacyImport::Rule => {
+                let rules_path = dir.join(RULES_DIR);
+                tx.create_dir(&rules_path)?;
+                let rule_name = write_rule(tx, &rules_path, name, &content)?;
+                Path::new(RULES_DIR).join(rule_name)
+            }
+        };
+
+        tx.remove_file(&legacy_path)?;
+        if config.cleanup == LegacyCleanup::Link {
+            create_platform_symlink(&destination, &legacy_path)?;
+        }
+
+        imported.push(ImportedLegacyFile {
+            name,
+            destination,
+            cleanup: config.cleanup,
+        });
+    }
+
+    Ok(imported)
+}
+
+/// Appends the content of a legacy file to AGENTS.md under a labelled heading.
+fn append_section(
+    tx: &mut Transaction,
+    agents_path: &Path,
+    name: &str,
+    content: &str,
+) -> io::Result<()> {
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut agents = fs::read_to_string(agents_path).unwrap_or_default();
+
+    if !agents.is_empty() {
+        if !agents.ends_with('\n') {
+            agents.push('\n');
+        }
+        agents.push('\n');
+    }
+    agents.push_str(&format!("## Imported from {}\n\n", name));
+    agents.push_str(content.trim_matches('\n'));
+    agents.push('\n');
+    tx.write(agents_path, agents)
+}
+
+/// Writes the content of a legacy file as a rule and returns the rule's file name.
+///
+/// A rule with identical content is reused instead of writing a duplicate.
+fn write_rule(
+    tx: &mut Transaction,
+    rules_path: &Path,
+    name: &str,
+    content: &str,
+) -> io::Result<String> {
+    let stem = name.trim_start_matches('.');
+    let mut rule_name = format!("{}.md", stem);
+    let mut counter = 2;
+    while let Ok(existing) = fs::read_to_string(rules_path.join(&rule_name)) {
+        if existing == content {
+            return Ok(rule_name);
+        }
+        rule_name = format!("{}-{}.md", stem, counter);
+        counter += 1;
+    }
+
+    tx.write(&rules_path.join(&rule_name), content)?;
+    Ok(rule_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn read_link(path: &Path) -> PathBuf {
+        fs::read_link(path).unwrap()
+    }
+
+    #[test]
+    fn test_import_as_section_and_link() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        fs::write(dir.path().join(CURSORRULES_FILENAME), "Use tabs.\n").unwrap();
+        fs::write(dir.path().join(CLINERULES_FILENAME), "Write tests.").unwrap();
+
+        let imported = Transaction::run(dir.path(), "init", |tx| {
+            import_legacy_rule_files(tx, &LegacyConfig::default())
+        })
+        .unwrap();
+
+        assert_eq!(imported.len(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.path().join(AGENTS_FILENAME)).unwrap(),
+            "# Agents\n\n## Imported from .cursorrules\n\nUse tabs.\n\n## Imported from .clinerules\n\nWrite tests.\n"
+        );
+        assert_eq!(
+            read_link(&dir.path().join(CURSORRULES_FILENAME)),
+            Path::new(AGENTS_FILENAME)
+        );
+
+        // Linked files are not imported again
+        assert!(find_legacy_rule_files(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_import_as_rule_and_remove() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("windsurfrules.md"), "Other rules").unwrap();
+        fs::write(dir.path().join(WINDSURFRULES_FILENAME), "Use tabs.").unwrap();
+
+        let config = LegacyConfig {
+            import: LegacyImport::Rule,
+            cleanup: LegacyCleanup::Remove,
+        };
+        let imported = Transaction::run(dir.path(), "init", |tx| {
+            import_legacy_rule_files(tx, &config)
+        })
+        .unwrap();
+
+        assert_eq!(
+            imported[0].destination,
+            Path::new(RULES_DIR).join("windsurfrules-2.md")
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("windsurfrules-2.md")).unwrap(),
+            "Use tabs."
+        );
+        assert!(fs::symlink_metadata(dir.path().join(WINDSURFRULES_FILENAME)).is_err());
+    }
+}
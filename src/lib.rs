@@ -4,21 +4,82 @@
 //! in project directories, with support for renaming existing CLAUDE.md files.
 
 pub mod agents;
+pub mod aider;
 pub mod autostart;
+pub mod budget;
+pub mod claude;
+pub mod conditional;
 pub mod config;
 pub mod constants;
 pub mod daemon;
+pub mod eject;
+pub mod explain;
+pub mod gemini;
+pub mod git;
+pub mod global;
+pub mod legacy;
+pub mod lint;
+pub mod managed;
+pub mod merge;
+pub mod migration;
+pub mod monorepo;
+pub mod repair;
+pub mod rules;
 pub mod single_instance;
+pub mod status;
 pub mod symlinks;
+pub mod templates;
+pub mod transaction;
 
 // Re-export public API functions
-pub use agents::{create_agents_file, create_agents_file_in_dir};
+pub use agents::{
+    create_agents_file, create_agents_file_in_dir, create_agents_file_with_options, InitOptions,
+};
+pub use aider::{
+    remove_aider_config_block, sync_aider_config, sync_aider_config_if_enabled,
+    unmanage_aider_config,
+};
 pub use autostart::{disable_autostart, enable_autostart, is_autostart_enabled};
+pub use budget::{
+    budget_report, estimate_tokens, AgentBudget, BudgetReport, LoadedFile, RuleBudget,
+};
+pub use claude::{
+    check_claude_import, remove_claude_import, sync_claude_import, unmanage_claude_import,
+    ImportState,
+};
+pub use conditional::{has_conditional_sections, render_for_agent, sync_symlinked_alias};
 pub use config::{
     add_directory_to_config, add_directory_to_config_file, get_config_file_path, load_config,
-    load_config_from_file, remove_directory_from_config, remove_directory_from_config_file,
-    save_config, Config,
+    load_config_from_file, load_project_config, remove_directory_from_config,
+    remove_directory_from_config_file, save_config, save_project_config, AliasConfig, BudgetConfig,
+    ClaudeStrategy, Config, GeminiStrategy, LegacyCleanup, LegacyConfig, LegacyImport, LintConfig,
+    ProjectConfig, RuleProfile,
 };
 pub use daemon::start_daemon;
-pub use single_instance::{is_daemon_running, stop_daemon, SingleInstanceLock};
-pub use symlinks::{create_symlinks, create_symlinks_in_dir};
+pub use eject::{eject_project, EjectOptions, EjectReport};
+pub use explain::{explain_path, AgentExplanation, AppliedFile, Reason};
+pub use gemini::{gemini_uses_agents_file, set_gemini_context_file, unset_gemini_context_file};
+pub use global::{
+    init_global_agents_file, GlobalInitReport, UserAgentFile, UserFileLink, USER_AGENT_FILES,
+};
+pub use legacy::{find_legacy_rule_files, ImportedLegacyFile};
+pub use lint::{lint_project, LintCheck, LintFinding, Severity, LINT_CHECKS};
+pub use merge::{merge_instruction_files, MergeStrategy, MergedInstructions};
+pub use migration::{ConflictPolicy, MigrationAction, MigrationDecision, MigrationReport};
+pub use monorepo::discover_packages;
+pub use repair::{repair_alias, AliasRepair};
+pub use rules::manage::{
+    create_rule, list_profiles, list_rules, rename_rule, set_rule_enabled, show_rule, use_profile,
+    NewRule, ProfileSummary, RuleSummary, RuleToggle, RuleView,
+};
+pub use single_instance::{
+    is_daemon_running, stop_daemon, stop_daemon_and_wait, SingleInstanceLock,
+};
+pub use status::{project_status, StatusCheck};
+pub use symlinks::{
+    create_symlinks, create_symlinks_in_dir, create_symlinks_with_options, SymlinkOptions,
+};
+pub use templates::{
+    detect_project, render_agents_template, ProjectInfo, RenderedTemplate, BUILTIN_TEMPLATES,
+};
+pub use transaction::{move_path, undo_last_run, PreservedEdit, Transaction, UndoReport};
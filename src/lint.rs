@@ -0,0 +1,665 @@
+//! Checks for mistakes in AGENTS.md and `.rules` files, reported by `known lint`.
+//!
+//! Each check has a stable ID that can be listed in the `lint.disable` setting
+//! of `.known.json` to skip it for a project:
+//!
+//! ```json
+//! { "lint": { "disable": ["unmatched-glob"] } }
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+
+use crate::conditional::render_for_agent;
+use crate::config::{load_project_config, LintConfig};
+use crate::constants::{
+    AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, AIDER_TARGET, CURSOR_TARGET, PROJECT_CONFIG_FILENAME,
+    RULES_DIR, RULES_LOCAL_DIR, WINDSURF_TARGET,
+};
+use crate::monorepo::{discover_packages, glob_matches, list_project_files};
+use crate::rules::{list_rule_files, split_frontmatter, Rule, RuleMetadata, BUILTIN_TARGETS};
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file is broken for at least one agent
+    Error,
+    /// The file works but probably not as intended
+    Warning,
+}
+
+/// A check run by `known lint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintCheck {
+    /// Stable ID of the check, used in output and in `lint.disable`
+    pub id: &'static str,
+    /// Severity of the check's findings
+    pub severity: Severity,
+    /// What the check looks for
+    pub description: &'static str,
+}
+
+/// Frontmatter that cannot be parsed
+pub const INVALID_FRONTMATTER: &str = "invalid-frontmatter";
+/// Files that aren't valid UTF-8
+pub const INVALID_ENCODING: &str = "invalid-encoding";
+/// Frontmatter keys that no target understands
+pub const UNKNOWN_KEY: &str = "unknown-key";
+/// Names in `agents` or `exclude_agents` that aren't targets
+pub const UNKNOWN_AGENT: &str = "unknown-agent";
+/// Conditional sections in AGENTS.md that are nested, unclosed or stray
+pub const INVALID_SECTION: &str = "invalid-section";
+/// Globs that match no file in the project
+pub const UNMATCHED_GLOB: &str = "unmatched-glob";
+/// Rules with the same title
+pub const DUPLICATE_TITLE: &str = "duplicate-title";
+/// Files without any instructions
+pub const EMPTY_FILE: &str = "empty-file";
+/// Relative links to files that don't exist
+pub const BROKEN_LINK: &str = "broken-link";
+/// Files over a size limit of a tool that reads them
+pub const FILE_TOO_LARGE: &str = "file-too-large";
+
+/// Every check run by `known lint`
+pub const LINT_CHECKS: [LintCheck; 10] = [
+    LintCheck {
+        id: INVALID_FRONTMATTER,
+        severity: Severity::Error,
+        description: "frontmatter that cannot be parsed",
+    },
+    LintCheck {
+        id: INVALID_ENCODING,
+        severity: Severity::Error,
+        description: "files that aren't valid UTF-8",
+    },
+    LintCheck {
+        id: UNKNOWN_KEY,
+        severity: Severity::Warning,
+        description: "frontmatter keys that no target understands",
+    },
+    LintCheck {
+        id: UNKNOWN_AGENT,
+        severity: Severity::Warning,
+        description: "names in agents or exclude_agents that aren't targets",
+    },
+    LintCheck {
+        id: INVALID_SECTION,
+        severity: Severity::Error,
+        description: "conditional sections in AGENTS.md that are nested, unclosed or stray",
+    },
+    LintCheck {
+        id: UNMATCHED_GLOB,
+        severity: Severity::Warning,
+        description: "globs that match no file in the project",
+    },
+    LintCheck {
+        id: DUPLICATE_TITLE,
+        severity: Severity::Warning,
+        description: "rules with the same title",
+    },
+    LintCheck {
+        id: EMPTY_FILE,
+        severity: Severity::Error,
+        description: "files without any instructions",
+    },
+    LintCheck {
+        id: BROKEN_LINK,
+        severity: Severity::Error,
+        description: "relative links to files that don't exist",
+    },
+    LintCheck {
+        id: FILE_TOO_LARGE,
+        severity: Severity::Warning,
+        description: "files over a size limit of a tool that reads them",
+    },
+];
+
+/// Frontmatter keys understood by known or by one of its targets
+const KNOWN_FRONTMATTER_KEYS: [&str; 10] = [
+    "description",
+    "globs",
+    "alwaysApply",
+    "trigger",
+    "inclusion",
+    "fileMatchPattern",
+    "agents",
+    "exclude_agents",
+    "enabled",
+    "tags",
+];
+
+/// Characters Windsurf reads from a single rule file
+const WINDSURF_RULE_MAX_CHARS: usize = 6000;
+
+/// Lines Cursor recommends a rule to stay under
+const CURSOR_RULE_MAX_LINES: usize = 500;
+
+/// Bytes of AGENTS.md that Codex CLI reads by default
+const CODEX_AGENTS_MAX_BYTES: usize = 32 * 1024;
+
+/// A problem found by `known lint`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct LintFinding {
+    /// ID of the check that found the problem
+    pub check: &'static str,
+    /// Severity of the problem
+    pub severity: Severity,
+    /// Path of the file, relative to the project directory
+    pub path: String,
+    /// Line of the problem, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+/// Lints AGENTS.md, AGENTS.local.md and the rules of a project.
+///
+/// In recursive mode, the files of every package are linted as well. Checks
+/// listed in the `lint.disable` setting of `.known.json` are skipped.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns the findings, sorted by path and line.
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if `lint.disable` names an unknown check, or an
+/// error if the project configuration is malformed or a file cannot be read.
+pub fn lint_project(dir: &Path) -> io::Result<Vec<LintFinding>> {
+    let project_config = load_project_config(dir)?;
+    if let Some(unknown) = project_config
+        .lint
+        .disable
+        .iter()
+        .find(|id| find_check(id).is_none())
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unknown lint check '{}' in {}; expected one of: {}",
+                unknown,
+                PROJECT_CONFIG_FILENAME,
+                LINT_CHECKS
+                    .iter()
+                    .map(|check| check.id)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    let mut linter = Linter {
+        root: dir,
+        config: &project_config.lint,
+        files: list_project_files(dir),
+        findings: Vec::new(),
+    };
+    let mut dirs = vec![dir.to_path_buf()];
+    if project_config.recursive {
+        dirs.extend(discover_packages(dir)?);
+    }
+    for package in &dirs {
+        linter.lint_dir(package)?;
+    }
+
+    let mut findings = linter.findings;
+    findings.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+    Ok(findings)
+}
+
+/// Finds a check by its ID.
+pub fn find_check(id: &str) -> Option<&'static LintCheck> {
+    LINT_CHECKS.iter().find(|check| check.id == id)
+}
+
+/// State shared by the checks of one `known lint` run
+struct Linter<'a> {
+    root: &'a Path,
+    config: &'a LintConfig,
+    /// Files of the project, relative to the root, for matching globs
+    files: Vec<String>,
+    findings: Vec<LintFinding>,
+}
+
+impl Linter<'_> {
+    /// Lints the instruction files and rules of a project or package directory.
+    fn lint_dir(&mut self, dir: &Path) -> io::Result<()> {
+        for file_name in [AGENTS_FILENAME, AGENTS_LOCAL_FILENAME] {
+            let path = dir.join(file_name);
+            if path.is_file() {
+                self.lint_instructions(&path)?;
+            }
+        }
+
+        // Title of the first rule with each title, to report the ones after it
+        let mut titles: HashMap<String, String> = HashMap::new();
+        for rules_dir in [RULES_DIR, RULES_LOCAL_DIR] {
+            for rule_path in list_rule_files(&dir.join(rules_dir))? {
+                if let Some(title) = self.lint_rule(dir, &rule_path)? {
+                    let relative = self.relative(&rule_path);
+                    match titles.get(&title.to_lowercase()) {
+                        Some(first) => self.report(
+                            DUPLICATE_TITLE,
+                            &rule_path,
+                            None,
+                            format!("the title '{}' is also used by {}", title, first),
+                        ),
+                        None => {
+                            titles.insert(title.to_lowercase(), relative);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lints AGENTS.md or AGENTS.local.md.
+    fn lint_instructions(&mut self, path: &Path) -> io::Result<()> {
+        let content = match self.read_text(path)? {
+            Some(content) => content,
+            None => return Ok(()),
+        };
+        if content.trim().is_empty() {
+            self.report(EMPTY_FILE, path, None, "the file has no instructions");
+        }
+        if let Err(e) = render_for_agent(&content, "claude") {
+            self.report(INVALID_SECTION, path, None, e.to_string());
+        }
+        if content.len() > CODEX_AGENTS_MAX_BYTES {
+            self.report(
+                FILE_TOO_LARGE,
+                path,
+                None,
+                format!(
+                    "{} bytes; Codex CLI only reads the first {}",
+                    content.len(),
+                    CODEX_AGENTS_MAX_BYTES
+                ),
+            );
+        }
+        self.lint_links(path, &content, 0);
+        Ok(())
+    }
+
+    /// Lints a rules file.
+    ///
+    /// # Returns
+    ///
+    /// Returns the title of the rule, if it has one.
+    fn lint_rule(&mut self, dir: &Path, path: &Path) -> io::Result<Option<String>> {
+        let content = match self.read_text(path)? {
+            Some(content) => content,
+            None => return Ok(None),
+        };
+        let frontmatter = match split_frontmatter(&content) {
+            Ok((frontmatter, _)) => frontmatter,
+            Err(e) => {
+                self.report(INVALID_FRONTMATTER, path, Some(1), e.to_string());
+                return Ok(None);
+            }
+        };
+        let rule = Rule::parse(path, &content)?;
+        let body_offset = content[..content.len() - rule.body.len()].lines().count();
+
+        for (key, _) in frontmatter
+            .iter()
+            .flat_map(|frontmatter| &frontmatter.entries)
+        {
+            if !KNOWN_FRONTMATTER_KEYS.contains(&key.as_str()) {
+                self.report(
+                    UNKNOWN_KEY,
+                    path,
+                    key_line(&content, key),
+                    format!("'{}' is not a frontmatter key known to any target", key),
+                );
+            }
+        }
+        for (key, names) in [
+            ("agents", &rule.metadata.agents),
+            ("exclude_agents", &rule.metadata.exclude_agents),
+        ] {
+            for name in names.iter().filter(|name| !is_target_name(name)) {
+                self.report(
+                    UNKNOWN_AGENT,
+                    path,
+                    key_line(&content, key),
+                    format!("'{}' in {} is not a target", name, key),
+                );
+            }
+        }
+
+        for glob in &rule.metadata.globs {
+            if !self.glob_matches_any(dir, glob) {
+                self.report(
+                    UNMATCHED_GLOB,
+                    path,
+                    key_line(&content, "globs"),
+                    format!("'{}' matches no file", glob),
+                );
+            }
+        }
+
+        if rule.body.trim().is_empty() {
+            self.report(EMPTY_FILE, path, None, "the rule has no instructions");
+        }
+        self.lint_rule_size(path, &content, &rule.metadata);
+        self.lint_links(path, &rule.body, body_offset);
+
+        Ok(title(&rule.body))
+    }
+
+    /// Reports a rule over the size limit of a target it is synced to.
+    fn lint_rule_size(&mut self, path: &Path, content: &str, metadata: &RuleMetadata) {
+        let chars = content.chars().count();
+        if metadata.applies_to(WINDSURF_TARGET) && chars > WINDSURF_RULE_MAX_CHARS {
+            self.report(
+                FILE_TOO_LARGE,
+                path,
+                None,
+                format!(
+                    "{} characters; Windsurf only reads the first {}",
+                    chars, WINDSURF_RULE_MAX_CHARS
+                ),
+            );
+        }
+        let lines = content.lines().count();
+        if metadata.applies_to(CURSOR_TARGET) && lines > CURSOR_RULE_MAX_LINES {
+            self.report(
+                FILE_TOO_LARGE,
+                path,
+                None,
+                format!(
+                    "{} lines; Cursor recommends keeping rules under {}",
+                    lines, CURSOR_RULE_MAX_LINES
+                ),
+            );
+        }
+    }
+
+    /// Reports relative Markdown links whose file doesn't exist.
+    ///
+    /// Links in fenced code blocks and inline code are ignored. Paths starting
+    /// with `/` are resolved from the project root.
+    fn lint_links(&mut self, path: &Path, content: &str, line_offset: usize) {
+        let base = path.parent().unwrap_or(self.root);
+        let mut in_fence = false;
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            for link in relative_links(line) {
+                let resolved = match link.strip_prefix('/') {
+                    Some(from_root) => self.root.join(from_root),
+                    None => base.join(&link),
+                };
+                if !resolved.exists() {
+                    self.report(
+                        BROKEN_LINK,
+                        path,
+                        Some(line_offset + index + 1),
+                        format!("'{}' doesn't exist", link),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reads a file as text, reporting it if it isn't valid UTF-8.
+    ///
+    /// # Returns
+    ///
+    /// Returns the content, or `None` if the file isn't valid UTF-8.
+    fn read_text(&mut self, path: &Path) -> io::Result<Option<String>> {
+        match String::from_utf8(fs::read(path)?) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) => {
+                let line = e.as_bytes()[..e.utf8_error().valid_up_to()]
+                    .iter()
+                    .filter(|&&byte| byte == b'\n')
+                    .count()
+                    + 1;
+                self.report(
+                    INVALID_ENCODING,
+                    path,
+                    Some(line),
+                    "the file is not valid UTF-8",
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Checks whether a glob of a rule in a directory matches a file below it.
+    fn glob_matches_any(&self, dir: &Path, glob: &str) -> bool {
+        let prefix = match dir.strip_prefix(self.root) {
+            Ok(relative) if relative.as_os_str().is_empty() => String::new(),
+            Ok(relative) => format!("{}/", slash_separated(relative)),
+            Err(_) => return true,
+        };
+        self.files.iter().any(|file| {
+            file.strip_prefix(&prefix)
+                .is_some_and(|file| glob_matches(glob, file))
+        })
+    }
+
+    /// Records a finding, unless its check is disabled.
+    fn report(
+        &mut self,
+        check: &'static str,
+        path: &Path,
+        line: Option<usize>,
+        message: impl Into<String>,
+    ) {
+        if !self.config.is_enabled(check) {
+            return;
+        }
+        let severity = find_check(check)
+            .map(|check| check.severity)
+            .unwrap_or(Severity::Error);
+        self.findings.push(LintFinding {
+            check,
+            severity,
+            path: self.relative(path),
+            line,
+            message: message.into(),
+        });
+    }
+
+    /// Returns a path relative to the project root, slash-separated.
+    fn relative(&self, path: &Path) -> String {
+        match path.strip_prefix(self.root) {
+            Ok(relative) => slash_separated(relative),
+            Err(_) => path.display().to_string(),
+        }
+    }
+}
+
+/// Checks whether a name can be used in `agents` or `exclude_agents`.
+fn is_target_name(name: &str) -> bool {
+    name.eq_ignore_ascii_case(AIDER_TARGET)
+        || BUILTIN_TARGETS
+            .iter()
+            .any(|target| target.name.eq_ignore_ascii_case(name))
+}
+
+/// Returns the line of a frontmatter key, counting from 1.
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    content
+        .lines()
+        .skip(1)
+        .take_while(|line| line.trim_end() != "---")
+        .position(|line| {
+            line.strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|index| index + 2)
+}
+
+/// Returns the text of the first top-level heading of a Markdown body.
+fn title(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+/// Returns the relative file targets of the Markdown links on a line.
+///
+/// URLs, anchors within the same file and links in inline code are skipped, and
+/// anchors and titles are stripped from the targets.
+fn relative_links(line: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut in_code = false;
+    let mut rest = line;
+    while let Some(index) = rest.find(['`', ']']) {
+        let is_code = rest[index..].starts_with('`');
+        rest = &rest[index + 1..];
+        if is_code {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || !rest.starts_with('(') {
+            continue;
+        }
+        let end = match rest.find(')') {
+            Some(end) => end,
+            None => break,
+        };
+        let destination = rest[1..end].trim();
+        let target = match destination.strip_prefix('<') {
+            Some(quoted) => quoted.split('>').next().unwrap_or_default(),
+            None => destination.split_whitespace().next().unwrap_or_default(),
+        };
+        let target = target.split('#').next().unwrap_or_default();
+        rest = &rest[end + 1..];
+        if target.is_empty() || target.contains("://") || target.starts_with("mailto:") {
+            continue;
+        }
+        links.push(target.to_string());
+    }
+    links
+}
+
+/// Joins the components of a relative path with `/`.
+fn slash_separated(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::save_project_config;
+    use tempfile::tempdir;
+
+    fn checks(findings: &[LintFinding]) -> Vec<(&str, &str)> {
+        findings
+            .iter()
+            .map(|finding| (finding.check, finding.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_project_reports_each_check() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(
+            root.join(AGENTS_FILENAME),
+            "# Project\n\nSee [the guide](docs/guide.md) and [the site](https://example.com).\n<!-- known:only claude -->\n",
+        )
+        .unwrap();
+        let rules = root.join(RULES_DIR);
+        fs::create_dir(&rules).unwrap();
+        fs::write(
+            rules.join("rust.md"),
+            "---\nglobs: [\"src/**/*.rs\", \"*.py\"]\nagents: [cursor, vim]\npriority: high\n---\n# Style\n\nUse `[x](missing.md)` in docs.\n",
+        )
+        .unwrap();
+        fs::write(rules.join("style.md"), "# style\n\nSee [rust](rust.md).\n").unwrap();
+        fs::write(rules.join("empty.md"), "---\nalwaysApply: true\n---\n\n").unwrap();
+        fs::write(rules.join("broken.md"), "---\nglobs: *.rs\n").unwrap();
+        fs::write(rules.join("long.md"), "x\n".repeat(600)).unwrap();
+
+        let findings = lint_project(root).unwrap();
+        assert_eq!(
+            checks(&findings),
+            vec![
+                (INVALID_FRONTMATTER, ".rules/broken.md"),
+                (EMPTY_FILE, ".rules/empty.md"),
+                (FILE_TOO_LARGE, ".rules/long.md"),
+                (UNMATCHED_GLOB, ".rules/rust.md"),
+                (UNKNOWN_AGENT, ".rules/rust.md"),
+                (UNKNOWN_KEY, ".rules/rust.md"),
+                (DUPLICATE_TITLE, ".rules/style.md"),
+                (INVALID_SECTION, AGENTS_FILENAME),
+                (BROKEN_LINK, AGENTS_FILENAME),
+            ]
+        );
+        let unmatched = &findings[3];
+        assert_eq!(unmatched.line, Some(2));
+        assert_eq!(unmatched.message, "'*.py' matches no file");
+        assert_eq!(findings[8].line, Some(3));
+        assert_eq!(findings[8].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_lint_project_reports_invalid_encoding() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Project\n").unwrap();
+        let rules = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules).unwrap();
+        fs::write(rules.join("latin1.md"), b"# Style\n\nCaf\xe9\n").unwrap();
+
+        let findings = lint_project(dir.path()).unwrap();
+        assert_eq!(
+            checks(&findings),
+            vec![(INVALID_ENCODING, ".rules/latin1.md")]
+        );
+        assert_eq!(findings[0].line, Some(3));
+        assert_eq!(findings[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_lint_project_skips_disabled_checks() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "").unwrap();
+        let mut project_config = load_project_config(dir.path()).unwrap();
+        project_config.lint.disable = vec![EMPTY_FILE.to_string()];
+        save_project_config(dir.path(), &project_config).unwrap();
+        assert!(lint_project(dir.path()).unwrap().is_empty());
+
+        project_config.lint.disable = vec!["no-such-check".to_string()];
+        save_project_config(dir.path(), &project_config).unwrap();
+        let error = lint_project(dir.path()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_relative_links() {
+        assert_eq!(
+            relative_links("[a](a.md#part) [b](<b c.md>) [c](#top) `[d](d.md)` [e](mailto:x@y)"),
+            vec!["a.md", "b c.md"]
+        );
+    }
+}
@@ -1,12 +1,22 @@
 use clap::{Parser, Subcommand};
+use known::rules::Activation;
 use known::{
-    add_directory_to_config, create_agents_file, create_symlinks, disable_autostart,
-    enable_autostart, is_autostart_enabled, is_daemon_running, remove_directory_from_config,
-    start_daemon, stop_daemon,
+    add_directory_to_config, budget_report, create_agents_file_with_options, create_rule,
+    create_symlinks_with_options, disable_autostart, discover_packages, eject_project,
+    enable_autostart, explain_path, init_global_agents_file, is_autostart_enabled,
+    is_daemon_running, lint_project, list_profiles, list_rules, load_project_config,
+    project_status, remove_directory_from_config, rename_rule, set_rule_enabled, show_rule,
+    start_daemon, stop_daemon, stop_daemon_and_wait, undo_last_run, use_profile, ClaudeStrategy,
+    ConflictPolicy, EjectOptions, GeminiStrategy, InitOptions, LegacyCleanup, LegacyImport,
+    MergeStrategy, NewRule, Severity, SymlinkOptions,
 };
 use std::io;
 use std::process::{Command, Stdio};
 use std::sync::mpsc;
+use std::time::Duration;
+
+/// How long `known eject` waits for a running daemon to exit
+const DAEMON_STOP_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Parser)]
 #[command(name = "known")]
@@ -19,9 +29,47 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize project by creating AGENTS.md file
-    Init,
+    Init {
+        /// Where legacy .cursorrules, .windsurfrules and .clinerules files are imported
+        /// ('section' of AGENTS.md or 'rule' in .rules), saved to .known.json
+        #[arg(long, value_name = "MODE")]
+        legacy_import: Option<LegacyImport>,
+        /// What happens to legacy files after the import ('link' or 'remove'), saved to .known.json
+        #[arg(long, value_name = "CLEANUP")]
+        legacy_cleanup: Option<LegacyCleanup>,
+        /// How CLAUDE.md and GEMINI.md are combined when both exist
+        /// ('merge', 'prefer-claude', 'prefer-gemini' or 'empty')
+        #[arg(long, value_name = "STRATEGY", default_value = "merge")]
+        strategy: MergeStrategy,
+        /// Template for a new AGENTS.md ('minimal', 'generic', 'rust', 'node', 'python', 'go'
+        /// or a user template); detected from the project by default
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+    },
     /// Create symlinks from AGENTS.md to CLAUDE.md and GEMINI.md
-    Symlink,
+    Symlink {
+        /// How Claude Code is pointed at AGENTS.md ('symlink' or 'import'), saved to .known.json
+        #[arg(long, value_name = "STRATEGY")]
+        claude: Option<ClaudeStrategy>,
+        /// How Gemini CLI is pointed at AGENTS.md ('symlink' or 'settings'), saved to .known.json
+        #[arg(long, value_name = "STRATEGY")]
+        gemini: Option<GeminiStrategy>,
+        /// What to do when a migrated rule conflicts with a file in .rules
+        /// ('skip', 'keep-both', 'prefer-newer' or 'fail')
+        #[arg(long, value_name = "POLICY", default_value = "skip")]
+        on_conflict: ConflictPolicy,
+        /// Preview the rules migration without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Also link nested packages that have their own AGENTS.md (honours .gitignore),
+        /// saved to .known.json
+        #[arg(long)]
+        recursive: bool,
+        /// Give nested packages the rules of the .rules directories above them as well
+        /// (implies --recursive), saved to .known.json
+        #[arg(long)]
+        inherit: bool,
+    },
     /// Start daemon to watch all configured directories and maintain symlinks
     Start,
     /// Run daemon process (internal command, used by start)
@@ -49,6 +97,154 @@ enum Commands {
     Stop,
     /// List all watched directories from the configuration file
     List,
+    /// Check that AGENTS.md, its aliases and the rules targets of the current project are intact
+    Status,
+    /// Check AGENTS.md and the rules of the current project for mistakes
+    Lint {
+        /// Print the findings as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also fail on warnings
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Estimate the tokens each agent loads from the instructions and rules of the current project
+    Budget {
+        /// Tokens any agent may always load, overriding the budget in .known.json
+        #[arg(long, value_name = "TOKENS")]
+        limit: Option<usize>,
+    },
+    /// Show which instructions and rules each agent applies to a file
+    Explain {
+        /// Path of the file, relative to the current directory
+        path: std::path::PathBuf,
+    },
+    /// Revert the last init, symlink or profile run in the current project
+    Undo,
+    /// Stop managing the current project, replacing every link with a regular file
+    Eject {
+        /// Also delete the .rules directory once its files were copied into the targets
+        #[arg(long)]
+        remove_rules: bool,
+    },
+    /// Manage the personal instructions shared by every project
+    Global {
+        #[command(subcommand)]
+        command: GlobalCommands,
+    },
+    /// Manage the rules in .rules
+    Rule {
+        #[command(subcommand)]
+        command: RuleCommands,
+    },
+    /// Switch between the named rule sets defined in .known.json
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum GlobalCommands {
+    /// Create the global AGENTS.md and link ~/.claude/CLAUDE.md, ~/.gemini/GEMINI.md
+    /// and ~/.codex/AGENTS.md to it
+    Init,
+}
+
+#[derive(Subcommand)]
+enum RuleCommands {
+    /// Create a rule in .rules with canonical frontmatter
+    New {
+        /// File name of the rule; .md is added if it has no extension
+        #[arg(value_name = "NAME")]
+        name: String,
+        /// When the rule should be applied, for agents that choose rules themselves
+        #[arg(long)]
+        description: Option<String>,
+        /// Files the rule applies to, comma-separated or repeated
+        #[arg(
+            long,
+            value_name = "GLOB",
+            value_delimiter = ',',
+            conflicts_with = "always"
+        )]
+        globs: Vec<String>,
+        /// Always apply the rule
+        #[arg(long)]
+        always: bool,
+        /// Targets the rule is limited to, comma-separated or repeated
+        #[arg(long, value_name = "AGENT", value_delimiter = ',')]
+        agents: Vec<String>,
+    },
+    /// List the rules with their metadata and the targets linking them
+    List,
+    /// Show a rule the way each target sees it
+    Show {
+        /// File name of the rule, with or without extension
+        #[arg(value_name = "NAME")]
+        name: String,
+        /// Only show the rule for this target
+        #[arg(long, value_name = "AGENT")]
+        agent: Option<String>,
+    },
+    /// Rename a rule and update its links in every target
+    Mv {
+        /// File name of the rule, with or without extension
+        #[arg(value_name = "NAME")]
+        from: String,
+        /// New file name; the old extension is kept if it has none
+        #[arg(value_name = "NEW_NAME")]
+        to: String,
+    },
+    /// Stop syncing a rule to the targets while keeping it in .rules, saved to .known.json
+    Disable {
+        /// File name of the rule, with or without extension
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Sync a disabled rule to the targets again
+    Enable {
+        /// File name of the rule, with or without extension
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// Link only the rules of a profile into the targets, saved to .known.json
+    Use {
+        /// Name of the profile
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// Deactivate the current profile and link every rule again
+    Clear,
+    /// List the profiles and the rules they select
+    List,
+}
+
+/// Describes how a rule is activated for the `rule list` table
+fn describe_activation(activation: &Activation) -> String {
+    match activation {
+        Activation::Always => "always".to_string(),
+        Activation::FileMatch(globs) => format!("globs: {}", globs.join(", ")),
+        Activation::Manual => "manual".to_string(),
+    }
+}
+
+/// Describes the targets a rule is limited to for the `rule list` table
+fn describe_agents(agents: &[String], exclude_agents: &[String]) -> String {
+    let included = if agents.is_empty() {
+        "all".to_string()
+    } else {
+        agents.join(", ")
+    };
+    if exclude_agents.is_empty() {
+        included
+    } else {
+        format!("{} except {}", included, exclude_agents.join(", "))
+    }
 }
 
 /// Spawns a new process to run the daemon in the background
@@ -94,15 +290,99 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Init => {
-            create_agents_file()?;
+        Commands::Init {
+            legacy_import,
+            legacy_cleanup,
+            strategy,
+            template,
+        } => {
+            let current_dir = std::env::current_dir()?;
+            let options = InitOptions {
+                legacy_import: *legacy_import,
+                legacy_cleanup: *legacy_cleanup,
+                merge_strategy: *strategy,
+                template: template.clone(),
+            };
+            let imported = create_agents_file_with_options(&current_dir, &options)?;
+            for file in &imported {
+                println!(
+                    "Imported {} into {} ({})",
+                    file.name,
+                    file.destination.display(),
+                    match file.cleanup {
+                        LegacyCleanup::Link => "replaced with a link",
+                        LegacyCleanup::Remove => "removed",
+                    }
+                );
+            }
             println!("Successfully initialized project with AGENTS.md");
         }
-        Commands::Symlink => {
-            create_symlinks()?;
-            println!(
-                "Successfully created symlinks: CLAUDE.md and GEMINI.md now point to AGENTS.md"
-            );
+        Commands::Symlink {
+            claude,
+            gemini,
+            on_conflict,
+            dry_run,
+            recursive,
+            inherit,
+        } => {
+            let current_dir = std::env::current_dir()?;
+            let options = SymlinkOptions {
+                conflict_policy: *on_conflict,
+                dry_run: *dry_run,
+                claude: *claude,
+                gemini: *gemini,
+                recursive: *recursive,
+                inherit: *inherit,
+            };
+
+            if *dry_run {
+                let report = create_symlinks_with_options(&current_dir, &options)?;
+                if report.is_empty() {
+                    println!("No rules to migrate");
+                } else {
+                    println!("{}", report);
+                }
+                println!("Dry run: no changes were made");
+                return Ok(());
+            }
+
+            let report = create_symlinks_with_options(&current_dir, &options)?;
+            if !report.is_empty() {
+                println!("{}", report);
+            }
+            let project_config = load_project_config(&current_dir)?;
+            match (project_config.aliases.claude, project_config.aliases.gemini) {
+                (ClaudeStrategy::Symlink, GeminiStrategy::Symlink) => println!(
+                    "Successfully created symlinks: CLAUDE.md and GEMINI.md now point to AGENTS.md"
+                ),
+                (claude, gemini) => println!(
+                    "Successfully linked AGENTS.md: {}; {}",
+                    match claude {
+                        ClaudeStrategy::Symlink => "CLAUDE.md now points to AGENTS.md",
+                        ClaudeStrategy::Import => "CLAUDE.md imports AGENTS.md",
+                    },
+                    match gemini {
+                        GeminiStrategy::Symlink => "GEMINI.md now points to AGENTS.md",
+                        GeminiStrategy::Settings => ".gemini/settings.json uses AGENTS.md",
+                    }
+                ),
+            }
+            if project_config.recursive {
+                let packages = discover_packages(&current_dir)?;
+                println!(
+                    "Linked {} nested package(s){}",
+                    packages.len(),
+                    if project_config.inherit {
+                        ", inheriting rules from the .rules directories above them"
+                    } else {
+                        ""
+                    }
+                );
+                for package in &packages {
+                    let relative = package.strip_prefix(&current_dir).unwrap_or(package);
+                    println!("  - {}", relative.display());
+                }
+            }
         }
         Commands::Start => {
             spawn_daemon_process()?;
@@ -207,6 +487,394 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Commands::Status => {
+            let current_dir = std::env::current_dir()?;
+            let config = known::load_config()?;
+            let checks = project_status(&current_dir, &config)?;
+
+            println!("Status of {}:", current_dir.display());
+            for check in &checks {
+                let marker = if check.healthy { "ok" } else { "!!" };
+                println!("  [{}] {}: {}", marker, check.name, check.detail);
+            }
+
+            let problems = checks.iter().filter(|check| !check.healthy).count();
+            if problems > 0 {
+                return Err(format!("{} problem(s) found", problems).into());
+            }
+        }
+        Commands::Lint { json, strict } => {
+            let current_dir = std::env::current_dir()?;
+            let findings = lint_project(&current_dir)?;
+
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&findings)?);
+            } else {
+                for finding in &findings {
+                    let location = match finding.line {
+                        Some(line) => format!("{}:{}", finding.path, line),
+                        None => finding.path.clone(),
+                    };
+                    let severity = match finding.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                    };
+                    println!(
+                        "{}: {}[{}]: {}",
+                        location, severity, finding.check, finding.message
+                    );
+                }
+            }
+
+            let errors = findings
+                .iter()
+                .filter(|finding| finding.severity == Severity::Error)
+                .count();
+            let warnings = findings.len() - errors;
+            if !*json {
+                if findings.is_empty() {
+                    println!("No problems found");
+                } else {
+                    println!("{} error(s), {} warning(s)", errors, warnings);
+                }
+            }
+            // Warnings only fail the run with --strict
+            if errors > 0 {
+                return Err(format!("{} error(s) found", errors).into());
+            }
+            if *strict && warnings > 0 {
+                return Err(format!("{} warning(s) found with --strict", warnings).into());
+            }
+        }
+        Commands::Budget { limit } => {
+            let current_dir = std::env::current_dir()?;
+            let mut report = budget_report(&current_dir)?;
+            if let Some(limit) = limit {
+                for agent in &mut report.agents {
+                    agent.limit = Some(*limit);
+                }
+            }
+
+            println!("Rules (estimated tokens):");
+            if report.rules.is_empty() {
+                println!("  none");
+            }
+            let width = report
+                .rules
+                .iter()
+                .map(|rule| rule.name.len())
+                .max()
+                .unwrap_or(0);
+            for rule in &report.rules {
+                println!(
+                    "  {:<width$}  {:>6}  {}",
+                    rule.name,
+                    rule.tokens,
+                    describe_activation(&rule.activation),
+                    width = width
+                );
+            }
+
+            println!("Agents (estimated tokens):");
+            let width = report
+                .agents
+                .iter()
+                .map(|agent| agent.agent.len())
+                .max()
+                .unwrap_or(0);
+            for agent in &report.agents {
+                let mut line = format!(
+                    "  {:<width$}  {:>6} always",
+                    agent.agent,
+                    agent.always_tokens(),
+                    width = width
+                );
+                if agent.glob_tokens() > 0 {
+                    line.push_str(&format!(
+                        ", up to {} more for matching files",
+                        agent.glob_tokens()
+                    ));
+                }
+                if let Some(limit) = agent.limit {
+                    line.push_str(&format!(" (budget {})", limit));
+                }
+                println!("{}", line);
+            }
+
+            for agent in report.agents.iter().filter(|agent| agent.is_over_budget()) {
+                eprintln!(
+                    "Warning: {} always loads about {} tokens, over its budget of {}",
+                    agent.agent,
+                    agent.always_tokens(),
+                    agent.limit.unwrap_or_default()
+                );
+            }
+        }
+        Commands::Explain { path } => {
+            let current_dir = std::env::current_dir()?;
+            let explanations = explain_path(&current_dir, path)?;
+
+            println!("Instructions and rules for {}:", path.display());
+            for explanation in &explanations {
+                println!("{}", explanation.agent);
+                if explanation.files.is_empty() {
+                    println!("  nothing applies");
+                }
+                let width = explanation
+                    .files
+                    .iter()
+                    .map(|file| file.path.len())
+                    .max()
+                    .unwrap_or(0);
+                for file in &explanation.files {
+                    println!("  {:<width$}  {}", file.path, file.reason, width = width);
+                }
+            }
+        }
+        Commands::Undo => {
+            let current_dir = std::env::current_dir()?;
+            let report = match undo_last_run(&current_dir) {
+                Ok(report) => report,
+                // Report that there is nothing to undo without the io::Error details
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(e.to_string().into());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if report.interrupted {
+                println!(
+                    "Reverted the interrupted 'known {}' run ({} change(s) undone)",
+                    report.command, report.changes
+                );
+            } else {
+                println!(
+                    "Reverted the last 'known {}' run ({} change(s) undone)",
+                    report.command, report.changes
+                );
+            }
+            for edit in &report.preserved {
+                eprintln!(
+                    "Warning: {} was edited after the run; its content was saved to {}",
+                    edit.path.display(),
+                    edit.backup_path.display()
+                );
+            }
+            for reason in &report.skipped {
+                eprintln!("Warning: Skipped a change: {}", reason);
+            }
+        }
+        Commands::Eject { remove_rules } => {
+            let current_dir = std::env::current_dir()?;
+            let options = EjectOptions {
+                remove_rules: *remove_rules,
+            };
+            // The daemon would react to the links being replaced, so it is stopped
+            // for the eject and restarted for the other watched directories
+            let daemon_stopped = stop_daemon_and_wait(DAEMON_STOP_TIMEOUT)?;
+            let result = eject_project(&current_dir, &options);
+            if daemon_stopped {
+                spawn_daemon_process()?;
+            }
+            let report = result?;
+
+            for path in &report.materialized {
+                let display_path = path.strip_prefix(&current_dir).unwrap_or(path);
+                println!("Materialized {}", display_path.display());
+            }
+            if report.unregistered {
+                println!(
+                    "Removed '{}' from watched directories",
+                    current_dir.display()
+                );
+            }
+            if report.rules_removed {
+                println!("Removed .rules directory");
+            }
+            println!("Successfully ejected project: known no longer manages this directory");
+        }
+        Commands::Global {
+            command: GlobalCommands::Init,
+        } => {
+            let report = init_global_agents_file()?;
+            match (&report.created, &report.seeded_from) {
+                (true, Some(source)) => println!(
+                    "Created global AGENTS.md at {} from {}",
+                    report.agents_path.display(),
+                    source.display()
+                ),
+                (true, None) => println!(
+                    "Created global AGENTS.md at {}",
+                    report.agents_path.display()
+                ),
+                (false, _) => println!(
+                    "Using existing global AGENTS.md at {}",
+                    report.agents_path.display()
+                ),
+            }
+            for (path, link) in &report.files {
+                println!("  - {} ({})", path.display(), link);
+            }
+            for backup in &report.backups {
+                println!("Backed up differing content to {}", backup.display());
+            }
+        }
+        Commands::Rule { command } => {
+            let current_dir = std::env::current_dir()?;
+            match command {
+                RuleCommands::New {
+                    name,
+                    description,
+                    globs,
+                    always,
+                    agents,
+                } => {
+                    let options = NewRule {
+                        description: description.clone(),
+                        globs: globs.clone(),
+                        always: *always,
+                        agents: agents.clone(),
+                    };
+                    let rule_path = create_rule(&current_dir, name, &options)?;
+                    println!("Created {}", rule_path.display());
+                }
+                RuleCommands::List => {
+                    let rules = list_rules(&current_dir)?;
+                    if rules.is_empty() {
+                        println!("No rules found");
+                        return Ok(());
+                    }
+                    let rows: Vec<[String; 4]> = rules
+                        .iter()
+                        .map(|rule| {
+                            [
+                                rule.name.clone(),
+                                describe_activation(&rule.activation),
+                                describe_agents(&rule.agents, &rule.exclude_agents),
+                                match &rule.inactive {
+                                    Some(reason) => format!("- ({})", reason),
+                                    None if rule.linked.is_empty() => "-".to_string(),
+                                    None => rule.linked.join(", "),
+                                },
+                            ]
+                        })
+                        .collect();
+                    let header = ["RULE", "ACTIVATION", "AGENTS", "LINKED"].map(String::from);
+                    let widths: Vec<usize> = (0..3)
+                        .map(|column| {
+                            std::iter::once(&header)
+                                .chain(&rows)
+                                .map(|row| row[column].len())
+                                .max()
+                                .unwrap_or(0)
+                        })
+                        .collect();
+                    for row in std::iter::once(&header).chain(&rows) {
+                        println!(
+                            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+                            row[0],
+                            row[1],
+                            row[2],
+                            row[3],
+                            w0 = widths[0],
+                            w1 = widths[1],
+                            w2 = widths[2]
+                        );
+                    }
+                }
+                RuleCommands::Show { name, agent } => {
+                    let (name, views) = show_rule(&current_dir, name)?;
+                    let views: Vec<_> = views
+                        .into_iter()
+                        .filter(|view| {
+                            agent
+                                .as_ref()
+                                .is_none_or(|agent| view.agent.eq_ignore_ascii_case(agent))
+                        })
+                        .collect();
+                    if let (Some(agent), true) = (agent, views.is_empty()) {
+                        return Err(format!(
+                            "The {} target is not enabled for this project",
+                            agent
+                        )
+                        .into());
+                    }
+                    for (index, view) in views.iter().enumerate() {
+                        if index > 0 {
+                            println!();
+                        }
+                        println!("==> {} ({}): {}", view.agent, name, view.note);
+                        if let Some(content) = &view.content {
+                            print!("{}", content);
+                            if !content.ends_with('\n') {
+                                println!();
+                            }
+                        }
+                    }
+                }
+                RuleCommands::Mv { from, to } => {
+                    let (from, to) = rename_rule(&current_dir, from, to)?;
+                    println!("Renamed rule {} to {} and updated its links", from, to);
+                }
+                RuleCommands::Disable { name } | RuleCommands::Enable { name } => {
+                    let enabled = matches!(command, RuleCommands::Enable { .. });
+                    let toggle = set_rule_enabled(&current_dir, name, enabled)?;
+                    match (toggle.changed, enabled) {
+                        (true, true) => println!("Enabled rule {}", toggle.name),
+                        (true, false) => println!(
+                            "Disabled rule {}; it stays in .rules but is no longer linked",
+                            toggle.name
+                        ),
+                        (false, true) => println!("Rule {} is not disabled", toggle.name),
+                        (false, false) => println!("Rule {} is already disabled", toggle.name),
+                    }
+                    if enabled && toggle.disabled_in_frontmatter {
+                        println!(
+                            "Warning: {} still has 'enabled: false' in its frontmatter and stays unlinked",
+                            toggle.name
+                        );
+                    }
+                }
+            }
+        }
+        Commands::Profile { command } => {
+            let current_dir = std::env::current_dir()?;
+            match command {
+                ProfileCommands::Use { name } => {
+                    if let Some(profile) = use_profile(&current_dir, Some(name))? {
+                        println!(
+                            "Switched to profile {}: {} rule(s) linked",
+                            profile.name,
+                            profile.rules.len()
+                        );
+                        for rule in &profile.rules {
+                            println!("  - {}", rule);
+                        }
+                    }
+                }
+                ProfileCommands::Clear => {
+                    use_profile(&current_dir, None)?;
+                    println!("Deactivated the profile: every rule is linked again");
+                }
+                ProfileCommands::List => {
+                    let profiles = list_profiles(&current_dir)?;
+                    if profiles.is_empty() {
+                        println!("No profiles are defined in .known.json");
+                    }
+                    for profile in &profiles {
+                        println!(
+                            "{} {} ({})",
+                            if profile.active { "*" } else { " " },
+                            profile.name,
+                            if profile.rules.is_empty() {
+                                "no rules".to_string()
+                            } else {
+                                profile.rules.join(", ")
+                            }
+                        );
+                    }
+                }
+            }
+        }
     }
 
     Ok(())
@@ -299,7 +967,6 @@ mod tests {
         }
 
         // Test passes if we reach this point without panicking
-        assert!(true, "stop_daemon function executed without panicking");
     }
 
     #[test]
@@ -0,0 +1,134 @@
+//! Sections delimited by marker lines that known manages inside files the user
+//! also edits, such as `CLAUDE.md`, `.aider.conf.yml` and `.git/info/exclude`.
+
+/// Where a section goes when the file doesn't have one yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Before the existing content, separated from it by a blank line
+    Top,
+    /// After the existing content
+    Bottom,
+}
+
+/// Finds the byte range of a section, including the newline after the end marker.
+///
+/// # Arguments
+///
+/// * `content` - The file content
+/// * `start_marker` - The line that opens the section
+/// * `end_marker` - The line that closes the section
+///
+/// # Returns
+///
+/// Returns the start and end offsets, or `None` if the section is missing or
+/// isn't closed.
+pub fn find_section(content: &str, start_marker: &str, end_marker: &str) -> Option<(usize, usize)> {
+    let start = content.find(start_marker)?;
+    let end = content[start..]
+        .find(end_marker)
+        .map(|offset| start + offset + end_marker.len())?;
+    let end = if content[end..].starts_with('\n') {
+        end + 1
+    } else {
+        end
+    };
+    Some((start, end))
+}
+
+/// Replaces (or removes, when `block` is `None`) a section in `content`.
+///
+/// `block` must include the marker lines. When the section doesn't exist yet, the
+/// block is inserted according to `placement`. Removing a section placed at the
+/// top also drops the blank line that separated it from the rest of the file.
+///
+/// # Arguments
+///
+/// * `content` - The file content
+/// * `start_marker` - The line that opens the section
+/// * `end_marker` - The line that closes the section
+/// * `block` - The new section, or `None` to remove it
+/// * `placement` - Where a new section goes
+///
+/// # Returns
+///
+/// Returns the updated content.
+pub fn replace_section(
+    content: &str,
+    start_marker: &str,
+    end_marker: &str,
+    block: Option<&str>,
+    placement: Placement,
+) -> String {
+    match find_section(content, start_marker, end_marker) {
+        Some((start, end)) => {
+            let mut updated = content[..start].to_string();
+            match block {
+                Some(block) => {
+                    if !updated.is_empty() && !updated.ends_with('\n') {
+                        updated.push('\n');
+                    }
+                    updated.push_str(block);
+                    updated.push_str(&content[end..]);
+                }
+                None if placement == Placement::Top => {
+                    updated.push_str(content[end..].trim_start_matches('\n'))
+                }
+                None => updated.push_str(&content[end..]),
+            }
+            updated
+        }
+        None => match (block, placement) {
+            (None, _) => content.to_string(),
+            (Some(block), _) if content.trim().is_empty() => block.to_string(),
+            (Some(block), Placement::Top) => format!("{}\n{}", block, content),
+            (Some(block), Placement::Bottom) => {
+                let mut updated = content.to_string();
+                if !updated.ends_with('\n') {
+                    updated.push('\n');
+                }
+                updated.push_str(block);
+                updated
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "# >>> start >>>";
+    const END: &str = "# <<< end <<<";
+
+    #[test]
+    fn test_replace_section() {
+        let block = format!("{}\na\n{}\n", START, END);
+
+        // New sections go to the top or the bottom
+        let bottom = replace_section("x: 1", START, END, Some(&block), Placement::Bottom);
+        assert_eq!(bottom, format!("x: 1\n{}", block));
+        let top = replace_section("# Notes\n", START, END, Some(&block), Placement::Top);
+        assert_eq!(top, format!("{}\n# Notes\n", block));
+
+        // Existing sections are replaced in place
+        let updated = format!("{}\nb\n{}\n", START, END);
+        assert_eq!(
+            replace_section(&bottom, START, END, Some(&updated), Placement::Bottom),
+            format!("x: 1\n{}", updated)
+        );
+
+        // Removal keeps the rest of the file
+        assert_eq!(
+            replace_section(&bottom, START, END, None, Placement::Bottom),
+            "x: 1\n"
+        );
+        assert_eq!(
+            replace_section(&top, START, END, None, Placement::Top),
+            "# Notes\n"
+        );
+        assert_eq!(
+            replace_section("x: 1\n", START, END, None, Placement::Bottom),
+            "x: 1\n"
+        );
+    }
+}
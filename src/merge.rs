@@ -0,0 +1,258 @@
+//! Merging of CLAUDE.md and GEMINI.md into a single AGENTS.md.
+//!
+//! When a project has both files, `known init` aligns them by Markdown heading.
+//! Sections with the same heading and content are kept once, sections that only
+//! exist in one file are kept in place, and sections whose content differs are
+//! wrapped in conflict markers so they can be resolved by hand.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::constants::{CLAUDE_FILENAME, GEMINI_FILENAME};
+
+/// How AGENTS.md is created when both CLAUDE.md and GEMINI.md exist
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Merge both files section by section, marking divergent sections
+    #[default]
+    Merge,
+    /// Use the content of CLAUDE.md
+    PreferClaude,
+    /// Use the content of GEMINI.md
+    PreferGemini,
+    /// Create an empty AGENTS.md and leave the merge to the user
+    Empty,
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::Merge => write!(f, "merge"),
+            MergeStrategy::PreferClaude => write!(f, "prefer-claude"),
+            MergeStrategy::PreferGemini => write!(f, "prefer-gemini"),
+            MergeStrategy::Empty => write!(f, "empty"),
+        }
+    }
+}
+
+impl FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "merge" => Ok(MergeStrategy::Merge),
+            "prefer-claude" => Ok(MergeStrategy::PreferClaude),
+            "prefer-gemini" => Ok(MergeStrategy::PreferGemini),
+            "empty" => Ok(MergeStrategy::Empty),
+            _ => Err(format!(
+                "Unknown merge strategy '{}'. Expected 'merge', 'prefer-claude', 'prefer-gemini' or 'empty'",
+                s
+            )),
+        }
+    }
+}
+
+/// The result of merging two instruction files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedInstructions {
+    /// The merged Markdown document
+    pub content: String,
+    /// Number of sections wrapped in conflict markers
+    pub conflicts: usize,
+}
+
+/// Identifies a section: its normalized heading and how many sections with the
+/// same heading came before it in the same document
+type SectionKey = (String, usize);
+
+/// A heading and the lines below it, up to the next heading
+#[derive(Debug)]
+struct Section {
+    key: SectionKey,
+    heading: String,
+    body: String,
+}
+
+/// Merges the content of CLAUDE.md and GEMINI.md.
+///
+/// The documents are split at Markdown headings (outside fenced code blocks).
+/// Sections follow the order of CLAUDE.md; sections only found in GEMINI.md are
+/// inserted before the next section both files share, or at the end. Divergent sections keep
+/// their heading once and wrap both bodies in Git-style conflict markers labelled
+/// with the file names.
+///
+/// # Arguments
+///
+/// * `claude` - Content of CLAUDE.md
+/// * `gemini` - Content of GEMINI.md
+pub fn merge_instruction_files(claude: &str, gemini: &str) -> MergedInstructions {
+    let claude_sections = split_sections(claude);
+    let gemini_sections = split_sections(gemini);
+
+    // Start from the order of CLAUDE.md and slot in sections only GEMINI.md has,
+    // right before the next section both files share
+    let mut order: Vec<SectionKey> = claude_sections.iter().map(|s| s.key.clone()).collect();
+    let shared = |key: &SectionKey| claude_sections.iter().any(|s| s.key == *key);
+    for (index, section) in gemini_sections.iter().enumerate() {
+        if shared(&section.key) {
+            continue;
+        }
+        let position = gemini_sections[index + 1..]
+            .iter()
+            .find(|next| shared(&next.key))
+            .and_then(|next| order.iter().position(|key| *key == next.key))
+            .unwrap_or(order.len());
+        order.insert(position, section.key.clone());
+    }
+
+    let find = |sections: &[Section], key: &SectionKey| -> Option<usize> {
+        sections.iter().position(|section| section.key == *key)
+    };
+
+    let mut content = String::new();
+    let mut conflicts = 0;
+    for key in &order {
+        let chunk = match (
+            find(&claude_sections, key).map(|i| &claude_sections[i]),
+            find(&gemini_sections, key).map(|i| &gemini_sections[i]),
+        ) {
+            (Some(claude), Some(gemini)) if claude.body.trim() == gemini.body.trim() => {
+                format!("{}{}", claude.heading, claude.body)
+            }
+            (Some(claude), Some(gemini)) => {
+                conflicts += 1;
+                render_conflict(&claude.heading, &claude.body, &gemini.body)
+            }
+            (Some(section), None) | (None, Some(section)) => {
+                format!("{}{}", section.heading, section.body)
+            }
+            (None, None) => continue,
+        };
+        push_chunk(&mut content, &chunk);
+    }
+
+    let content = if content.trim().is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", content.trim_end_matches('\n'))
+    };
+    MergedInstructions { content, conflicts }
+}
+
+/// Splits a Markdown document into the text before the first heading and one
+/// section per heading.
+fn split_sections(content: &str) -> Vec<Section> {
+    let mut sections = vec![Section {
+        key: (String::new(), 0),
+        heading: String::new(),
+        body: String::new(),
+    }];
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+
+        if !in_fence && is_heading(trimmed) {
+            let normalized = trimmed.trim_end_matches('#').trim().to_lowercase();
+            let count = occurrences.entry(normalized.clone()).or_insert(0);
+            *count += 1;
+            let mut heading = line.to_string();
+            if !heading.ends_with('\n') {
+                heading.push('\n');
+            }
+            sections.push(Section {
+                key: (normalized, *count),
+                heading,
+                body: String::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.body.push_str(line);
+        }
+    }
+
+    sections
+}
+
+/// Checks whether a trimmed line is an ATX heading (`#` to `######` followed by a space).
+fn is_heading(line: &str) -> bool {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&level) && line[level..].starts_with(' ')
+}
+
+/// Renders a section whose content differs between the two files.
+fn render_conflict(heading: &str, claude_body: &str, gemini_body: &str) -> String {
+    let mut rendered = heading.to_string();
+    rendered.push_str(&format!("<<<<<<< {}\n", CLAUDE_FILENAME));
+    push_body(&mut rendered, claude_body);
+    rendered.push_str("=======\n");
+    push_body(&mut rendered, gemini_body);
+    rendered.push_str(&format!(">>>>>>> {}\n", GEMINI_FILENAME));
+    rendered
+}
+
+/// Appends a section body without its surrounding blank lines.
+fn push_body(rendered: &mut String, body: &str) {
+    let body = body.trim_matches('\n');
+    if !body.trim().is_empty() {
+        rendered.push_str(body);
+        rendered.push('\n');
+    }
+}
+
+/// Appends a rendered section, separated from the previous one by a blank line.
+fn push_chunk(content: &mut String, chunk: &str) {
+    if chunk.trim().is_empty() {
+        return;
+    }
+    if !content.is_empty() && !content.ends_with("\n\n") {
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push('\n');
+    }
+    content.push_str(chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_shared_sections_once() {
+        let claude = "# Project\n\n## Build\n\nRun cargo build.\n\n## Claude\n\nUse plan mode.\n";
+        let gemini = "# Project\n\n## Gemini\n\nUse sandbox.\n\n## Build\n\nRun cargo build.\n";
+
+        let merged = merge_instruction_files(claude, gemini);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.content,
+            "# Project\n\n## Gemini\n\nUse sandbox.\n\n## Build\n\nRun cargo build.\n\n## Claude\n\nUse plan mode.\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_marks_divergent_sections() {
+        let claude = "Intro\n\n## Style\n\nUse tabs.\n";
+        let gemini = "Intro\n\n## Style\n\nUse spaces.\n\n```sh\n# not a heading\n```\n";
+
+        let merged = merge_instruction_files(claude, gemini);
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.content,
+            "Intro\n\n## Style\n<<<<<<< CLAUDE.md\nUse tabs.\n=======\nUse spaces.\n\n```sh\n# not a heading\n```\n>>>>>>> GEMINI.md\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_strategy_parsing() {
+        assert_eq!("prefer-claude".parse(), Ok(MergeStrategy::PreferClaude));
+        assert_eq!("Empty".parse(), Ok(MergeStrategy::Empty));
+        assert!("union".parse::<MergeStrategy>().is_err());
+        assert_eq!(MergeStrategy::PreferGemini.to_string(), "prefer-gemini");
+    }
+}
@@ -0,0 +1,610 @@
+//! Migration of rules from tool-specific directories into `.rules`.
+//!
+//! `known symlink` moves the files found in `.cursor/rules`, `.windsurf/rules`,
+//! `.amazonq/rules` and `.kiro/steering` into the unified `.rules` directory. When
+//! a file with the same name already exists there, a [`ConflictPolicy`] decides
+//! what happens. Identical files are recognised by comparing their content and
+//! deduplicated regardless of the policy.
+//!
+//! The migration is planned first, so it can be previewed (`--dry-run`) or
+//! rejected (`fail` policy) before anything is changed.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use crate::config::load_project_config;
+use crate::constants::{
+    AMAZONQ_RULES_DIR, AMAZONQ_TARGET, CURSOR_RULES_DIR, CURSOR_TARGET, KIRO_STEERING_DIR,
+    KIRO_TARGET, RULES_DIR, WINDSURF_RULES_DIR, WINDSURF_TARGET,
+};
+use crate::repair::backup_file;
+use crate::rules::enabled_targets;
+use crate::rules::kiro::is_generated_steering_file;
+use crate::transaction::Transaction;
+
+/// The tool-specific directories rules are migrated from, with the suffix used
+/// for their files when both versions of a conflicting rule are kept
+pub const MIGRATION_SOURCES: [(&str, &str); 4] = [
+    (CURSOR_RULES_DIR, CURSOR_TARGET),
+    (WINDSURF_RULES_DIR, WINDSURF_TARGET),
+    (AMAZONQ_RULES_DIR, AMAZONQ_TARGET),
+    (KIRO_STEERING_DIR, KIRO_TARGET),
+];
+
+/// What to do when a migrated file has the same name as a file in `.rules`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Leave the source file where it is
+    #[default]
+    Skip,
+    /// Move the source file into `.rules` under a name with the tool as suffix
+    KeepBoth,
+    /// Keep whichever file was modified most recently, backing up the other one
+    PreferNewer,
+    /// Abort the migration before changing anything
+    Fail,
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::Skip => write!(f, "skip"),
+            ConflictPolicy::KeepBoth => write!(f, "keep-both"),
+            ConflictPolicy::PreferNewer => write!(f, "prefer-newer"),
+            ConflictPolicy::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "keep-both" => Ok(ConflictPolicy::KeepBoth),
+            "prefer-newer" => Ok(ConflictPolicy::PreferNewer),
+            "fail" => Ok(ConflictPolicy::Fail),
+            _ => Err(format!(
+                "Unknown conflict policy '{}'. Expected 'skip', 'keep-both', 'prefer-newer' or 'fail'",
+                s
+            )),
+        }
+    }
+}
+
+/// What happens to a single file during the migration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationAction {
+    /// The file is moved into `.rules`
+    Move,
+    /// The file is identical to the one in `.rules` and is removed
+    Deduplicate,
+    /// The file conflicts with the one in `.rules` and is left in place
+    Skip,
+    /// The file conflicts and is moved into `.rules` under a suffixed name
+    KeepBoth,
+    /// The file is newer and replaces the one in `.rules`, which is backed up
+    ReplaceOlder,
+    /// The file is older than the one in `.rules`; it is backed up and removed
+    DiscardOlder,
+}
+
+/// The planned outcome for one file in a tool-specific directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationDecision {
+    /// The file in the tool-specific directory
+    pub source: PathBuf,
+    /// The file in `.rules` it is moved to or compared with
+    pub destination: PathBuf,
+    /// What happens to the file
+    pub action: MigrationAction,
+}
+
+/// The planned (or applied) decisions of a migration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Path to the project directory
+    pub project_dir: PathBuf,
+    /// One decision per file found in the tool-specific directories
+    pub decisions: Vec<MigrationDecision>,
+}
+
+impl MigrationReport {
+    /// Checks whether there was nothing to migrate
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty()
+    }
+
+    /// Returns the decisions with the given action
+    pub fn with_action(&self, action: MigrationAction) -> impl Iterator<Item = &MigrationDecision> {
+        self.decisions
+            .iter()
+            .filter(move |decision| decision.action == action)
+    }
+
+    /// Returns a path relative to the project directory for display
+    fn display_path<'a>(&self, path: &'a Path) -> std::path::Display<'a> {
+        path.strip_prefix(&self.project_dir)
+            .unwrap_or(path)
+            .display()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rules migration summary:")?;
+        for decision in &self.decisions {
+            let source = self.display_path(&decision.source);
+            let destination = self.display_path(&decision.destination);
+            match decision.action {
+                MigrationAction::Move => {
+                    writeln!(f, "  moved         {} -> {}", source, destination)?
+                }
+                MigrationAction::Deduplicate => writeln!(
+                    f,
+                    "  deduplicated  {} (identical to {})",
+                    source, destination
+                )?,
+                MigrationAction::Skip => writeln!(
+                    f,
+                    "  skipped       {} (conflicts with {})",
+                    source, destination
+                )?,
+                MigrationAction::KeepBoth => {
+                    writeln!(f, "  kept both     {} -> {}", source, destination)?
+                }
+                MigrationAction::ReplaceOlder => writeln!(
+                    f,
+                    "  replaced      {} with newer {} (old version backed up)",
+                    destination, source
+                )?,
+                MigrationAction::DiscardOlder => writeln!(
+                    f,
+                    "  kept newer    {}; removed older {} (backed up)",
+                    destination, source
+                )?,
+            }
+        }
+
+        let count = |action| self.with_action(action).count();
+        write!(
+            f,
+            "{} moved, {} deduplicated, {} skipped, {} kept both, {} resolved by modification time",
+            count(MigrationAction::Move),
+            count(MigrationAction::Deduplicate),
+            count(MigrationAction::Skip),
+            count(MigrationAction::KeepBoth),
+            count(MigrationAction::ReplaceOlder) + count(MigrationAction::DiscardOlder)
+        )
+    }
+}
+
+/// A file in `.rules` as it will be once the planned decisions are applied
+#[derive(Debug, Clone)]
+struct PlannedFile {
+    /// Content of the file, or `None` for directories
+    content: Option<Vec<u8>>,
+    modified: Option<SystemTime>,
+}
+
+/// Plans the migration of the tool-specific rules directories of a project into `.rules`.
+///
+/// Symlinks in the tool-specific directories (such as the links maintained by the
+/// daemon) and the Kiro steering files generated by known are not migrated, nor
+/// are the directories of optional targets the project hasn't enabled.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `policy` - What to do when a file conflicts with a different file in `.rules`
+///
+/// # Errors
+///
+/// Returns an `AlreadyExists` error listing the conflicting files if the policy is
+/// [`ConflictPolicy::Fail`] and there are conflicts. Also returns an error if the
+/// directories or files cannot be read.
+pub fn plan_rules_migration(dir: &Path, policy: ConflictPolicy) -> io::Result<MigrationReport> {
+    let rules_path = dir.join(RULES_DIR);
+    let mut planned: HashMap<OsString, PlannedFile> = HashMap::new();
+    if rules_path.exists() {
+        for entry in fs::read_dir(&rules_path)? {
+            let entry = entry?;
+            planned.insert(entry.file_name(), planned_file(&entry.path())?);
+        }
+    }
+
+    let mut report = MigrationReport {
+        project_dir: dir.to_path_buf(),
+        decisions: Vec::new(),
+    };
+    let mut conflicts = Vec::new();
+    let targets = enabled_targets(&load_project_config(dir)?);
+
+    for (source_dir, suffix) in MIGRATION_SOURCES {
+        // Rules only leave a tool's directory if known puts them back there
+        let source_path = dir.join(source_dir);
+        if !source_path.exists() || !targets.iter().any(|target| target.name == suffix) {
+            continue;
+        }
+
+        let mut entries: Vec<_> = fs::read_dir(&source_path)?.collect::<Result<_, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let source = entry.path();
+            if is_managed_entry(&source)? {
+                continue;
+            }
+            let file_name = entry.file_name();
+            let incoming = planned_file(&source)?;
+
+            let existing = match planned.get(&file_name) {
+                Some(existing) => existing.clone(),
+                None => {
+                    report.decisions.push(MigrationDecision {
+                        source,
+                        destination: rules_path.join(&file_name),
+                        action: MigrationAction::Move,
+                    });
+                    planned.insert(file_name, incoming);
+                    continue;
+                }
+            };
+
+            let destination = rules_path.join(&file_name);
+            let identical = incoming.content.is_some() && incoming.content == existing.content;
+            let (action, destination) = if identical {
+                (MigrationAction::Deduplicate, destination)
+            } else if incoming.content.is_none() || existing.content.is_none() {
+                // Directories are never merged or replaced
+                (MigrationAction::Skip, destination)
+            } else {
+                match policy {
+                    ConflictPolicy::Skip => (MigrationAction::Skip, destination),
+                    ConflictPolicy::Fail => {
+                        conflicts.push(source.clone());
+                        (MigrationAction::Skip, destination)
+                    }
+                    ConflictPolicy::KeepBoth => {
+                        let name = suffixed_name(&planned, &file_name, suffix);
+                        planned.insert(name.clone(), incoming);
+                        (MigrationAction::KeepBoth, rules_path.join(name))
+                    }
+                    ConflictPolicy::PreferNewer => {
+                        if incoming.modified > existing.modified {
+                            planned.insert(file_name, incoming);
+                            (MigrationAction::ReplaceOlder, destination)
+                        } else {
+                            (MigrationAction::DiscardOlder, destination)
+                        }
+                    }
+                }
+            };
+
+            report.decisions.push(MigrationDecision {
+                source,
+                destination,
+                action,
+            });
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let names: Vec<String> = conflicts
+            .iter()
+            .map(|path| report.display_path(path).to_string())
+            .collect();
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "Conflicting rules already exist in {}: {}",
+                RULES_DIR,
+                names.join(", ")
+            ),
+        ));
+    }
+
+    Ok(report)
+}
+
+/// Applies the decisions of a planned migration as part of a transaction.
+///
+/// Files that are replaced or discarded because of their modification time are
+/// backed up to `.known/backups` first. Every move and removal is recorded in the
+/// transaction so that it can be reverted.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be moved, removed or backed up.
+pub fn apply_rules_migration(report: &MigrationReport, tx: &mut Transaction) -> io::Result<()> {
+    if let Some(decision) = report.decisions.first() {
+        if let Some(rules_path) = decision.destination.parent() {
+            tx.create_dir(rules_path)?;
+        }
+    }
+
+    for decision in &report.decisions {
+        match decision.action {
+            MigrationAction::Move | MigrationAction::KeepBoth => {
+                tx.move_path(&decision.source, &decision.destination)?;
+            }
+            MigrationAction::Deduplicate => {
+                tx.remove_file(&decision.source)?;
+            }
+            MigrationAction::Skip => {}
+            MigrationAction::ReplaceOlder => {
+                backup_rule(&report.project_dir, &decision.destination)?;
+                tx.remove_file(&decision.destination)?;
+                tx.move_path(&decision.source, &decision.destination)?;
+            }
+            MigrationAction::DiscardOlder => {
+                backup_rule(&report.project_dir, &decision.source)?;
+                tx.remove_file(&decision.source)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copies a rule that is about to be replaced or removed to `.known/backups`.
+fn backup_rule(dir: &Path, path: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    backup_file(dir, &name, &fs::read(path)?)
+}
+
+/// Reads the content and modification time of a file (or marks a directory).
+fn planned_file(path: &Path) -> io::Result<PlannedFile> {
+    let metadata = fs::metadata(path)?;
+    let content = if metadata.is_file() {
+        Some(fs::read(path)?)
+    } else {
+        None
+    };
+    Ok(PlannedFile {
+        content,
+        modified: metadata.modified().ok(),
+    })
+}
+
+/// Checks whether a file in a tool-specific directory is an entry known maintains.
+///
+/// Links (such as those maintained by the daemon) and the Kiro steering files
+/// rendered from `.rules` are never migrated back.
+fn is_managed_entry(path: &Path) -> io::Result<bool> {
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        return Ok(true);
+    }
+    Ok(path.is_file()
+        && fs::read_to_string(path).is_ok_and(|content| is_generated_steering_file(&content)))
+}
+
+/// Builds a name that isn't planned yet by adding a suffix to the stem.
+///
+/// `style.mdc` with suffix `cursor` becomes `style-cursor.mdc`, then `style-cursor-2.mdc`.
+fn suffixed_name(
+    planned: &HashMap<OsString, PlannedFile>,
+    file_name: &OsStr,
+    suffix: &str,
+) -> OsString {
+    let original = Path::new(file_name);
+    let stem = original
+        .file_stem()
+        .unwrap_or(file_name)
+        .to_string_lossy()
+        .into_owned();
+    let extension = original
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut counter = 1;
+    loop {
+        let candidate = if counter == 1 {
+            OsString::from(format!("{}-{}{}", stem, suffix, extension))
+        } else {
+            OsString::from(format!("{}-{}-{}{}", stem, suffix, counter, extension))
+        };
+        if !planned.contains_key(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    /// Creates a project with one conflicting, one identical and one new rule in .cursor/rules.
+    fn setup_project(dir: &Path) {
+        let rules_path = dir.join(RULES_DIR);
+        let cursor_path = dir.join(CURSOR_RULES_DIR);
+        fs::create_dir_all(&rules_path).unwrap();
+        fs::create_dir_all(&cursor_path).unwrap();
+
+        fs::write(rules_path.join("style.md"), "spaces").unwrap();
+        fs::write(rules_path.join("same.md"), "same").unwrap();
+        fs::write(cursor_path.join("style.md"), "tabs").unwrap();
+        fs::write(cursor_path.join("same.md"), "same").unwrap();
+        fs::write(cursor_path.join("new.md"), "new").unwrap();
+    }
+
+    fn actions(report: &MigrationReport) -> Vec<(String, MigrationAction)> {
+        report
+            .decisions
+            .iter()
+            .map(|d| {
+                (
+                    d.source.file_name().unwrap().to_string_lossy().into_owned(),
+                    d.action,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_skip_policy_deduplicates_identical_files() {
+        let dir = tempdir().unwrap();
+        setup_project(dir.path());
+
+        let report = plan_rules_migration(dir.path(), ConflictPolicy::Skip).unwrap();
+        assert_eq!(
+            actions(&report),
+            vec![
+                ("new.md".to_string(), MigrationAction::Move),
+                ("same.md".to_string(), MigrationAction::Deduplicate),
+                ("style.md".to_string(), MigrationAction::Skip),
+            ]
+        );
+
+        // Planning alone doesn't change anything
+        assert!(dir.path().join(CURSOR_RULES_DIR).join("same.md").exists());
+
+        Transaction::run(dir.path(), "symlink", |tx| {
+            apply_rules_migration(&report, tx)
+        })
+        .unwrap();
+        let cursor_path = dir.path().join(CURSOR_RULES_DIR);
+        assert!(!cursor_path.join("same.md").exists());
+        assert!(!cursor_path.join("new.md").exists());
+        assert_eq!(
+            fs::read_to_string(cursor_path.join("style.md")).unwrap(),
+            "tabs"
+        );
+        assert!(report
+            .to_string()
+            .contains("1 moved, 1 deduplicated, 1 skipped"));
+    }
+
+    #[test]
+    fn test_keep_both_policy() {
+        let dir = tempdir().unwrap();
+        setup_project(dir.path());
+        let windsurf_path = dir.path().join(WINDSURF_RULES_DIR);
+        fs::create_dir_all(&windsurf_path).unwrap();
+        fs::write(windsurf_path.join("new.md"), "different").unwrap();
+
+        let report = plan_rules_migration(dir.path(), ConflictPolicy::KeepBoth).unwrap();
+        Transaction::run(dir.path(), "symlink", |tx| {
+            apply_rules_migration(&report, tx)
+        })
+        .unwrap();
+
+        let rules_path = dir.path().join(RULES_DIR);
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style.md")).unwrap(),
+            "spaces"
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style-cursor.md")).unwrap(),
+            "tabs"
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("new.md")).unwrap(),
+            "new"
+        );
+        assert_eq!(
+            fs::read_to_string(rules_path.join("new-windsurf.md")).unwrap(),
+            "different"
+        );
+    }
+
+    #[test]
+    fn test_plan_includes_amazonq_and_kiro_rules() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"targets": ["amazonq", "kiro"]}"#,
+        )
+        .unwrap();
+        let amazonq_path = dir.path().join(AMAZONQ_RULES_DIR);
+        let kiro_path = dir.path().join(KIRO_STEERING_DIR);
+        fs::create_dir_all(&amazonq_path).unwrap();
+        fs::create_dir_all(&kiro_path).unwrap();
+        fs::write(amazonq_path.join("testing.md"), "Run the tests.").unwrap();
+        fs::write(
+            kiro_path.join("product.md"),
+            "---\ninclusion: always\n---\nShop",
+        )
+        .unwrap();
+        fs::write(
+            kiro_path.join("style.md"),
+            "---\ninclusion: always\n---\n<!-- Generated by known from .rules/style.md. Edit the source file instead. -->\n",
+        )
+        .unwrap();
+
+        let report = plan_rules_migration(dir.path(), ConflictPolicy::Skip).unwrap();
+        assert_eq!(
+            actions(&report),
+            vec![
+                ("testing.md".to_string(), MigrationAction::Move),
+                ("product.md".to_string(), MigrationAction::Move),
+            ]
+        );
+
+        // Without the targets, the files stay where the tools read them
+        fs::remove_file(dir.path().join(crate::constants::PROJECT_CONFIG_FILENAME)).unwrap();
+        let report = plan_rules_migration(dir.path(), ConflictPolicy::Skip).unwrap();
+        assert!(report.decisions.is_empty());
+    }
+
+    #[test]
+    fn test_prefer_newer_policy_backs_up_older_file() {
+        let dir = tempdir().unwrap();
+        setup_project(dir.path());
+        let cursor_style = dir.path().join(CURSOR_RULES_DIR).join("style.md");
+        let newer = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&cursor_style)
+            .unwrap()
+            .set_modified(newer)
+            .unwrap();
+
+        let report = plan_rules_migration(dir.path(), ConflictPolicy::PreferNewer).unwrap();
+        assert!(report
+            .with_action(MigrationAction::ReplaceOlder)
+            .any(|d| d.source == cursor_style));
+        Transaction::run(dir.path(), "symlink", |tx| {
+            apply_rules_migration(&report, tx)
+        })
+        .unwrap();
+
+        let rules_path = dir.path().join(RULES_DIR);
+        assert_eq!(
+            fs::read_to_string(rules_path.join("style.md")).unwrap(),
+            "tabs"
+        );
+        let backups: Vec<_> = fs::read_dir(dir.path().join(".known/backups"))
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert_eq!(backups, vec!["spaces".to_string()]);
+    }
+
+    #[test]
+    fn test_fail_policy_changes_nothing() {
+        let dir = tempdir().unwrap();
+        setup_project(dir.path());
+
+        let error = plan_rules_migration(dir.path(), ConflictPolicy::Fail).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(error.to_string().contains("style.md"));
+        assert!(dir.path().join(CURSOR_RULES_DIR).join("new.md").exists());
+
+        assert_eq!("keep-both".parse(), Ok(ConflictPolicy::KeepBoth));
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+}
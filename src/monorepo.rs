@@ -0,0 +1,650 @@
+//! Discovery of nested packages in a monorepo.
+//!
+//! In recursive mode (`"recursive": true` in `.known.json`), every directory
+//! below a registered root that has its own AGENTS.md is a package: it gets its
+//! own alias links and `.rules` mirrors. Directories excluded by `.gitignore`
+//! files or the repository's `.git/info/exclude` are skipped, as is `.git`.
+//! Symlinked directories are not followed.
+//!
+//! With `"inherit": true` as well, the target directories of a package get the
+//! rules of every `.rules` directory between the package and the root, not just
+//! its own. A rule in a nearer directory overrides one with the same file name
+//! further up.
+//!
+//! Ignore files support the common subset of the gitignore syntax: comments,
+//! negation with `!`, directory-only patterns ending in `/`, patterns anchored
+//! with a `/`, and the `*`, `?` and `**` wildcards.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::load_project_config;
+use crate::constants::{AGENTS_FILENAME, RULES_DIR, RULES_LOCAL_DIR};
+use crate::git::{exclude_file_path, find_git_dir};
+use crate::rules::list_rule_files;
+
+/// The name of the ignore files that are honoured
+const GITIGNORE_FILENAME: &str = ".gitignore";
+
+/// The name of the Git metadata directory, which is never searched
+const GIT_DIR: &str = ".git";
+
+/// A single pattern from an ignore file
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory of the ignore file the pattern comes from
+    base: PathBuf,
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    /// Whether the pattern matches the path relative to `base` instead of the name
+    anchored: bool,
+}
+
+/// The patterns of all ignore files that apply to a directory
+#[derive(Debug, Clone, Default)]
+struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Returns the patterns that apply at a root directory: those of the
+    /// repository's `.git/info/exclude`, which known writes to as well, followed
+    /// by those of the root's `.gitignore`, which take precedence.
+    fn for_root(root: &Path) -> Self {
+        let mut rules = IgnoreRules::default();
+        if let Some(exclude_path) = exclude_file_path(root) {
+            let work_tree = find_git_dir(root)
+                .and_then(|git_dir| git_dir.parent().map(|work_tree| work_tree.to_path_buf()));
+            if let (Some(work_tree), Ok(content)) = (work_tree, fs::read_to_string(exclude_path)) {
+                rules.add_patterns(&work_tree, &content);
+            }
+        }
+        rules.add_file(root);
+        rules
+    }
+
+    /// Adds the patterns of the ignore file in a directory, if it has one.
+    fn add_file(&mut self, dir: &Path) {
+        if let Ok(content) = fs::read_to_string(dir.join(GITIGNORE_FILENAME)) {
+            self.add_patterns(dir, &content);
+        }
+    }
+
+    /// Adds the patterns of an ignore file, relative to a base directory.
+    fn add_patterns(&mut self, dir: &Path, content: &str) {
+        for line in content.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let anchored = line.contains('/');
+            let pattern = line.trim_start_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+            self.rules.push(IgnoreRule {
+                base: dir.to_path_buf(),
+                pattern: pattern.to_string(),
+                negated,
+                dir_only,
+                anchored,
+            });
+        }
+    }
+
+    /// Checks whether a path is ignored; the last matching pattern wins.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let relative = match path.strip_prefix(&rule.base) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            let text = if rule.anchored {
+                slash_separated(relative)
+            } else {
+                match relative.file_name() {
+                    Some(name) => name.to_string_lossy().into_owned(),
+                    None => continue,
+                }
+            };
+            if glob_match(rule.pattern.as_bytes(), text.as_bytes()) {
+                ignored = !rule.negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Finds the nested packages below a root directory.
+///
+/// A package is a directory with its own AGENTS.md. The root itself is not
+/// included. Directories ignored by `.gitignore` files (at the root or in any
+/// directory on the way down) or by the repository's `.git/info/exclude` are not
+/// searched, and neither are `.git`,
+/// symlinked directories and directories that cannot be read.
+///
+/// # Arguments
+///
+/// * `root` - Path to the root project directory
+///
+/// # Returns
+///
+/// Returns the package directories, sorted by path.
+///
+/// # Errors
+///
+/// Returns an error if the root directory cannot be read.
+pub fn discover_packages(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let rules = IgnoreRules::for_root(root);
+
+    let mut packages = Vec::new();
+    let entries = fs::read_dir(root)?;
+    walk_entries(entries, &rules, &mut packages);
+    packages.sort();
+    Ok(packages)
+}
+
+/// Checks whether a path below a root is ignored by the root's `.gitignore`
+/// files or the repository's `.git/info/exclude`, or lies in an ignored
+/// directory or in `.git`.
+///
+/// # Arguments
+///
+/// * `root` - Path to the root project directory
+/// * `path` - Path to check
+pub fn is_ignored(root: &Path, path: &Path) -> bool {
+    let relative = match path.strip_prefix(root) {
+        Ok(relative) => relative,
+        Err(_) => return false,
+    };
+
+    let mut rules = IgnoreRules::for_root(root);
+    let mut current = root.to_path_buf();
+    for component in relative.components() {
+        if component.as_os_str() == GIT_DIR {
+            return true;
+        }
+        current.push(component);
+        let is_dir = current.is_dir();
+        if rules.is_ignored(&current, is_dir) {
+            return true;
+        }
+        if is_dir {
+            rules.add_file(&current);
+        }
+    }
+    false
+}
+
+/// Finds the project in recursive mode with rule inheritance that a directory belongs to.
+///
+/// The directory itself and its ancestors are checked, nearest first. Directories
+/// excluded by the project's `.gitignore` files don't inherit.
+///
+/// # Arguments
+///
+/// * `dir` - Path to a project, package or intermediate directory
+///
+/// # Returns
+///
+/// Returns the root directory of the inheriting project, or `None` if there is none.
+pub fn inheriting_root(dir: &Path) -> Option<PathBuf> {
+    let root = dir.ancestors().find(|ancestor| {
+        load_project_config(ancestor)
+            .map(|config| config.recursive && config.inherit)
+            .unwrap_or(false)
+    })?;
+    if is_ignored(root, dir) {
+        return None;
+    }
+    Some(root.to_path_buf())
+}
+
+/// Returns the rules directories whose rules a directory gets, nearest first.
+///
+/// The directory's own `.rules.local` comes first, so local rules override team
+/// rules with the same file name, followed by its own `.rules`. With inheritance,
+/// the existing `.rules` directories of every ancestor up to the inheriting root
+/// follow.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project or package directory
+pub fn rules_chain(dir: &Path) -> Vec<PathBuf> {
+    let mut chain = vec![dir.join(RULES_LOCAL_DIR), dir.join(RULES_DIR)];
+    if let Some(root) = inheriting_root(dir) {
+        chain.extend(
+            dir.ancestors()
+                .skip(1)
+                .take_while(|ancestor| ancestor.starts_with(&root))
+                .map(|ancestor| ancestor.join(RULES_DIR))
+                .filter(|rules_path| rules_path.is_dir()),
+        );
+    }
+    chain
+}
+
+/// Lists the rules a directory's targets get, sorted by file name.
+///
+/// With inheritance, rules from every directory of the [`rules_chain`] are
+/// included, and a rule overrides rules with the same file name further up.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project or package directory
+///
+/// # Errors
+///
+/// Returns an error if a `.rules` directory cannot be read.
+pub fn inherited_rule_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut rules: Vec<PathBuf> = Vec::new();
+    for rules_path in rules_chain(dir) {
+        for rule in list_rule_files(&rules_path)? {
+            if !rules
+                .iter()
+                .any(|known| known.file_name() == rule.file_name())
+            {
+                rules.push(rule);
+            }
+        }
+    }
+    rules.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(rules)
+}
+
+/// Lists the files below a root directory as slash-separated relative paths.
+///
+/// Files ignored by `.gitignore` files are left out, and `.git` and symlinked
+/// directories are not searched, as in [`discover_packages`].
+///
+/// # Arguments
+///
+/// * `root` - Path to the root project directory
+///
+/// # Returns
+///
+/// Returns the relative paths of the files, sorted.
+pub fn list_project_files(root: &Path) -> Vec<String> {
+    let rules = IgnoreRules::for_root(root);
+
+    let mut files = Vec::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        walk_files(root, entries, &rules, &mut files);
+    }
+    files.sort();
+    files
+}
+
+/// Lists the directories below a root directory that Git doesn't ignore.
+///
+/// Directories ignored by `.gitignore` files are left out along with everything
+/// below them, and `.git` and symlinked directories are not searched, as in
+/// [`discover_packages`].
+///
+/// # Arguments
+///
+/// * `root` - Path to the root project directory
+///
+/// # Returns
+///
+/// Returns the directories, sorted by path. The root itself is not included.
+pub fn list_project_dirs(root: &Path) -> Vec<PathBuf> {
+    let rules = IgnoreRules::for_root(root);
+
+    let mut dirs = Vec::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        walk_dirs(entries, &rules, &mut dirs);
+    }
+    dirs.sort();
+    dirs
+}
+
+/// Checks whether a slash-separated relative path matches a gitignore-style glob.
+///
+/// Patterns without a `/` match the file name at any depth, and patterns with
+/// one match the whole path (a leading `/` is ignored). Brace sets such as
+/// `*.{ts,tsx}` match any of their alternatives.
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+    let alternatives = expand_braces(pattern);
+    if alternatives != [pattern] {
+        return alternatives
+            .iter()
+            .any(|alternative| glob_matches(alternative, path));
+    }
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches('/').as_bytes(), path.as_bytes())
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        glob_match(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+/// Expands the first `{a,b}` set of a glob into one pattern per alternative.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let (start, end) = match (glob.find('{'), glob.find('}')) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        _ => return vec![glob.to_string()],
+    };
+    glob[start + 1..end]
+        .split(',')
+        .flat_map(|alternative| {
+            expand_braces(&format!(
+                "{}{}{}",
+                &glob[..start],
+                alternative,
+                &glob[end + 1..]
+            ))
+        })
+        .collect()
+}
+
+/// Collects the files among the entries of a directory and its subdirectories.
+fn walk_files(root: &Path, entries: fs::ReadDir, rules: &IgnoreRules, files: &mut Vec<String>) {
+    for entry in entries.flatten() {
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+        let path = entry.path();
+        if entry.file_name() == GIT_DIR || rules.is_ignored(&path, file_type.is_dir()) {
+            continue;
+        }
+
+        if !file_type.is_dir() {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(slash_separated(relative));
+            }
+            continue;
+        }
+        let entries = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        if path.join(GITIGNORE_FILENAME).is_file() {
+            let mut nested = rules.clone();
+            nested.add_file(&path);
+            walk_files(root, entries, &nested, files);
+        } else {
+            walk_files(root, entries, rules, files);
+        }
+    }
+}
+
+/// Collects the directories among the entries of a directory and its subdirectories.
+fn walk_dirs(entries: fs::ReadDir, rules: &IgnoreRules, dirs: &mut Vec<PathBuf>) {
+    for entry in entries.flatten() {
+        // file_type() doesn't follow symlinks, so linked directories are skipped
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_name() == GIT_DIR || rules.is_ignored(&path, true) {
+            continue;
+        }
+        dirs.push(path.clone());
+
+        let entries = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        if path.join(GITIGNORE_FILENAME).is_file() {
+            let mut nested = rules.clone();
+            nested.add_file(&path);
+            walk_dirs(entries, &nested, dirs);
+        } else {
+            walk_dirs(entries, rules, dirs);
+        }
+    }
+}
+
+/// Searches the entries of a directory for packages.
+fn walk_entries(entries: fs::ReadDir, rules: &IgnoreRules, packages: &mut Vec<PathBuf>) {
+    for entry in entries.flatten() {
+        // file_type() doesn't follow symlinks, so linked directories are skipped
+        if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_name() == GIT_DIR || rules.is_ignored(&path, true) {
+            continue;
+        }
+
+        if path.join(AGENTS_FILENAME).is_file() {
+            packages.push(path.clone());
+        }
+
+        let entries = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        if path.join(GITIGNORE_FILENAME).is_file() {
+            let mut nested = rules.clone();
+            nested.add_file(&path);
+            walk_entries(entries, &nested, packages);
+        } else {
+            walk_entries(entries, rules, packages);
+        }
+    }
+}
+
+/// Joins the components of a relative path with `/`, as in ignore patterns.
+fn slash_separated(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Matches text against a gitignore-style glob.
+///
+/// `*` and `?` don't match `/`, `**/` matches zero or more directories and a
+/// trailing `**` matches everything.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+            let rest = &rest[1..];
+            match rest.strip_prefix(b"/") {
+                Some(after) => {
+                    glob_match(after, text)
+                        || text
+                            .iter()
+                            .enumerate()
+                            .any(|(i, c)| *c == b'/' && glob_match(after, &text[i + 1..]))
+                }
+                None => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+            }
+        }
+        Some((b'*', rest)) => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        Some((b'?', rest)) => {
+            matches!(text.split_first(), Some((c, tail)) if *c != b'/' && glob_match(rest, tail))
+        }
+        Some((c, rest)) => {
+            matches!(text.split_first(), Some((t, tail)) if t == c && glob_match(rest, tail))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn package(root: &Path, relative: &str) -> PathBuf {
+        let dir = root.join(relative);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(AGENTS_FILENAME), "# Package").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_discover_packages_honours_gitignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(AGENTS_FILENAME), "# Root").unwrap();
+        fs::write(
+            root.join(GITIGNORE_FILENAME),
+            "# build output\nnode_modules/\n/dist\n*.tmp\n",
+        )
+        .unwrap();
+
+        let web = package(root, "packages/web");
+        let api = package(root, "packages/api");
+        let nested = package(root, "packages/api/plugins/auth");
+        package(root, "node_modules/dep");
+        package(root, "packages/web/node_modules/dep");
+        package(root, "dist");
+        package(root, "cache.tmp");
+        package(root, ".git/modules/sub");
+
+        // Nested ignore files apply below their directory, and can re-include
+        fs::write(api.join(GITIGNORE_FILENAME), "plugins/\n!plugins/\n").unwrap();
+        package(root, "packages/web/generated");
+        fs::write(web.join(GITIGNORE_FILENAME), "generated\n").unwrap();
+
+        assert_eq!(discover_packages(root).unwrap(), vec![api, nested, web]);
+    }
+
+    #[test]
+    fn test_discover_packages_honours_git_info_exclude() {
+        let dir = tempdir().unwrap();
+        let repo = dir.path();
+        fs::create_dir_all(repo.join(".git").join("info")).unwrap();
+        fs::write(
+            repo.join(".git").join("info").join("exclude"),
+            "# local\nvendor/\n/app/scratch\n",
+        )
+        .unwrap();
+        // The project is a subdirectory of the repository
+        let root = repo.join("app");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join(AGENTS_FILENAME), "# Root").unwrap();
+
+        let web = package(&root, "web");
+        package(&root, "vendor/v");
+        package(&root, "scratch");
+
+        assert_eq!(discover_packages(&root).unwrap(), vec![web]);
+        assert!(is_ignored(&root, &root.join("vendor").join("v")));
+        assert!(!list_project_dirs(&root).contains(&root.join("vendor")));
+    }
+
+    #[test]
+    fn test_list_project_dirs_skips_ignored_directories() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(GITIGNORE_FILENAME), "node_modules/\ntarget\n").unwrap();
+        for relative in [
+            "packages/web/.cursor/rules",
+            "packages/web/node_modules/dep",
+            "target/debug",
+            ".git/objects",
+        ] {
+            fs::create_dir_all(root.join(relative)).unwrap();
+        }
+
+        assert_eq!(
+            list_project_dirs(root),
+            vec![
+                root.join("packages"),
+                root.join("packages/web"),
+                root.join("packages/web/.cursor"),
+                root.join("packages/web/.cursor/rules"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(GITIGNORE_FILENAME), "build/\n**/fixtures/**\n").unwrap();
+        fs::create_dir_all(root.join("build/out")).unwrap();
+        fs::create_dir_all(root.join("packages/web/fixtures/a")).unwrap();
+
+        assert!(is_ignored(root, &root.join("build/out")));
+        assert!(is_ignored(root, &root.join("packages/web/fixtures/a")));
+        assert!(is_ignored(root, &root.join(".git/hooks")));
+        assert!(!is_ignored(root, &root.join("packages/web")));
+    }
+
+    #[test]
+    fn test_inherited_rule_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let package = package(root, "packages/web");
+        for (relative, name, content) in [
+            (".rules", "style.md", "root style"),
+            (".rules", "security.md", "root security"),
+            ("packages/.rules", "style.md", "packages style"),
+            ("packages/web/.rules", "web.md", "web"),
+        ] {
+            fs::create_dir_all(root.join(relative)).unwrap();
+            fs::write(root.join(relative).join(name), content).unwrap();
+        }
+
+        // Without inheritance only the package's own rules apply, local ones first
+        fs::create_dir(package.join(".rules.local")).unwrap();
+        fs::write(package.join(".rules.local/web.md"), "local web").unwrap();
+        assert_eq!(
+            inherited_rule_files(&package).unwrap(),
+            vec![package.join(".rules.local/web.md")]
+        );
+        fs::remove_dir_all(package.join(".rules.local")).unwrap();
+
+        fs::write(
+            root.join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"recursive": true, "inherit": true}"#,
+        )
+        .unwrap();
+        assert_eq!(inheriting_root(&package).as_deref(), Some(root));
+        assert_eq!(
+            inherited_rule_files(&package).unwrap(),
+            vec![
+                root.join(".rules/security.md"),
+                root.join("packages/.rules/style.md"),
+                package.join(".rules/web.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*.md", b"notes.md"));
+        assert!(!glob_match(b"*.md", b"docs/notes.md"));
+        assert!(glob_match(b"docs/**", b"docs/a/b.md"));
+        assert!(glob_match(b"**/target", b"target"));
+        assert!(glob_match(b"a/**/b", b"a/x/y/b"));
+        assert!(glob_match(b"a/**/b", b"a/b"));
+        assert!(glob_match(b"file?.txt", b"file1.txt"));
+        assert!(!glob_match(b"file?.txt", b"file/.txt"));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.rs", "src/daemon/mod.rs"));
+        assert!(glob_matches("/src/**/*.rs", "src/daemon/mod.rs"));
+        assert!(!glob_matches("src/*.rs", "src/daemon/mod.rs"));
+        assert!(glob_matches("src/*.{ts,tsx}", "src/app.tsx"));
+        assert!(!glob_matches("src/*.{ts,tsx}", "src/app.js"));
+    }
+}
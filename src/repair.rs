@@ -0,0 +1,370 @@
+//! Repair of AGENTS.md aliases that were replaced by regular files.
+//!
+//! Some tools (for example Claude Code's `/init`, or editors that save by writing a
+//! new file and renaming it over the old one) replace the `CLAUDE.md` or `GEMINI.md`
+//! symlink with a regular file. known preserves the new content, merges it into
+//! AGENTS.md inside a marked block for review, and restores the symlink.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::conditional::{rendered_alias_content, sync_symlinked_alias};
+use crate::config::{load_project_config, ClaudeStrategy, GeminiStrategy};
+use crate::constants::{
+    AGENTS_FILENAME, BACKUPS_DIR, CLAUDE_FILENAME, GEMINI_FILENAME, KNOWN_STATE_DIR, REPAIRS_DIR,
+};
+
+/// Prefix of the marker line that opens a block merged from a replaced alias
+pub const MERGED_BLOCK_START: &str = "<!-- >>> known: merged from";
+
+/// Marker line that closes a block merged from a replaced alias
+pub const MERGED_BLOCK_END: &str = "<!-- <<< known: end of merged content <<< -->";
+
+/// Delay between the checks of whether a file is still being written
+const SETTLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of checks before a file that keeps changing is read anyway
+const SETTLE_ATTEMPTS: usize = 10;
+
+/// What was done to repair a replaced alias
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasRepair {
+    /// Name of the repaired alias (for example `CLAUDE.md`)
+    pub alias: String,
+    /// Where the content of the replacing file was preserved
+    pub backup_path: PathBuf,
+    /// Whether new content was appended to AGENTS.md
+    pub merged: bool,
+}
+
+/// Returns the aliases of a project that are expected to be symlinks to AGENTS.md.
+///
+/// Aliases using another strategy (the Claude `import` strategy or the Gemini
+/// `settings` strategy) are regular files or absent by design and are not included.
+///
+/// # Errors
+///
+/// Returns an error if the project configuration cannot be loaded.
+pub fn symlinked_aliases(dir: &Path) -> io::Result<Vec<&'static str>> {
+    let project_config = load_project_config(dir)?;
+    let mut aliases = Vec::new();
+    if project_config.aliases.claude == ClaudeStrategy::Symlink {
+        aliases.push(CLAUDE_FILENAME);
+    }
+    if project_config.aliases.gemini == GeminiStrategy::Symlink {
+        aliases.push(GEMINI_FILENAME);
+    }
+    Ok(aliases)
+}
+
+/// Repairs an alias that was replaced by a regular file.
+///
+/// The content of the regular file is copied to `.known/backups/repairs/`, where
+/// `known status` finds the repairs (other commands back files up to
+/// `.known/backups/` itself). If it contains
+/// anything AGENTS.md doesn't already have, that content is appended to AGENTS.md
+/// between marker comments so it can be reviewed and integrated. Finally the
+/// alias is turned back into a symlink to AGENTS.md, or into the copy rendered
+/// for its agent when AGENTS.md has conditional sections.
+///
+/// The alias is read once writes to it have stopped, so a tool that deletes it
+/// and writes a new file isn't caught between the two steps. Nothing is done if
+/// the alias is missing, empty, still a symlink or an up-to-date rendered copy,
+/// or if AGENTS.md doesn't exist.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `alias` - File name of the alias (for example `CLAUDE.md`)
+///
+/// # Returns
+///
+/// Returns a description of the repair, or `None` if no repair was needed.
+///
+/// # Errors
+///
+/// Returns an error if reading the files, writing the backup, merging into
+/// AGENTS.md or restoring the symlink fails.
+pub fn repair_alias(dir: &Path, alias: &str) -> io::Result<Option<AliasRepair>> {
+    let alias_path = dir.join(alias);
+    let agents_path = dir.join(AGENTS_FILENAME);
+
+    match fs::symlink_metadata(&alias_path) {
+        Ok(metadata) if metadata.file_type().is_file() => {}
+        _ => return Ok(None),
+    }
+    if !agents_path.is_file() {
+        return Ok(None);
+    }
+
+    let replaced_content = match read_settled(&alias_path) {
+        Ok(content) => {
+            String::from_utf8(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if replaced_content.is_empty() {
+        // The tool hasn't written its content yet; its next write triggers the repair
+        return Ok(None);
+    }
+    let rendered_content = rendered_alias_content(dir, alias)?;
+    if rendered_content.as_deref() == Some(replaced_content.as_str()) {
+        // The copy known renders when AGENTS.md has conditional sections
+        return Ok(None);
+    }
+    let agents_content = fs::read_to_string(&agents_path)?;
+
+    let backup_path = write_backup(&repairs_dir(dir), alias, replaced_content.as_bytes())?;
+
+    let new_content = new_content_for_agents(
+        &replaced_content,
+        rendered_content.as_deref().unwrap_or(&agents_content),
+    );
+    let merged = !new_content.trim().is_empty();
+    if merged {
+        let mut updated = agents_content;
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(&render_merged_block(alias, new_content));
+        fs::write(&agents_path, updated)?;
+    }
+
+    sync_symlinked_alias(dir, alias)?;
+
+    Ok(Some(AliasRepair {
+        alias: alias.to_string(),
+        backup_path,
+        merged,
+    }))
+}
+
+/// Lists the backups left by repairs of an alias, oldest first.
+///
+/// The daemon repairs aliases in the background, so these backups (and the
+/// blocks merged into AGENTS.md) are how `known status` reports its repairs.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `alias` - File name of the alias (for example `CLAUDE.md`)
+pub fn alias_backups(dir: &Path, alias: &str) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(repairs_dir(dir)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let prefix = format!("{}.", alias);
+    let mut backups: Vec<(u64, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            let timestamp = name
+                .strip_prefix(&prefix)?
+                .split('.')
+                .next()?
+                .parse()
+                .ok()?;
+            Some((timestamp, path))
+        })
+        .collect();
+    backups.sort();
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Counts the blocks merged from an alias into AGENTS.md that are still awaiting review.
+pub fn pending_merges(agents_content: &str, alias: &str) -> usize {
+    let marker = format!("{} {} at ", MERGED_BLOCK_START, alias);
+    agents_content.matches(&marker).count()
+}
+
+/// Returns the part of the replacing content that AGENTS.md doesn't already contain.
+///
+/// Tools that rewrite the alias usually start from its previous content, which is
+/// the content of AGENTS.md, so only the text added after it is merged.
+fn new_content_for_agents<'a>(replaced_content: &'a str, agents_content: &str) -> &'a str {
+    if replaced_content.trim() == agents_content.trim() {
+        ""
+    } else if let Some(rest) = replaced_content.strip_prefix(agents_content.trim_end()) {
+        rest
+    } else {
+        replaced_content
+    }
+}
+
+/// Renders the marked block that is appended to AGENTS.md.
+fn render_merged_block(alias: &str, content: &str) -> String {
+    format!(
+        "\n{} {} at {}; review and integrate, then delete these markers >>> -->\n{}\n{}\n",
+        MERGED_BLOCK_START,
+        alias,
+        unix_timestamp(),
+        content.trim_matches('\n'),
+        MERGED_BLOCK_END
+    )
+}
+
+/// Writes a copy of a replaced file to `.known/backups/<name>.<timestamp>`.
+pub(crate) fn backup_file(dir: &Path, name: &str, content: &[u8]) -> io::Result<PathBuf> {
+    write_backup(&dir.join(KNOWN_STATE_DIR).join(BACKUPS_DIR), name, content)
+}
+
+/// Returns the directory that holds the backups of repaired aliases.
+fn repairs_dir(dir: &Path) -> PathBuf {
+    dir.join(KNOWN_STATE_DIR)
+        .join(BACKUPS_DIR)
+        .join(REPAIRS_DIR)
+}
+
+/// Writes a copy of a file to `<backups_dir>/<name>.<timestamp>`.
+fn write_backup(backups_dir: &Path, name: &str, content: &[u8]) -> io::Result<PathBuf> {
+    fs::create_dir_all(backups_dir)?;
+
+    let timestamp = unix_timestamp();
+    let mut backup_path = backups_dir.join(format!("{}.{}", name, timestamp));
+    let mut counter = 1;
+    while backup_path.exists() {
+        backup_path = backups_dir.join(format!("{}.{}.{}", name, timestamp, counter));
+        counter += 1;
+    }
+
+    fs::write(&backup_path, content)?;
+    Ok(backup_path)
+}
+
+/// Reads a file once writes to it have stopped.
+///
+/// Tools that save by deleting a file and writing a new one trigger an event as
+/// soon as the empty file is created, before its content is written. The file's
+/// size and modification time are compared after a short delay until they stop
+/// changing, for at most a second, before it is read.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, for example because it was
+/// renamed away in the meantime.
+pub(crate) fn read_settled(path: &Path) -> io::Result<Vec<u8>> {
+    let state = |path: &Path| {
+        fs::symlink_metadata(path).map(|metadata| (metadata.len(), metadata.modified().ok()))
+    };
+    let mut previous = state(path)?;
+    for _ in 0..SETTLE_ATTEMPTS {
+        thread::sleep(SETTLE_INTERVAL);
+        let current = state(path)?;
+        if current == previous {
+            break;
+        }
+        previous = current;
+    }
+    fs::read(path)
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn is_symlink(path: &Path) -> bool {
+        fs::symlink_metadata(path)
+            .map(|metadata| metadata.file_type().is_symlink())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_repair_alias_merges_new_content_and_restores_link() {
+        let dir = tempdir().unwrap();
+        let agents_path = dir.path().join(AGENTS_FILENAME);
+        fs::write(&agents_path, "# Agents\nShared rules.\n").unwrap();
+        fs::write(
+            dir.path().join(CLAUDE_FILENAME),
+            "# Agents\nShared rules.\n\n## Commands\nRun cargo test.\n",
+        )
+        .unwrap();
+
+        let repair = repair_alias(dir.path(), CLAUDE_FILENAME).unwrap().unwrap();
+        assert!(repair.merged);
+        assert!(repair
+            .backup_path
+            .starts_with(dir.path().join(KNOWN_STATE_DIR)));
+        assert!(fs::read_to_string(&repair.backup_path)
+            .unwrap()
+            .contains("Run cargo test."));
+
+        let agents = fs::read_to_string(&agents_path).unwrap();
+        assert!(agents
+            .starts_with("# Agents\nShared rules.\n\n<!-- >>> known: merged from CLAUDE.md at "));
+        assert!(agents.ends_with(
+            "## Commands\nRun cargo test.\n<!-- <<< known: end of merged content <<< -->\n"
+        ));
+        assert_eq!(agents.matches("Shared rules.").count(), 1);
+
+        assert!(is_symlink(&dir.path().join(CLAUDE_FILENAME)));
+        assert!(repair_alias(dir.path(), CLAUDE_FILENAME).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_repair_is_recorded() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents\n").unwrap();
+        fs::write(dir.path().join(CLAUDE_FILENAME), "# Agents\nRun tests.\n").unwrap();
+        assert!(alias_backups(dir.path(), CLAUDE_FILENAME).is_empty());
+
+        // Backups written by other commands are not repairs
+        backup_file(dir.path(), CLAUDE_FILENAME, b"# Claude\n").unwrap();
+        assert!(alias_backups(dir.path(), CLAUDE_FILENAME).is_empty());
+
+        let repair = repair_alias(dir.path(), CLAUDE_FILENAME).unwrap().unwrap();
+
+        assert_eq!(
+            alias_backups(dir.path(), CLAUDE_FILENAME),
+            vec![repair.backup_path]
+        );
+        assert!(alias_backups(dir.path(), GEMINI_FILENAME).is_empty());
+        let agents_content = fs::read_to_string(dir.path().join(AGENTS_FILENAME)).unwrap();
+        assert_eq!(pending_merges(&agents_content, CLAUDE_FILENAME), 1);
+        assert_eq!(pending_merges(&agents_content, GEMINI_FILENAME), 0);
+    }
+
+    #[test]
+    fn test_repair_alias_without_new_content() {
+        let dir = tempdir().unwrap();
+        let agents_path = dir.path().join(AGENTS_FILENAME);
+        fs::write(&agents_path, "# Agents\n").unwrap();
+        fs::write(dir.path().join(GEMINI_FILENAME), "# Agents\n").unwrap();
+
+        let repair = repair_alias(dir.path(), GEMINI_FILENAME).unwrap().unwrap();
+        assert!(!repair.merged);
+        assert_eq!(fs::read_to_string(&agents_path).unwrap(), "# Agents\n");
+        assert!(is_symlink(&dir.path().join(GEMINI_FILENAME)));
+    }
+
+    #[test]
+    fn test_symlinked_aliases_respects_strategies() {
+        let dir = tempdir().unwrap();
+        assert_eq!(
+            symlinked_aliases(dir.path()).unwrap(),
+            vec![CLAUDE_FILENAME, GEMINI_FILENAME]
+        );
+
+        fs::write(
+            dir.path().join(crate::constants::PROJECT_CONFIG_FILENAME),
+            r#"{"aliases": {"claude": "import"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            symlinked_aliases(dir.path()).unwrap(),
+            vec![GEMINI_FILENAME]
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Parsing of the frontmatter block at the top of rules files.
+//!
+//! Rules files may start with a small YAML-style frontmatter block delimited by
+//! `---` lines, as used by Cursor (`.mdc`) and Windsurf rules. Only the subset
+//! needed for rule metadata is supported: `key: value` pairs, flow lists
+//! (`[a, b]`), block lists (`- item`), quoted strings and comments.
+
+use std::io;
+
+/// The delimiter line that opens and closes a frontmatter block
+const DELIMITER: &str = "---";
+
+/// A single frontmatter value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontmatterValue {
+    /// A scalar value such as a string, boolean or number
+    Scalar(String),
+    /// A list of scalar values
+    List(Vec<String>),
+}
+
+impl FrontmatterValue {
+    /// Returns the value as a list, splitting scalar values on top-level commas.
+    pub fn as_list(&self) -> Vec<String> {
+        match self {
+            FrontmatterValue::Scalar(value) => split_top_level_commas(value),
+            FrontmatterValue::List(items) => items.clone(),
+        }
+    }
+
+    /// Returns the value as a boolean, if it is a scalar `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            FrontmatterValue::Scalar(value) => match value.to_ascii_lowercase().as_str() {
+                "true" | "yes" => Some(true),
+                "false" | "no" => Some(false),
+                _ => None,
+            },
+            FrontmatterValue::List(_) => None,
+        }
+    }
+
+    /// Returns the value as a string, if it is a scalar.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FrontmatterValue::Scalar(value) => Some(value),
+            FrontmatterValue::List(_) => None,
+        }
+    }
+}
+
+/// The parsed frontmatter block of a rules file, in source order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frontmatter {
+    pub entries: Vec<(String, FrontmatterValue)>,
+}
+
+impl Frontmatter {
+    /// Gets the value of a key, if present
+    pub fn get(&self, key: &str) -> Option<&FrontmatterValue> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Splits a rules file into its frontmatter and body.
+///
+/// Returns `None` for the frontmatter if the file doesn't start with a `---` line.
+///
+/// # Arguments
+///
+/// * `content` - The full content of the rules file
+///
+/// # Errors
+///
+/// Returns an `InvalidData` error if the frontmatter block is never closed or
+/// contains a line that is neither a `key: value` pair nor a list item.
+pub fn split_frontmatter(content: &str) -> io::Result<(Option<Frontmatter>, &str)> {
+    let first_line_end = content.find('\n').unwrap_or(content.len());
+    if content[..first_line_end].trim_end() != DELIMITER {
+        return Ok((None, content));
+    }
+
+    let mut frontmatter = Frontmatter::default();
+    let mut offset = (first_line_end + 1).min(content.len());
+    let mut pending_list: Option<(String, Vec<String>)> = None;
+
+    while offset < content.len() {
+        let line_end = content[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(content.len());
+        let line = content[offset..line_end].trim_end_matches('\r');
+        let next_offset = (line_end + 1).min(content.len());
+
+        if line.trim_end() == DELIMITER {
+            if let Some((key, items)) = pending_list.take() {
+                frontmatter.entries.push((key, pending_value(items)));
+            }
+            return Ok((Some(frontmatter), &content[next_offset..]));
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            offset = next_offset;
+            continue;
+        }
+
+        let list_item = if trimmed == "-" {
+            Some("")
+        } else {
+            trimmed.strip_prefix("- ")
+        };
+        if let Some(item) = list_item {
+            if is_unterminated(item.trim()) {
+                return Err(unterminated_value(line));
+            }
+            match pending_list.as_mut() {
+                Some((_, items)) => items.push(unquote(item.trim())),
+                None => return Err(invalid_line(line)),
+            }
+            offset = next_offset;
+            continue;
+        }
+
+        if let Some((key, items)) = pending_list.take() {
+            frontmatter.entries.push((key, pending_value(items)));
+        }
+
+        let (key, value) = trimmed.split_once(':').ok_or_else(|| invalid_line(line))?;
+        let key = key.trim().to_string();
+        let value = strip_comment(value).trim();
+        if key.is_empty() {
+            return Err(invalid_line(line));
+        }
+
+        if is_unterminated(value) {
+            return Err(unterminated_value(line));
+        }
+
+        if value.is_empty() {
+            // Either an empty scalar or the start of a block list
+            pending_list = Some((key, Vec::new()));
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            frontmatter
+                .entries
+                .push((key, FrontmatterValue::List(split_top_level_commas(inner))));
+        } else {
+            frontmatter
+                .entries
+                .push((key, FrontmatterValue::Scalar(unquote(value))));
+        }
+
+        offset = next_offset;
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Frontmatter block is not terminated by a '---' line",
+    ))
+}
+
+/// Renders frontmatter entries back into a `---` delimited block.
+pub fn render_frontmatter(entries: &[(String, FrontmatterValue)]) -> String {
+    let mut rendered = String::from("---\n");
+    for (key, value) in entries {
+        match value {
+            FrontmatterValue::Scalar(value) if value.is_empty() => {
+                rendered.push_str(&format!("{}:\n", key));
+            }
+            FrontmatterValue::Scalar(value) => {
+                rendered.push_str(&format!("{}: {}\n", key, value));
+            }
+            FrontmatterValue::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| quote(item)).collect();
+                rendered.push_str(&format!("{}: [{}]\n", key, items.join(", ")));
+            }
+        }
+    }
+    rendered.push_str("---\n");
+    rendered
+}
+
+/// Quotes a value if it contains characters with special meaning in YAML.
+pub fn quote(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(['*', '&', '!', '{', '[', '\'', '"', '-', '?', '@', '`'])
+        || value.contains([':', '#', ',', '[', ']', '{', '}']);
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Removes surrounding quotes from a scalar value.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')))
+    {
+        value[1..value.len() - 1]
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\")
+    } else {
+        value.to_string()
+    }
+}
+
+/// Removes a trailing ` # comment` from an unquoted value.
+fn strip_comment(value: &str) -> &str {
+    let trimmed = value.trim_start();
+    if trimmed.starts_with('"') || trimmed.starts_with('\'') {
+        return value;
+    }
+    match value.find(" #") {
+        Some(index) => &value[..index],
+        None => value,
+    }
+}
+
+/// Splits a comma-separated list, ignoring commas inside braces and quotes.
+fn split_top_level_commas(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote_char: Option<char> = None;
+
+    for c in value.chars() {
+        match c {
+            '"' | '\'' if quote_char.is_none() => quote_char = Some(c),
+            c if Some(c) == quote_char => quote_char = None,
+            '{' | '[' if quote_char.is_none() => depth += 1,
+            '}' | ']' if quote_char.is_none() => depth = depth.saturating_sub(1),
+            ',' if depth == 0 && quote_char.is_none() => {
+                items.push(unquote(&current));
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(unquote(&current));
+
+    items.into_iter().filter(|item| !item.is_empty()).collect()
+}
+
+/// Converts the items collected after a `key:` line into a value.
+///
+/// A key without any list items is an empty scalar.
+fn pending_value(items: Vec<String>) -> FrontmatterValue {
+    if items.is_empty() {
+        FrontmatterValue::Scalar(String::new())
+    } else {
+        FrontmatterValue::List(items)
+    }
+}
+
+/// Builds the error returned for an unparsable frontmatter line.
+fn invalid_line(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid frontmatter line: '{}'", line.trim()),
+    )
+}
+
+/// Checks whether a value opens a flow list, a brace set or a quoted scalar
+/// without closing it.
+///
+/// Such values would otherwise be read as plain scalars, and a rule with
+/// `agents: [cursor` would silently apply to no target.
+fn is_unterminated(value: &str) -> bool {
+    if value.starts_with('[') {
+        !value.ends_with(']')
+    } else if value.starts_with('{') {
+        value.matches('{').count() != value.matches('}').count()
+    } else if let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        value.len() < 2 || !value.ends_with(quote)
+    } else {
+        false
+    }
+}
+
+fn unterminated_value(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unterminated value in frontmatter line: '{}'", line.trim()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frontmatter_without_block() {
+        let (frontmatter, body) = split_frontmatter("# Rule\nBody").unwrap();
+        assert!(frontmatter.is_none());
+        assert_eq!(body, "# Rule\nBody");
+    }
+
+    #[test]
+    fn test_split_frontmatter_scalars_and_lists() {
+        let content = "---\ndescription: \"Style: short\"\nglobs: src/**/*.rs, tests/*.rs\nalwaysApply: false # comment\ntags: [review, \"a,b\"]\nagents:\n  - cursor\n  - windsurf\n---\n# Body\n";
+        let (frontmatter, body) = split_frontmatter(content).unwrap();
+        let frontmatter = frontmatter.unwrap();
+
+        assert_eq!(body, "# Body\n");
+        assert_eq!(
+            frontmatter.get("description").unwrap().as_str(),
+            Some("Style: short")
+        );
+        assert_eq!(
+            frontmatter.get("globs").unwrap().as_list(),
+            vec!["src/**/*.rs", "tests/*.rs"]
+        );
+        assert_eq!(
+            frontmatter.get("alwaysApply").unwrap().as_bool(),
+            Some(false)
+        );
+        assert_eq!(
+            frontmatter.get("tags").unwrap().as_list(),
+            vec!["review", "a,b"]
+        );
+        assert_eq!(
+            frontmatter.get("agents").unwrap().as_list(),
+            vec!["cursor", "windsurf"]
+        );
+    }
+
+    #[test]
+    fn test_split_frontmatter_brace_globs() {
+        let (frontmatter, _) = split_frontmatter("---\nglobs: src/**/*.{ts,tsx}\n---\n").unwrap();
+        assert_eq!(
+            frontmatter.unwrap().get("globs").unwrap().as_list(),
+            vec!["src/**/*.{ts,tsx}"]
+        );
+    }
+
+    #[test]
+    fn test_split_frontmatter_errors() {
+        let unterminated = split_frontmatter("---\ndescription: test\n");
+        assert_eq!(unterminated.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let invalid = split_frontmatter("---\nnot a pair\n---\n");
+        assert_eq!(invalid.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        for line in [
+            "agents: [cursor",
+            "globs: [",
+            "globs: {src,lib/**",
+            "description: \"unterminated",
+            "description: 'unterminated",
+            "description: \"",
+        ] {
+            let content = format!("---\n{}\n---\n", line);
+            let error = split_frontmatter(&content).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", line);
+            assert!(error.to_string().contains("Unterminated"), "{}", line);
+        }
+        let block_item = split_frontmatter("---\nagents:\n  - \"cursor\n---\n");
+        assert_eq!(block_item.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let (frontmatter, _) = split_frontmatter("---\nglobs: {src,lib}/**/*.rs\n---\n").unwrap();
+        assert_eq!(
+            frontmatter.unwrap().get("globs").unwrap().as_list(),
+            vec!["{src,lib}/**/*.rs"]
+        );
+    }
+
+    #[test]
+    fn test_render_frontmatter_round_trip() {
+        let entries = vec![
+            (
+                "inclusion".to_string(),
+                FrontmatterValue::Scalar("fileMatch".to_string()),
+            ),
+            (
+                "globs".to_string(),
+                FrontmatterValue::List(vec!["src/**/*.{ts,tsx}".to_string()]),
+            ),
+        ];
+        let rendered = render_frontmatter(&entries);
+        let (frontmatter, body) = split_frontmatter(&rendered).unwrap();
+        assert_eq!(frontmatter.unwrap().entries, entries);
+        assert_eq!(body, "");
+    }
+}
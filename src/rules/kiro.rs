@@ -0,0 +1,134 @@
+//! Rendering of rules into Kiro steering files.
+//!
+//! Kiro reads `.kiro/steering/*.md` and decides when to include each file from
+//! its `inclusion` frontmatter (`always`, `fileMatch` or `manual`). Since the
+//! format differs from the canonical rule metadata, steering files are generated
+//! copies rather than symlinks.
+
+use super::frontmatter::{render_frontmatter, FrontmatterValue};
+use super::{Activation, Rule};
+
+/// Prefix of the comment that marks a steering file as generated by known
+pub const GENERATED_MARKER: &str = "<!-- Generated by known";
+
+/// Translates canonical rule metadata into Kiro steering frontmatter entries.
+pub fn steering_frontmatter(rule: &Rule) -> Vec<(String, FrontmatterValue)> {
+    let scalar = |value: &str| FrontmatterValue::Scalar(value.to_string());
+
+    match rule.metadata.activation() {
+        Activation::Always => vec![("inclusion".to_string(), scalar("always"))],
+        Activation::FileMatch(globs) => {
+            // Kiro accepts a single pattern, so multiple globs become a brace set
+            let pattern = if globs.len() == 1 {
+                globs[0].clone()
+            } else {
+                format!("{{{}}}", globs.join(","))
+            };
+            vec![
+                ("inclusion".to_string(), scalar("fileMatch")),
+                (
+                    "fileMatchPattern".to_string(),
+                    FrontmatterValue::Scalar(format!(
+                        "\"{}\"",
+                        pattern.replace('\\', "\\\\").replace('"', "\\\"")
+                    )),
+                ),
+            ]
+        }
+        Activation::Manual => vec![("inclusion".to_string(), scalar("manual"))],
+    }
+}
+
+/// Renders the full content of the steering file generated from a rule.
+///
+/// # Arguments
+///
+/// * `rule` - The parsed source rule
+/// * `source` - Project-relative path of the source rule, shown in the marker comment
+pub fn render_steering_file(rule: &Rule, source: &str) -> String {
+    let mut rendered = render_frontmatter(&steering_frontmatter(rule));
+    rendered.push_str(&format!(
+        "{} from {}. Edit the source file instead. -->\n\n",
+        GENERATED_MARKER, source
+    ));
+    rendered.push_str(rule.body.trim_start_matches('\n'));
+    rendered
+}
+
+/// Checks whether the content of a steering file was generated by known.
+pub fn is_generated_steering_file(content: &str) -> bool {
+    content.contains(GENERATED_MARKER)
+}
+
+/// Returns the source path noted in the marker comment of a generated steering file.
+pub fn steering_source(content: &str) -> Option<&str> {
+    let start = content.find(GENERATED_MARKER)? + GENERATED_MARKER.len();
+    let line = content[start..].lines().next()?;
+    let source = line.strip_prefix(" from ")?;
+    source
+        .find(". Edit the source file instead.")
+        .map(|end| &source[..end])
+}
+
+/// Removes the marker comment from a generated steering file, leaving a plain
+/// steering file that known no longer recognizes as its own.
+pub fn strip_generated_marker(content: &str) -> String {
+    let start = match content.find(GENERATED_MARKER) {
+        Some(start) => start,
+        None => return content.to_string(),
+    };
+    let end = content[start..]
+        .find('\n')
+        .map(|offset| start + offset + 1)
+        .unwrap_or(content.len());
+    // Drop the blank line that separated the marker from the body
+    let rest = content[end..].strip_prefix('\n').unwrap_or(&content[end..]);
+    format!("{}{}", &content[..start], rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn render(content: &str) -> String {
+        let rule = Rule::parse(Path::new(".rules/rule.mdc"), content).unwrap();
+        render_steering_file(&rule, ".rules/rule.mdc")
+    }
+
+    #[test]
+    fn test_render_always_rule() {
+        let rendered = render("# Style\nUse tabs.\n");
+        assert!(rendered.starts_with("---\ninclusion: always\n---\n"));
+        assert!(rendered.ends_with("# Style\nUse tabs.\n"));
+        assert!(is_generated_steering_file(&rendered));
+
+        let stripped = strip_generated_marker(&rendered);
+        assert_eq!(
+            stripped,
+            "---\ninclusion: always\n---\n# Style\nUse tabs.\n"
+        );
+        assert!(!is_generated_steering_file(&stripped));
+        assert_eq!(steering_source(&rendered), Some(".rules/rule.mdc"));
+        assert_eq!(steering_source(&stripped), None);
+    }
+
+    #[test]
+    fn test_render_file_match_rule() {
+        let rendered = render("---\nglobs: src/**/*.ts, src/**/*.tsx\n---\n# TS\n");
+        assert!(rendered.starts_with(
+            "---\ninclusion: fileMatch\nfileMatchPattern: \"{src/**/*.ts,src/**/*.tsx}\"\n---\n"
+        ));
+        assert!(rendered.ends_with("# TS\n"));
+
+        let single = render("---\nglobs: \"*.py\"\n---\nPython\n");
+        assert!(single.contains("fileMatchPattern: \"*.py\"\n"));
+    }
+
+    #[test]
+    fn test_render_manual_rule() {
+        let rendered = render("---\ndescription: SQL guidance\nalwaysApply: false\n---\nSQL\n");
+        assert!(rendered.starts_with("---\ninclusion: manual\n---\n"));
+        assert!(!rendered.contains("alwaysApply"));
+    }
+}
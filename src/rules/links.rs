@@ -0,0 +1,131 @@
+//! Record of the links known created in target directories.
+//!
+//! Editors that save by writing a new file and renaming it over the old one
+//! replace a managed link with a regular file. A regular file in a target
+//! directory is only treated as an edit to a rule when the record shows that
+//! known linked that entry to that rule; any other file is adopted as a new rule.
+//!
+//! The record is kept in `.known/links.json`, mapping each entry (relative to the
+//! project directory) to the rule it links to.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::constants::{KNOWN_STATE_DIR, LINKS_FILENAME};
+use crate::transaction::exclude_state_dir;
+
+type LinkRecord = BTreeMap<String, PathBuf>;
+
+/// Records that an entry in a target directory links to a rule.
+///
+/// # Errors
+///
+/// Returns an error if the record cannot be read or written.
+pub(crate) fn record_link(
+    project_dir: &Path,
+    entry_path: &Path,
+    rule_path: &Path,
+) -> io::Result<()> {
+    let key = match record_key(project_dir, entry_path) {
+        Some(key) => key,
+        None => return Ok(()),
+    };
+    let mut record = load_record(project_dir)?;
+    if record.get(&key).map(PathBuf::as_path) == Some(rule_path) {
+        return Ok(());
+    }
+    record.insert(key, rule_path.to_path_buf());
+    save_record(project_dir, &record)
+}
+
+/// Removes an entry from the record, if it is in it.
+///
+/// # Errors
+///
+/// Returns an error if the record cannot be read or written.
+pub(crate) fn forget_link(project_dir: &Path, entry_path: &Path) -> io::Result<()> {
+    let key = match record_key(project_dir, entry_path) {
+        Some(key) => key,
+        None => return Ok(()),
+    };
+    let mut record = load_record(project_dir)?;
+    if record.remove(&key).is_none() {
+        return Ok(());
+    }
+    save_record(project_dir, &record)
+}
+
+/// Returns the rule an entry was linked to, if known created the link.
+pub(crate) fn recorded_link(project_dir: &Path, entry_path: &Path) -> Option<PathBuf> {
+    let key = record_key(project_dir, entry_path)?;
+    load_record(project_dir).ok()?.remove(&key)
+}
+
+/// Discards the whole record, for a project known no longer manages.
+///
+/// # Errors
+///
+/// Returns an error if the record exists but cannot be removed.
+pub(crate) fn discard_record(project_dir: &Path) -> io::Result<()> {
+    save_record(project_dir, &LinkRecord::new())
+}
+
+/// Returns the slash-separated path of an entry relative to the project directory.
+fn record_key(project_dir: &Path, entry_path: &Path) -> Option<String> {
+    let relative = entry_path.strip_prefix(project_dir).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn record_path(project_dir: &Path) -> PathBuf {
+    project_dir.join(KNOWN_STATE_DIR).join(LINKS_FILENAME)
+}
+
+fn load_record(project_dir: &Path) -> io::Result<LinkRecord> {
+    match fs::read_to_string(record_path(project_dir)) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(LinkRecord::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes the record, removing it (and an empty `.known`) when it has no entries.
+fn save_record(project_dir: &Path, record: &LinkRecord) -> io::Result<()> {
+    let path = record_path(project_dir);
+    if record.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        let _ = fs::remove_dir(project_dir.join(KNOWN_STATE_DIR));
+        return Ok(());
+    }
+
+    fs::create_dir_all(project_dir.join(KNOWN_STATE_DIR))?;
+    exclude_state_dir(project_dir)?;
+    let content = serde_json::to_string_pretty(record)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(&path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_and_forget_links() {
+        let dir = tempdir().unwrap();
+        let entry = dir.path().join(".cursor/rules/style.md");
+        let rule = dir.path().join(".rules/style.md");
+
+        assert_eq!(recorded_link(dir.path(), &entry), None);
+        record_link(dir.path(), &entry, &rule).unwrap();
+        assert_eq!(recorded_link(dir.path(), &entry), Some(rule));
+
+        forget_link(dir.path(), &entry).unwrap();
+        assert_eq!(recorded_link(dir.path(), &entry), None);
+        assert!(!dir.path().join(KNOWN_STATE_DIR).exists());
+    }
+}
@@ -0,0 +1,917 @@
+//! Commands that manage the rules of a project, such as `known rule new`,
+//! `known rule disable` and `known profile use`.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::frontmatter::{quote, render_frontmatter, FrontmatterValue};
+use super::kiro::render_steering_file;
+use super::{
+    is_rule_disabled, is_rule_in_profile, list_rule_files, Activation, Rule, RuleMetadata,
+    TargetFormat, BUILTIN_TARGETS,
+};
+use crate::aider::sync_aider_config_if_enabled;
+use crate::config::{get_global_rules_dir, load_project_config, save_project_config};
+use crate::constants::{
+    AIDER_CONFIG_FILENAME, AIDER_TARGET, GLOBAL_RULE_PREFIX, KNOWN_STATE_DIR, LINKS_FILENAME,
+    PROJECT_CONFIG_FILENAME, RULES_DIR, RULES_LOCAL_DIR,
+};
+use crate::daemon::symlinks::{project_targets, sync_project_targets};
+use crate::git::exclude_file_path;
+use crate::monorepo::{discover_packages, inherited_rule_files, inheriting_root};
+use crate::transaction::Transaction;
+
+/// What [`set_rule_enabled`] changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleToggle {
+    /// File name of the rule as recorded in `.known.json`
+    pub name: String,
+    /// Whether `.known.json` changed
+    pub changed: bool,
+    /// Whether the rule stays disabled by `enabled: false` in its frontmatter
+    pub disabled_in_frontmatter: bool,
+}
+
+/// Disables or re-enables a rule for a project and resyncs its targets.
+///
+/// Disabled rules are recorded by file name in the `disabled` list of
+/// `.known.json`. Their source stays in `.rules`, but their entries are removed
+/// from every target and from the Aider read list. The rule can be named by its
+/// file name or, if that is unambiguous, by its file name without extension.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `name` - Name of the rule
+/// * `enabled` - Whether the rule should be synced
+///
+/// # Returns
+///
+/// Returns what was changed.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if no rule of the project matches the name, or an
+/// error if the project configuration cannot be loaded or saved, or the targets
+/// cannot be synced.
+pub fn set_rule_enabled(dir: &Path, name: &str, enabled: bool) -> io::Result<RuleToggle> {
+    let mut project_config = load_project_config(dir)?;
+
+    let (name, rule_path) = match find_rule(dir, name)? {
+        Some((name, rule_path)) => (name, Some(rule_path)),
+        // A disabled rule can still be re-enabled after its file is gone
+        None if project_config.is_rule_disabled(name) => (name.to_string(), None),
+        None => return Err(no_such_rule(name)),
+    };
+
+    let changed = project_config.set_rule_disabled(&name, !enabled);
+    if changed {
+        save_project_config(dir, &project_config)?;
+        sync_project_targets(dir)?;
+        sync_aider_config_if_enabled(dir)?;
+    }
+
+    let disabled_in_frontmatter = rule_path
+        .and_then(|path| Rule::load(&path).ok())
+        .is_some_and(|rule| rule.metadata.disabled);
+    Ok(RuleToggle {
+        name,
+        changed,
+        disabled_in_frontmatter,
+    })
+}
+
+/// A profile defined in a project's `.known.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileSummary {
+    /// Name of the profile
+    pub name: String,
+    /// Whether the profile is the active one
+    pub active: bool,
+    /// File names of the rules the profile selects, sorted by name
+    pub rules: Vec<String>,
+}
+
+/// Lists the profiles defined for a project with the rules they select.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if the project configuration cannot be loaded or a rules
+/// directory cannot be read.
+pub fn list_profiles(dir: &Path) -> io::Result<Vec<ProfileSummary>> {
+    let project_config = load_project_config(dir)?;
+    let rules = inherited_rule_files(dir)?;
+    Ok(project_config
+        .profiles
+        .iter()
+        .map(|(name, profile)| ProfileSummary {
+            name: name.clone(),
+            active: project_config.profile.as_ref() == Some(name),
+            rules: rules
+                .iter()
+                .filter(|path| {
+                    let tags = Rule::load(path)
+                        .map(|rule| rule.metadata.tags)
+                        .unwrap_or_default();
+                    profile.selects(&file_name_of(path), &tags)
+                })
+                .map(|path| file_name_of(path))
+                .collect(),
+        })
+        .collect())
+}
+
+/// Activates a profile, or deactivates profiles, and relinks the project's targets.
+///
+/// The active profile is saved as `profile` in `.known.json`. Its rules are
+/// linked into the targets before the entries of rules outside the profile are
+/// removed, and each link is replaced atomically, so a rule in both the old and
+/// the new profile never disappears from a target. The switch is journaled like
+/// `known symlink`: if a step fails, every change is rolled back, and `known undo`
+/// reverts a completed switch.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `name` - Name of the profile, or `None` to sync every rule again
+///
+/// # Returns
+///
+/// Returns the profile that is now active, with the rules it selects.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if the profile isn't defined, or an error if the
+/// project configuration cannot be loaded or saved, or the targets cannot be
+/// synced.
+pub fn use_profile(dir: &Path, name: Option<&str>) -> io::Result<Option<ProfileSummary>> {
+    let mut project_config = load_project_config(dir)?;
+    if let Some(name) = name {
+        if !project_config.profiles.contains_key(name) {
+            let defined: Vec<&str> = project_config.profiles.keys().map(String::as_str).collect();
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                if defined.is_empty() {
+                    format!(
+                        "No profile named '{}'; no profiles are defined in {}",
+                        name, PROJECT_CONFIG_FILENAME
+                    )
+                } else {
+                    format!(
+                        "No profile named '{}' in {}; defined profiles: {}",
+                        name,
+                        PROJECT_CONFIG_FILENAME,
+                        defined.join(", ")
+                    )
+                },
+            ));
+        }
+    }
+
+    // The switch is journaled and rolled back as a whole if a step fails
+    Transaction::run(dir, "profile", |tx| {
+        let profile = name.map(str::to_string);
+        if project_config.profile != profile {
+            project_config.profile = profile;
+            tx.snapshot(&dir.join(PROJECT_CONFIG_FILENAME))?;
+            save_project_config(dir, &project_config)?;
+        }
+        snapshot_targets(tx, dir)?;
+        sync_project_targets(dir)?;
+        sync_aider_config_if_enabled(dir)
+    })?;
+
+    Ok(list_profiles(dir)?
+        .into_iter()
+        .find(|summary| summary.active))
+}
+
+/// Records every path that syncing a project's targets may change.
+///
+/// These are the target directories (or their missing parents), every entry
+/// already in them along with the `.rules` file it would be adopted as, the
+/// entry of every rule of the project and global rule, the link record, the
+/// Aider configuration and `.git/info/exclude`.
+fn snapshot_targets(tx: &mut Transaction, dir: &Path) -> io::Result<()> {
+    let rules = project_rules(dir)?;
+    for target in project_targets(dir) {
+        let target_path = target.path(dir);
+        let missing: Vec<&Path> = target_path
+            .ancestors()
+            .take_while(|ancestor| !ancestor.exists())
+            .collect();
+        for ancestor in missing.into_iter().rev() {
+            tx.snapshot(ancestor)?;
+        }
+
+        if target_path.is_dir() {
+            for entry in fs::read_dir(&target_path)? {
+                let path = entry?.path();
+                if let Some(file_name) = path.file_name() {
+                    tx.snapshot(&dir.join(RULES_DIR).join(file_name))?;
+                }
+                tx.snapshot(&path)?;
+            }
+        }
+        for (entry_name, _) in &rules {
+            tx.snapshot(&target_path.join(target.entry_name(OsStr::new(entry_name))))?;
+        }
+    }
+
+    tx.snapshot(&dir.join(KNOWN_STATE_DIR).join(LINKS_FILENAME))?;
+    tx.snapshot(&dir.join(AIDER_CONFIG_FILENAME))?;
+    if let Some(exclude_path) = exclude_file_path(dir).filter(|path| path.starts_with(dir)) {
+        tx.snapshot(&exclude_path)?;
+    }
+    Ok(())
+}
+
+/// Frontmatter of a rule scaffolded by [`create_rule`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewRule {
+    /// When the rule should be applied, for agents that choose rules themselves
+    pub description: Option<String>,
+    /// File globs the rule applies to
+    pub globs: Vec<String>,
+    /// Whether the rule is always applied
+    pub always: bool,
+    /// Targets the rule is limited to; empty means every target
+    pub agents: Vec<String>,
+}
+
+/// Creates a rule in a project's `.rules` with canonical frontmatter and syncs it.
+///
+/// The frontmatter follows Cursor's `.mdc` layout: `description` (left blank to
+/// fill in unless one is given), `globs` as a comma-separated value and
+/// `alwaysApply`, which is `true` if `always` is set. Otherwise the rule is
+/// applied to its `globs`, or only when requested if it has none. The rule body
+/// starts with a title, so the new rule passes `known lint`.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `name` - File name of the rule; `.md` is appended if it has no extension
+/// * `options` - Frontmatter of the new rule
+///
+/// # Returns
+///
+/// Returns the path of the new rule.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if the name isn't a plain file name or an
+/// agent isn't a known target, an `AlreadyExists` error if the rule exists, or
+/// an error if the rule cannot be written or the targets cannot be synced.
+pub fn create_rule(dir: &Path, name: &str, options: &NewRule) -> io::Result<PathBuf> {
+    let file_name = rule_file_name(name, "md")?;
+    if let Some(agent) = options.agents.iter().find(|agent| !is_known_agent(agent)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Unknown agent '{}'; expected one of: {}",
+                agent,
+                known_agents().join(", ")
+            ),
+        ));
+    }
+
+    let rules_path = dir.join(RULES_DIR);
+    let rule_path = rules_path.join(&file_name);
+    if rule_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", rule_path.display()),
+        ));
+    }
+
+    let scalar = |value: &str| FrontmatterValue::Scalar(value.to_string());
+    let description = options
+        .description
+        .as_deref()
+        .map(quote)
+        .unwrap_or_default();
+    let mut entries = vec![
+        ("description".to_string(), scalar(&description)),
+        ("globs".to_string(), scalar(&options.globs.join(", "))),
+    ];
+    entries.push((
+        "alwaysApply".to_string(),
+        scalar(if options.always { "true" } else { "false" }),
+    ));
+    if !options.agents.is_empty() {
+        entries.push((
+            "agents".to_string(),
+            FrontmatterValue::List(options.agents.clone()),
+        ));
+    }
+    let title = Path::new(&file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    fs::create_dir_all(&rules_path)?;
+    fs::write(
+        &rule_path,
+        format!("{}\n# {}\n\n", render_frontmatter(&entries), title),
+    )?;
+    sync_project_targets(dir)?;
+    sync_aider_config_if_enabled(dir)?;
+    Ok(rule_path)
+}
+
+/// A rule of a project as listed by [`list_rules`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSummary {
+    /// File name of the rule's entries
+    pub name: String,
+    /// Path to the rules file
+    pub path: PathBuf,
+    /// How the rule is activated
+    pub activation: Activation,
+    /// Targets the rule is limited to; empty means every target
+    pub agents: Vec<String>,
+    /// Targets the rule is never synced to
+    pub exclude_agents: Vec<String>,
+    /// Names of the enabled targets that have an entry for the rule
+    pub linked: Vec<&'static str>,
+    /// Why the rule is synced to no target, if it is disabled or outside the profile
+    pub inactive: Option<String>,
+}
+
+/// Lists the rules of a project with their metadata and the targets linking them.
+///
+/// Rules inherited from parent `.rules` directories, local rules and global rules
+/// (named with their `global-` prefix) are included, sorted by name.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Errors
+///
+/// Returns an error if a rules directory cannot be read.
+pub fn list_rules(dir: &Path) -> io::Result<Vec<RuleSummary>> {
+    let targets = project_targets(dir);
+    let mut rules = project_rules(dir)?;
+    rules.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(rules
+        .into_iter()
+        .map(|(name, path)| {
+            let metadata = Rule::load(&path)
+                .map(|rule| rule.metadata)
+                .unwrap_or_default();
+            let linked = targets
+                .iter()
+                .filter(|target| {
+                    let entry_name = target.entry_name(OsStr::new(&name));
+                    target.is_managed_entry(&target.path(dir).join(entry_name))
+                })
+                .map(|target| target.name)
+                .collect();
+            let inactive = inactive_reason(dir, &name, &path, &metadata);
+            RuleSummary {
+                activation: metadata.activation(),
+                agents: metadata.agents,
+                exclude_agents: metadata.exclude_agents,
+                name,
+                path,
+                linked,
+                inactive,
+            }
+        })
+        .collect())
+}
+
+/// What one agent gets from a rule, as returned by [`show_rule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleView {
+    /// Name of the target
+    pub agent: &'static str,
+    /// How the agent gets the rule, or why it doesn't
+    pub note: String,
+    /// Content the agent reads, or `None` if the rule isn't synced to it
+    pub content: Option<String>,
+}
+
+/// Renders a rule the way each target enabled for a project sees it.
+///
+/// Symlink targets and Aider read the rule as it is, while Kiro reads the
+/// steering file rendered from it. Targets the rule isn't synced to are
+/// included with the reason.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `name` - Name of the rule
+///
+/// # Returns
+///
+/// Returns the name of the rule's entries and a view per target.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if no rule of the project matches the name, or an
+/// error if the rule cannot be read or parsed.
+pub fn show_rule(dir: &Path, name: &str) -> io::Result<(String, Vec<RuleView>)> {
+    let (name, rule_path) = find_rule(dir, name)?.ok_or_else(|| no_such_rule(name))?;
+    let content = fs::read_to_string(&rule_path)?;
+    let rule = Rule::parse(&rule_path, &content)?;
+    let source = match rule_path.strip_prefix(dir) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => rule_path.display().to_string(),
+    };
+    let inactive = inactive_reason(dir, &name, &rule_path, &rule.metadata);
+
+    let mut views: Vec<RuleView> = project_targets(dir)
+        .into_iter()
+        .map(|target| {
+            let entry_path = Path::new(target.dir).join(target.entry_name(OsStr::new(&name)));
+            match skip_reason(&inactive, &rule.metadata, target.name) {
+                Some(reason) => RuleView {
+                    agent: target.name,
+                    note: format!("not synced: {}", reason),
+                    content: None,
+                },
+                None => match target.format {
+                    TargetFormat::Symlink => RuleView {
+                        agent: target.name,
+                        note: format!("{} links to {}", entry_path.display(), source),
+                        content: Some(content.clone()),
+                    },
+                    TargetFormat::KiroSteering => RuleView {
+                        agent: target.name,
+                        note: format!("{} is rendered from {}", entry_path.display(), source),
+                        content: Some(render_steering_file(&rule, &source)),
+                    },
+                },
+            }
+        })
+        .collect();
+
+    if load_project_config(dir)?.has_target(AIDER_TARGET) {
+        let in_rules_dir = rule_path.parent() == Some(dir.join(RULES_DIR).as_path());
+        views.push(match skip_reason(&inactive, &rule.metadata, AIDER_TARGET) {
+            Some(reason) => RuleView {
+                agent: AIDER_TARGET,
+                note: format!("not read: {}", reason),
+                content: None,
+            },
+            None if !in_rules_dir => RuleView {
+                agent: AIDER_TARGET,
+                note: format!("not read: only rules in {} are listed", RULES_DIR),
+                content: None,
+            },
+            None => RuleView {
+                agent: AIDER_TARGET,
+                note: format!("{} reads {}", AIDER_CONFIG_FILENAME, source),
+                content: Some(content.clone()),
+            },
+        });
+    }
+    Ok((name, views))
+}
+
+/// Renames a rule in a project's `.rules` or `.rules.local` and updates its links.
+///
+/// The entries of the old name are removed from every target, the rule is
+/// linked under its new name, and the `disabled` list and profiles in
+/// `.known.json` follow the rename. Packages that inherit the rule are resynced
+/// as well.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `from` - Name of the rule
+/// * `to` - New file name; the old extension is kept if it has none
+///
+/// # Returns
+///
+/// Returns the old and the new file name of the rule.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if no rule matches, an `InvalidInput` error if the
+/// rule doesn't belong to the project or the new name isn't a plain file name, an
+/// `AlreadyExists` error if a rule with the new name exists, or an error if the
+/// rule cannot be renamed or the targets cannot be synced.
+pub fn rename_rule(dir: &Path, from: &str, to: &str) -> io::Result<(String, String)> {
+    let (from, rule_path) = find_rule(dir, from)?.ok_or_else(|| no_such_rule(from))?;
+    let rules_dir = match rule_path.parent() {
+        Some(parent) if parent == dir.join(RULES_DIR) || parent == dir.join(RULES_LOCAL_DIR) => {
+            parent.to_path_buf()
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} doesn't belong to this project; rename it where it is defined",
+                    rule_path.display()
+                ),
+            ))
+        }
+    };
+    let extension = Path::new(&from)
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let to = rule_file_name(to, &extension)?;
+    let new_path = rules_dir.join(&to);
+    if new_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", new_path.display()),
+        ));
+    }
+
+    fs::rename(&rule_path, &new_path)?;
+    let mut project_config = load_project_config(dir)?;
+    if project_config.rename_rule(&from, &to) {
+        save_project_config(dir, &project_config)?;
+    }
+
+    let mut projects = vec![dir.to_path_buf()];
+    if inheriting_root(dir).is_some() {
+        projects.extend(discover_packages(dir)?);
+    }
+    for project in &projects {
+        for target in project_targets(project) {
+            target.remove_rule(project, OsStr::new(&from))?;
+        }
+        sync_project_targets(project)?;
+        sync_aider_config_if_enabled(project)?;
+    }
+    Ok((from, to))
+}
+
+/// Returns why a rule is synced to no target, if it is disabled or outside the profile.
+fn inactive_reason(
+    dir: &Path,
+    name: &str,
+    rule_path: &Path,
+    metadata: &RuleMetadata,
+) -> Option<String> {
+    let is_global = name.starts_with(GLOBAL_RULE_PREFIX)
+        && get_global_rules_dir().is_ok_and(|global_dir| rule_path.starts_with(global_dir));
+    if metadata.disabled {
+        Some("disabled by enabled: false".to_string())
+    } else if is_rule_disabled(dir, OsStr::new(name)) {
+        Some(format!("disabled in {}", PROJECT_CONFIG_FILENAME))
+    } else if !is_global && !is_rule_in_profile(dir, rule_path) {
+        let profile = load_project_config(dir)
+            .ok()
+            .and_then(|config| config.profile)
+            .unwrap_or_default();
+        Some(format!("not in profile {}", profile))
+    } else {
+        None
+    }
+}
+
+/// Returns why a rule isn't synced to a target, if it isn't.
+fn skip_reason(
+    inactive: &Option<String>,
+    metadata: &RuleMetadata,
+    target_name: &str,
+) -> Option<String> {
+    if let Some(reason) = inactive {
+        return Some(reason.clone());
+    }
+    if metadata.applies_to(target_name) {
+        None
+    } else if metadata
+        .exclude_agents
+        .iter()
+        .any(|agent| agent.eq_ignore_ascii_case(target_name))
+    {
+        Some("listed in exclude_agents".to_string())
+    } else {
+        Some("not listed in agents".to_string())
+    }
+}
+
+/// Returns the rules of a project by the name of their entries.
+///
+/// Rules inherited from parent `.rules` directories, local rules and global rules
+/// (named with their `global-` prefix) are included.
+fn project_rules(dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut rules: Vec<(String, PathBuf)> = inherited_rule_files(dir)?
+        .into_iter()
+        .map(|path| (file_name_of(&path), path))
+        .collect();
+    if let Ok(global_rules_dir) = get_global_rules_dir() {
+        for path in list_rule_files(&global_rules_dir)? {
+            let entry_name = format!("{}{}", GLOBAL_RULE_PREFIX, file_name_of(&path));
+            rules.push((entry_name, path));
+        }
+    }
+    Ok(rules)
+}
+
+/// Checks a rule name given on the command line and adds an extension if it has none.
+fn rule_file_name(name: &str, default_extension: &str) -> io::Result<String> {
+    let path = Path::new(name);
+    if name.is_empty()
+        || name.starts_with('.')
+        || path.file_name().is_none_or(|file_name| file_name != name)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{}' is not a valid rule file name", name),
+        ));
+    }
+    if path.extension().is_some() || default_extension.is_empty() {
+        Ok(name.to_string())
+    } else {
+        Ok(format!("{}.{}", name, default_extension))
+    }
+}
+
+/// Checks whether a name can be used in the `agents` frontmatter.
+fn is_known_agent(name: &str) -> bool {
+    known_agents()
+        .iter()
+        .any(|agent| agent.eq_ignore_ascii_case(name))
+}
+
+/// Returns the names of every target rules can be synced to.
+fn known_agents() -> Vec<&'static str> {
+    BUILTIN_TARGETS
+        .iter()
+        .map(|target| target.name)
+        .chain(std::iter::once(AIDER_TARGET))
+        .collect()
+}
+
+/// Builds the error returned when no rule of a project matches a name.
+fn no_such_rule(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No rule named '{}' in {}", name, RULES_DIR),
+    )
+}
+
+/// Finds the rule of a project with the given file name, or file stem if it is unambiguous.
+///
+/// Rules inherited from parent `.rules` directories, local rules and global rules
+/// (named with their `global-` prefix) are included.
+///
+/// # Returns
+///
+/// Returns the name of the rule's entries and the path of the rule.
+fn find_rule(dir: &Path, name: &str) -> io::Result<Option<(String, PathBuf)>> {
+    let rules = project_rules(dir)?;
+    if let Some(rule) = rules.iter().find(|(entry_name, _)| entry_name == name) {
+        return Ok(Some(rule.clone()));
+    }
+
+    let mut by_stem = rules.iter().filter(|(entry_name, _)| {
+        Path::new(entry_name)
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy() == name)
+    });
+    match (by_stem.next(), by_stem.next()) {
+        (Some(rule), None) => Ok(Some(rule.clone())),
+        (Some(_), Some(_)) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Several rules are named '{}'; give the file name with its extension",
+                name
+            ),
+        )),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the file name of a rule as a string.
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::CURSOR_RULES_DIR;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_disable_and_enable_rule() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        sync_project_targets(dir.path()).unwrap();
+        let link = dir.path().join(CURSOR_RULES_DIR).join("style.md");
+        assert!(fs::symlink_metadata(&link).is_ok());
+
+        let toggle = set_rule_enabled(dir.path(), "style", false).unwrap();
+        assert_eq!(toggle.name, "style.md");
+        assert!(toggle.changed);
+        assert!(fs::symlink_metadata(&link).is_err());
+        assert!(rules_path.join("style.md").exists());
+        assert_eq!(
+            load_project_config(dir.path()).unwrap().disabled,
+            vec!["style.md".to_string()]
+        );
+        assert!(
+            !set_rule_enabled(dir.path(), "style.md", false)
+                .unwrap()
+                .changed
+        );
+
+        set_rule_enabled(dir.path(), "style.md", true).unwrap();
+        assert!(fs::symlink_metadata(&link).is_ok());
+        assert!(load_project_config(dir.path()).unwrap().disabled.is_empty());
+
+        let missing = set_rule_enabled(dir.path(), "missing", false).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_use_profile_switches_linked_rules() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        fs::write(
+            rules_path.join("checklist.md"),
+            "---\ntags: [review]\n---\nCheck the tests.",
+        )
+        .unwrap();
+        let mut project_config = load_project_config(dir.path()).unwrap();
+        project_config.profiles.insert(
+            "review".to_string(),
+            crate::config::RuleProfile {
+                rules: vec!["style".to_string()],
+                tags: vec!["review".to_string()],
+            },
+        );
+        project_config
+            .profiles
+            .insert("feature".to_string(), Default::default());
+        save_project_config(dir.path(), &project_config).unwrap();
+        let cursor_rules = dir.path().join(CURSOR_RULES_DIR);
+
+        let active = use_profile(dir.path(), Some("feature")).unwrap().unwrap();
+        assert_eq!(active.name, "feature");
+        assert!(active.rules.is_empty());
+        assert!(fs::symlink_metadata(cursor_rules.join("style.md")).is_err());
+
+        let active = use_profile(dir.path(), Some("review")).unwrap().unwrap();
+        assert_eq!(active.rules, vec!["checklist.md", "style.md"]);
+        assert!(fs::symlink_metadata(cursor_rules.join("checklist.md")).is_ok());
+        assert_eq!(
+            load_project_config(dir.path()).unwrap().profile.as_deref(),
+            Some("review")
+        );
+
+        let unknown = use_profile(dir.path(), Some("incident")).unwrap_err();
+        assert_eq!(unknown.kind(), io::ErrorKind::NotFound);
+
+        assert!(use_profile(dir.path(), None).unwrap().is_none());
+        assert_eq!(list_profiles(dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_use_profile_rolls_back_on_failure() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        let mut project_config = load_project_config(dir.path()).unwrap();
+        project_config
+            .profiles
+            .insert("feature".to_string(), Default::default());
+        save_project_config(dir.path(), &project_config).unwrap();
+        use_profile(dir.path(), None).unwrap();
+        let cursor_entry = dir.path().join(CURSOR_RULES_DIR).join("style.md");
+        assert!(fs::symlink_metadata(&cursor_entry).is_ok());
+
+        // .windsurf is a file, so the Windsurf target cannot be synced
+        fs::remove_dir_all(dir.path().join(".windsurf")).unwrap();
+        fs::write(dir.path().join(".windsurf"), "").unwrap();
+        assert!(use_profile(dir.path(), Some("feature")).is_err());
+
+        // The profile isn't saved and the Cursor link is back
+        assert_eq!(load_project_config(dir.path()).unwrap().profile, None);
+        assert!(fs::symlink_metadata(&cursor_entry).is_ok());
+    }
+
+    #[test]
+    fn test_created_rules_pass_lint() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src").join("main.rs"), "fn main() {}").unwrap();
+        let rust = NewRule {
+            description: Some("Rust conventions: errors and tests".to_string()),
+            globs: vec!["src/**/*.rs".to_string(), "src/*.{rs,toml}".to_string()],
+            ..Default::default()
+        };
+        let rule_path = create_rule(dir.path(), "rust", &rust).unwrap();
+        create_rule(dir.path(), "tone", &NewRule::default()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&rule_path).unwrap(),
+            "---\ndescription: \"Rust conventions: errors and tests\"\nglobs: src/**/*.rs, src/*.{rs,toml}\nalwaysApply: false\n---\n\n# rust\n\n"
+        );
+        let rule = Rule::load(&rule_path).unwrap();
+        assert_eq!(
+            rule.metadata.activation(),
+            Activation::FileMatch(vec![
+                "src/**/*.rs".to_string(),
+                "src/*.{rs,toml}".to_string()
+            ])
+        );
+        assert_eq!(crate::lint::lint_project(dir.path()).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_create_list_and_rename_rule() {
+        let dir = tempdir().unwrap();
+        let options = NewRule {
+            globs: vec!["src/**/*.rs".to_string()],
+            agents: vec!["cursor".to_string()],
+            ..Default::default()
+        };
+        let rule_path = create_rule(dir.path(), "rust", &options).unwrap();
+        assert_eq!(rule_path, dir.path().join(RULES_DIR).join("rust.md"));
+        assert_eq!(
+            fs::read_to_string(&rule_path).unwrap(),
+            "---\ndescription:\nglobs: src/**/*.rs\nalwaysApply: false\nagents: [cursor]\n---\n\n# rust\n\n"
+        );
+        let existing = create_rule(dir.path(), "rust.md", &options).unwrap_err();
+        assert_eq!(existing.kind(), io::ErrorKind::AlreadyExists);
+        let unknown_agent = NewRule {
+            agents: vec!["vim".to_string()],
+            ..Default::default()
+        };
+        assert!(create_rule(dir.path(), "other", &unknown_agent).is_err());
+        assert!(create_rule(dir.path(), "../escape", &NewRule::default()).is_err());
+
+        let rules = list_rules(dir.path()).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "rust.md");
+        assert_eq!(
+            rules[0].activation,
+            Activation::FileMatch(vec!["src/**/*.rs".to_string()])
+        );
+        assert_eq!(rules[0].linked, vec!["cursor"]);
+        assert_eq!(rules[0].inactive, None);
+
+        set_rule_enabled(dir.path(), "rust", false).unwrap();
+        assert_eq!(
+            rename_rule(dir.path(), "rust", "rust-style").unwrap(),
+            ("rust.md".to_string(), "rust-style.md".to_string())
+        );
+        let cursor_rules = dir.path().join(CURSOR_RULES_DIR);
+        assert!(fs::symlink_metadata(cursor_rules.join("rust.md")).is_err());
+        assert_eq!(
+            load_project_config(dir.path()).unwrap().disabled,
+            vec!["rust-style.md".to_string()]
+        );
+        set_rule_enabled(dir.path(), "rust-style", true).unwrap();
+        assert!(fs::symlink_metadata(cursor_rules.join("rust-style.md")).is_ok());
+
+        let missing = rename_rule(dir.path(), "rust", "other").unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_show_rule_renders_each_target() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(
+            rules_path.join("style.md"),
+            "---\nglobs: \"*.rs\"\nexclude_agents: [windsurf]\n---\nUse tabs.\n",
+        )
+        .unwrap();
+        let mut project_config = load_project_config(dir.path()).unwrap();
+        project_config.enable_target("kiro");
+        save_project_config(dir.path(), &project_config).unwrap();
+
+        let (name, views) = show_rule(dir.path(), "style").unwrap();
+        assert_eq!(name, "style.md");
+        let agents: Vec<&str> = views.iter().map(|view| view.agent).collect();
+        assert_eq!(agents, vec!["cursor", "windsurf", "kiro"]);
+        assert!(views[0]
+            .content
+            .as_deref()
+            .unwrap()
+            .starts_with("---\nglobs"));
+        assert_eq!(views[1].content, None);
+        assert_eq!(views[1].note, "not synced: listed in exclude_agents");
+        let kiro = views[2].content.as_deref().unwrap();
+        assert!(kiro.contains("inclusion: fileMatch"));
+        assert!(kiro.ends_with("Use tabs.\n"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Rules files and the targets they are synchronized to.
+//!
+//! This module provides the canonical metadata model for files in `.rules`
+//! and the table of built-in targets (tool-specific rules directories) that
+//! the symlink machinery keeps in sync.
+
+pub mod frontmatter;
+pub mod kiro;
+pub mod links;
+pub mod manage;
+pub mod targets;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::load_project_config;
+
+pub use frontmatter::{split_frontmatter, Frontmatter, FrontmatterValue};
+pub use targets::{enabled_targets, find_target, RulesTarget, TargetFormat, BUILTIN_TARGETS};
+
+/// How a rule is attached to an agent's context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Activation {
+    /// The rule is always included
+    Always,
+    /// The rule is included when a file matching one of the globs is in context
+    FileMatch(Vec<String>),
+    /// The rule is only included when explicitly requested
+    Manual,
+}
+
+/// Canonical metadata of a rules file, read from its frontmatter.
+///
+/// The canonical keys follow Cursor's `.mdc` format (`description`, `globs`,
+/// `alwaysApply`). Windsurf's `trigger` key and Kiro's `inclusion` and
+/// `fileMatchPattern` keys are accepted as well so migrated rules keep their meaning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleMetadata {
+    /// Short description of the rule
+    pub description: Option<String>,
+    /// File globs the rule applies to
+    pub globs: Vec<String>,
+    /// Whether the rule is always applied
+    pub always_apply: Option<bool>,
+    /// Windsurf activation mode (`always_on`, `glob`, `manual`, `model_decision`)
+    pub trigger: Option<String>,
+    /// Kiro inclusion mode (`always`, `fileMatch`, `manual`)
+    pub inclusion: Option<String>,
+    /// Targets the rule is limited to; empty means every target
+    pub agents: Vec<String>,
+    /// Targets the rule is never synced to
+    pub exclude_agents: Vec<String>,
+    /// Whether the rule is turned off with `enabled: false`
+    pub disabled: bool,
+    /// Tags that select the rule in profiles
+    pub tags: Vec<String>,
+    /// Whether the rule had a frontmatter block at all
+    pub has_frontmatter: bool,
+}
+
+impl RuleMetadata {
+    /// Builds the metadata from a parsed frontmatter block
+    pub fn from_frontmatter(frontmatter: Option<&Frontmatter>) -> Self {
+        let frontmatter = match frontmatter {
+            Some(frontmatter) => frontmatter,
+            None => return Self::default(),
+        };
+
+        Self {
+            description: frontmatter
+                .get("description")
+                .and_then(|value| value.as_str())
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            globs: frontmatter
+                .get("globs")
+                .or_else(|| frontmatter.get("fileMatchPattern"))
+                .map(|value| value.as_list())
+                .unwrap_or_default(),
+            always_apply: frontmatter
+                .get("alwaysApply")
+                .and_then(|value| value.as_bool()),
+            trigger: frontmatter
+                .get("trigger")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+            inclusion: frontmatter
+                .get("inclusion")
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+            agents: frontmatter
+                .get("agents")
+                .map(|value| value.as_list())
+                .unwrap_or_default(),
+            exclude_agents: frontmatter
+                .get("exclude_agents")
+                .map(|value| value.as_list())
+                .unwrap_or_default(),
+            disabled: frontmatter.get("enabled").and_then(|value| value.as_bool()) == Some(false),
+            tags: frontmatter
+                .get("tags")
+                .map(|value| value.as_list())
+                .unwrap_or_default(),
+            has_frontmatter: true,
+        }
+    }
+
+    /// Checks whether the rule is synced to a target, given its `agents` and
+    /// `exclude_agents` lists. Target names are compared case-insensitively.
+    pub fn applies_to(&self, target_name: &str) -> bool {
+        let listed = |names: &[String]| {
+            names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(target_name))
+        };
+        (self.agents.is_empty() || listed(&self.agents)) && !listed(&self.exclude_agents)
+    }
+
+    /// Determines how the rule is activated.
+    ///
+    /// Rules without any activation metadata (including plain Markdown files
+    /// without frontmatter) are always applied.
+    pub fn activation(&self) -> Activation {
+        match self.trigger.as_deref() {
+            Some("always_on") => return Activation::Always,
+            Some("glob") if !self.globs.is_empty() => {
+                return Activation::FileMatch(self.globs.clone())
+            }
+            Some("manual") | Some("model_decision") => return Activation::Manual,
+            _ => {}
+        }
+        match self.inclusion.as_deref() {
+            Some("always") => return Activation::Always,
+            Some("fileMatch") if !self.globs.is_empty() => {
+                return Activation::FileMatch(self.globs.clone())
+            }
+            Some("manual") => return Activation::Manual,
+            _ => {}
+        }
+
+        if self.always_apply == Some(true) {
+            Activation::Always
+        } else if !self.globs.is_empty() {
+            Activation::FileMatch(self.globs.clone())
+        } else if self.always_apply == Some(false) || self.description.is_some() {
+            Activation::Manual
+        } else {
+            Activation::Always
+        }
+    }
+}
+
+/// A rules file with its parsed metadata and body
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Path to the rules file
+    pub path: PathBuf,
+    /// Canonical metadata from the frontmatter
+    pub metadata: RuleMetadata,
+    /// The content after the frontmatter block
+    pub body: String,
+}
+
+impl Rule {
+    /// Reads and parses a rules file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its frontmatter is invalid.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(path, &content)
+    }
+
+    /// Parses the content of a rules file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the frontmatter is invalid.
+    pub fn parse(path: &Path, content: &str) -> io::Result<Self> {
+        let (frontmatter, body) = split_frontmatter(content)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            metadata: RuleMetadata::from_frontmatter(frontmatter.as_ref()),
+            body: body.to_string(),
+        })
+    }
+}
+
+/// Checks whether the rule at a path is synced to a target.
+///
+/// Rules turned off with `enabled: false` apply to no target. Rules that cannot
+/// be read or parsed apply to every target, so they keep syncing as plain files.
+///
+/// # Arguments
+///
+/// * `rule_path` - Path to the rules file
+/// * `target_name` - Name of the target (for example `cursor`)
+pub fn rule_applies_to(rule_path: &Path, target_name: &str) -> bool {
+    Rule::load(rule_path)
+        .map(|rule| !rule.metadata.disabled && rule.metadata.applies_to(target_name))
+        .unwrap_or(true)
+}
+
+/// Checks whether a rule is listed as disabled in a project's `.known.json`.
+///
+/// # Arguments
+///
+/// * `project_dir` - Path to the project directory
+/// * `file_name` - File name of the rule's entries (for example `global-style.md`)
+pub fn is_rule_disabled(project_dir: &Path, file_name: &OsStr) -> bool {
+    load_project_config(project_dir)
+        .map(|config| config.is_rule_disabled(&file_name.to_string_lossy()))
+        .unwrap_or(false)
+}
+
+/// Checks whether a rule of a project is selected by its active profile.
+///
+/// Every rule is selected when no profile is active. Rules that cannot be read
+/// are matched by file name only.
+///
+/// # Arguments
+///
+/// * `project_dir` - Path to the project directory
+/// * `rule_path` - Path to the rules file
+pub fn is_rule_in_profile(project_dir: &Path, rule_path: &Path) -> bool {
+    let config = match load_project_config(project_dir) {
+        Ok(config) => config,
+        Err(_) => return true,
+    };
+    let profile = match config.active_profile() {
+        Some(profile) => profile,
+        None => return true,
+    };
+    let file_name = rule_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tags = Rule::load(rule_path)
+        .map(|rule| rule.metadata.tags)
+        .unwrap_or_default();
+    profile.selects(&file_name, &tags)
+}
+
+/// Lists the files in a rules directory, sorted by name.
+///
+/// Subdirectories are ignored. A missing directory yields an empty list.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn list_rule_files(rules_path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !rules_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(rules_path)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn activation_of(content: &str) -> Activation {
+        Rule::parse(Path::new("rule.md"), content)
+            .unwrap()
+            .metadata
+            .activation()
+    }
+
+    #[test]
+    fn test_activation_from_cursor_metadata() {
+        assert_eq!(activation_of("# Plain rule"), Activation::Always);
+        assert_eq!(
+            activation_of("---\nalwaysApply: true\nglobs: src/**\n---\n"),
+            Activation::Always
+        );
+        assert_eq!(
+            activation_of("---\nglobs: src/**/*.rs, tests/**\nalwaysApply: false\n---\n"),
+            Activation::FileMatch(vec!["src/**/*.rs".to_string(), "tests/**".to_string()])
+        );
+        assert_eq!(
+            activation_of("---\ndescription: Use when writing SQL\n---\n"),
+            Activation::Manual
+        );
+        assert_eq!(
+            activation_of("---\nalwaysApply: false\n---\n"),
+            Activation::Manual
+        );
+    }
+
+    #[test]
+    fn test_activation_from_windsurf_trigger() {
+        assert_eq!(
+            activation_of("---\ntrigger: always_on\n---\n"),
+            Activation::Always
+        );
+        assert_eq!(
+            activation_of("---\ntrigger: glob\nglobs: \"*.py\"\n---\n"),
+            Activation::FileMatch(vec!["*.py".to_string()])
+        );
+        assert_eq!(
+            activation_of("---\ntrigger: model_decision\ndescription: Python\n---\n"),
+            Activation::Manual
+        );
+    }
+
+    #[test]
+    fn test_activation_from_kiro_inclusion() {
+        assert_eq!(
+            activation_of("---\ninclusion: always\n---\n"),
+            Activation::Always
+        );
+        assert_eq!(
+            activation_of("---\ninclusion: fileMatch\nfileMatchPattern: \"{src/**/*.ts,src/**/*.tsx}\"\n---\n"),
+            Activation::FileMatch(vec!["{src/**/*.ts,src/**/*.tsx}".to_string()])
+        );
+        assert_eq!(
+            activation_of("---\ninclusion: manual\n---\n"),
+            Activation::Manual
+        );
+    }
+
+    #[test]
+    fn test_rule_agents_select_targets() {
+        let metadata = |content: &str| Rule::parse(Path::new("rule.md"), content).unwrap().metadata;
+
+        let plain = metadata("# Plain rule");
+        assert!(plain.applies_to("cursor") && plain.applies_to("kiro"));
+
+        let only = metadata("---\nagents: [cursor, roo]\n---\n");
+        assert!(only.applies_to("Cursor"));
+        assert!(!only.applies_to("windsurf"));
+
+        let excluded = metadata("---\nexclude_agents:\n  - windsurf\n---\n");
+        assert!(excluded.applies_to("cursor"));
+        assert!(!excluded.applies_to("windsurf"));
+
+        assert!(metadata("---\nenabled: false\n---\n").disabled);
+        assert!(!metadata("---\nenabled: true\n---\n").disabled);
+    }
+
+    #[test]
+    fn test_list_rule_files() {
+        let dir = tempdir().unwrap();
+        assert!(list_rule_files(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+
+        fs::write(dir.path().join("b.md"), "b").unwrap();
+        fs::write(dir.path().join("a.md"), "a").unwrap();
+        fs::create_dir(dir.path().join("subdir")).unwrap();
+
+        let files = list_rule_files(dir.path()).unwrap();
+        assert_eq!(
+            files,
+            vec![dir.path().join("a.md"), dir.path().join("b.md")]
+        );
+    }
+}
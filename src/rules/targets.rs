@@ -0,0 +1,438 @@
+//! Built-in targets that rules are synchronized to.
+//!
+//! A target is a tool-specific rules directory inside a project. Cursor and
+//! Windsurf are always synchronized; the other built-in targets are enabled per
+//! project through the `targets` list in `.known.json`.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::kiro::{is_generated_steering_file, render_steering_file, steering_source};
+use super::links::{forget_link, record_link};
+use super::{is_rule_disabled, is_rule_in_profile, rule_applies_to, Rule};
+use crate::config::ProjectConfig;
+use crate::constants::{
+    AMAZONQ_RULES_DIR, AMAZONQ_TARGET, CURSOR_RULES_DIR, CURSOR_TARGET, GLOBAL_RULE_PREFIX,
+    KIRO_STEERING_DIR, KIRO_TARGET, RULES_DIR, WINDSURF_RULES_DIR, WINDSURF_TARGET,
+};
+use crate::symlinks::create_symlink_to_file;
+
+/// How rules are materialized in a target directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetFormat {
+    /// Each rule is a symlink to the file in `.rules`
+    Symlink,
+    /// Each rule is rendered into a Kiro steering file with `inclusion` frontmatter
+    KiroSteering,
+}
+
+/// A tool-specific rules directory kept in sync with `.rules`
+#[derive(Debug, PartialEq, Eq)]
+pub struct RulesTarget {
+    /// Name used to enable the target in the project configuration
+    pub name: &'static str,
+    /// Directory relative to the project root
+    pub dir: &'static str,
+    /// How rules are materialized in the directory
+    pub format: TargetFormat,
+    /// Whether the target is synchronized without being listed in the project configuration
+    pub default: bool,
+}
+
+/// All built-in targets
+pub const BUILTIN_TARGETS: [RulesTarget; 4] = [
+    RulesTarget {
+        name: CURSOR_TARGET,
+        dir: CURSOR_RULES_DIR,
+        format: TargetFormat::Symlink,
+        default: true,
+    },
+    RulesTarget {
+        name: WINDSURF_TARGET,
+        dir: WINDSURF_RULES_DIR,
+        format: TargetFormat::Symlink,
+        default: true,
+    },
+    RulesTarget {
+        name: KIRO_TARGET,
+        dir: KIRO_STEERING_DIR,
+        format: TargetFormat::KiroSteering,
+        default: false,
+    },
+    RulesTarget {
+        name: AMAZONQ_TARGET,
+        dir: AMAZONQ_RULES_DIR,
+        format: TargetFormat::Symlink,
+        default: false,
+    },
+];
+
+/// Finds a built-in target by name (case-insensitive).
+pub fn find_target(name: &str) -> Option<&'static RulesTarget> {
+    BUILTIN_TARGETS
+        .iter()
+        .find(|target| target.name.eq_ignore_ascii_case(name))
+}
+
+/// Returns the targets that are synchronized for a project.
+///
+/// Default targets are always included; other built-in targets are included
+/// when listed in the project configuration.
+pub fn enabled_targets(config: &ProjectConfig) -> Vec<&'static RulesTarget> {
+    BUILTIN_TARGETS
+        .iter()
+        .filter(|target| target.default || config.has_target(target.name))
+        .collect()
+}
+
+impl RulesTarget {
+    /// Returns the target directory inside a project
+    pub fn path(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(self.dir)
+    }
+
+    /// Returns the name of the entry generated in the target directory for a rule
+    ///
+    /// Symlink targets keep the rule's file name; Kiro steering files must use
+    /// the `.md` extension, so rules that differ only in their extension, such as
+    /// `style.md` and `style.mdc`, share an entry.
+    pub fn entry_name(&self, rule_file_name: &OsStr) -> OsString {
+        match self.format {
+            TargetFormat::Symlink => rule_file_name.to_os_string(),
+            TargetFormat::KiroSteering => {
+                let mut name = Path::new(rule_file_name)
+                    .file_stem()
+                    .unwrap_or(rule_file_name)
+                    .to_os_string();
+                name.push(".md");
+                name
+            }
+        }
+    }
+
+    /// Creates or updates the entry for a rule in the target directory.
+    ///
+    /// If the project has an active profile that doesn't select the rule, its
+    /// entry is removed instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_dir` - Path to the project directory
+    /// * `rule_path` - Path to the rule file in `.rules`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target directory cannot be created, the rule
+    /// cannot be read, or the entry cannot be written.
+    pub fn sync_rule(&self, project_dir: &Path, rule_path: &Path) -> io::Result<()> {
+        let file_name = match rule_path.file_name() {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        if !is_rule_in_profile(project_dir, rule_path) {
+            return self.remove_managed_entry(project_dir, file_name);
+        }
+        // Rules from .rules.local or an inherited .rules are not in the project's .rules
+        let source = match rule_path.strip_prefix(project_dir) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => format!("{}/{}", RULES_DIR, file_name.to_string_lossy()),
+        };
+        self.sync_rule_as(project_dir, rule_path, file_name, &source)
+    }
+
+    /// Creates or updates the entry for a rule under a different file name.
+    ///
+    /// Used for rules from outside the project's `.rules`, such as global rules,
+    /// whose entries carry a prefix. If the rule is disabled, or its `agents` or
+    /// `exclude_agents` frontmatter leaves this target out, its entry is removed
+    /// instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_dir` - Path to the project directory
+    /// * `rule_path` - Path to the rule file
+    /// * `file_name` - File name the entry is derived from
+    /// * `source` - Where the rule comes from, noted in rendered entries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target directory cannot be created, the rule
+    /// cannot be read, or the entry cannot be written.
+    pub fn sync_rule_as(
+        &self,
+        project_dir: &Path,
+        rule_path: &Path,
+        file_name: &OsStr,
+        source: &str,
+    ) -> io::Result<()> {
+        if !rule_applies_to(rule_path, self.name) || is_rule_disabled(project_dir, file_name) {
+            return self.remove_managed_entry(project_dir, file_name);
+        }
+        let target_dir = self.path(project_dir);
+        fs::create_dir_all(&target_dir)?;
+        let entry_path = target_dir.join(self.entry_name(file_name));
+
+        match self.format {
+            TargetFormat::Symlink => {
+                create_symlink_to_file(rule_path, &entry_path)?;
+                record_link(project_dir, &entry_path, rule_path)
+            }
+            TargetFormat::KiroSteering => {
+                if entry_path.exists() && !self.is_managed_entry(&entry_path) {
+                    eprintln!(
+                        "Warning: {} was not generated by known. Skipping.",
+                        entry_path.display()
+                    );
+                    return Ok(());
+                }
+                let owner = kiro_entry_owner(rule_path);
+                if owner != rule_path {
+                    eprintln!(
+                        "Warning: {} and {} would both be written to {}. Skipping {}.",
+                        owner.display(),
+                        rule_path.display(),
+                        entry_path.display(),
+                        rule_path.display()
+                    );
+                    return Ok(());
+                }
+                let rule = Rule::load(rule_path)?;
+                let rendered = render_steering_file(&rule, source);
+                if fs::read_to_string(&entry_path).ok().as_deref() != Some(rendered.as_str()) {
+                    fs::write(&entry_path, rendered)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the entry for a rule from the target directory, if it exists.
+    ///
+    /// Kiro steering files are only removed if they were generated by known.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if file removal fails
+    pub fn remove_rule(&self, project_dir: &Path, rule_file_name: &OsStr) -> io::Result<()> {
+        let entry_path = self.path(project_dir).join(self.entry_name(rule_file_name));
+
+        // symlink_metadata also catches symlinks whose source is already gone
+        if fs::symlink_metadata(&entry_path).is_err() {
+            return Ok(());
+        }
+        if self.format == TargetFormat::KiroSteering
+            && !self.is_entry_from(&entry_path, rule_file_name)
+        {
+            return Ok(());
+        }
+        fs::remove_file(&entry_path)?;
+        forget_link(project_dir, &entry_path)
+    }
+
+    /// Removes the entry for a rule if it is managed by known.
+    fn remove_managed_entry(&self, project_dir: &Path, file_name: &OsStr) -> io::Result<()> {
+        let entry_path = self.path(project_dir).join(self.entry_name(file_name));
+        if self.is_managed_entry(&entry_path) && self.is_entry_from(&entry_path, file_name) {
+            fs::remove_file(&entry_path)?;
+            forget_link(project_dir, &entry_path)?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether a managed entry was generated from the rule with the given
+    /// file name, rather than from another rule that maps to the same entry.
+    fn is_entry_from(&self, entry_path: &Path, file_name: &OsStr) -> bool {
+        if self.format != TargetFormat::KiroSteering {
+            return true;
+        }
+        let content = match fs::read_to_string(entry_path) {
+            Ok(content) if is_generated_steering_file(&content) => content,
+            _ => return false,
+        };
+        let source_name = match steering_source(&content).and_then(|s| Path::new(s).file_name()) {
+            Some(name) => name.to_string_lossy().to_string(),
+            // Entries without a readable source are treated as this rule's
+            None => return true,
+        };
+        let file_name = file_name.to_string_lossy();
+        source_name == file_name
+            || file_name.strip_prefix(GLOBAL_RULE_PREFIX) == Some(source_name.as_str())
+    }
+
+    /// Checks whether an entry in the target directory is managed by known.
+    pub fn is_managed_entry(&self, entry_path: &Path) -> bool {
+        match self.format {
+            TargetFormat::Symlink => fs::symlink_metadata(entry_path)
+                .map(|metadata| metadata.file_type().is_symlink())
+                .unwrap_or(false),
+            TargetFormat::KiroSteering => fs::read_to_string(entry_path)
+                .map(|content| is_generated_steering_file(&content))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Returns the rule that gets the Kiro steering entry shared by rules that
+/// differ only in their extension.
+///
+/// A `.md` rule keeps its name in Kiro, so it takes the entry; otherwise the
+/// first rule by file name does. The others are skipped with a warning.
+fn kiro_entry_owner(rule_path: &Path) -> PathBuf {
+    let (dir, stem) = match (rule_path.parent(), rule_path.file_stem()) {
+        (Some(dir), Some(stem)) => (dir, stem),
+        _ => return rule_path.to_path_buf(),
+    };
+    let mut candidates: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.file_stem() == Some(stem) && path.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort_by_key(|path| (path.extension() != Some(OsStr::new("md")), path.clone()));
+    candidates
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| rule_path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_enabled_targets() {
+        let mut config = ProjectConfig::new();
+        let names: Vec<_> = enabled_targets(&config).iter().map(|t| t.name).collect();
+        assert_eq!(names, vec![CURSOR_TARGET, WINDSURF_TARGET]);
+
+        config.enable_target(KIRO_TARGET);
+        config.enable_target(AMAZONQ_TARGET);
+        let names: Vec<_> = enabled_targets(&config).iter().map(|t| t.name).collect();
+        assert_eq!(
+            names,
+            vec![CURSOR_TARGET, WINDSURF_TARGET, KIRO_TARGET, AMAZONQ_TARGET]
+        );
+        assert_eq!(find_target("AmazonQ").unwrap().dir, AMAZONQ_RULES_DIR);
+    }
+
+    #[test]
+    fn test_kiro_target_sync_and_remove() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        let rule_path = rules_path.join("typescript.mdc");
+        fs::write(&rule_path, "---\nglobs: \"*.ts\"\n---\nUse strict mode.\n").unwrap();
+
+        let kiro = find_target(KIRO_TARGET).unwrap();
+        kiro.sync_rule(dir.path(), &rule_path).unwrap();
+
+        let steering_path = dir.path().join(KIRO_STEERING_DIR).join("typescript.md");
+        let content = fs::read_to_string(&steering_path).unwrap();
+        assert!(content.starts_with("---\ninclusion: fileMatch\nfileMatchPattern: \"*.ts\"\n"));
+        assert!(content.ends_with("Use strict mode.\n"));
+
+        kiro.remove_rule(dir.path(), OsStr::new("typescript.mdc"))
+            .unwrap();
+        assert!(!steering_path.exists());
+    }
+
+    #[test]
+    fn test_kiro_target_skips_colliding_rules() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        let md_path = rules_path.join("style.md");
+        let mdc_path = rules_path.join("style.mdc");
+        fs::write(&md_path, "Use tabs.\n").unwrap();
+        fs::write(&mdc_path, "Use spaces.\n").unwrap();
+
+        // The .md rule gets style.md whichever rule is synced last
+        let kiro = find_target(KIRO_TARGET).unwrap();
+        kiro.sync_rule(dir.path(), &md_path).unwrap();
+        kiro.sync_rule(dir.path(), &mdc_path).unwrap();
+        let steering_path = dir.path().join(KIRO_STEERING_DIR).join("style.md");
+        let content = fs::read_to_string(&steering_path).unwrap();
+        assert!(content.contains("from .rules/style.md."));
+        assert!(content.ends_with("Use tabs.\n"));
+
+        // Removing the skipped rule leaves the other rule's entry alone
+        kiro.remove_rule(dir.path(), OsStr::new("style.mdc"))
+            .unwrap();
+        assert!(steering_path.is_file());
+        kiro.remove_rule(dir.path(), OsStr::new("style.md"))
+            .unwrap();
+        assert!(!steering_path.exists());
+    }
+
+    #[test]
+    fn test_kiro_target_leaves_user_steering_files() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        let rule_path = rules_path.join("product.md");
+        fs::write(&rule_path, "From known").unwrap();
+
+        let steering_dir = dir.path().join(KIRO_STEERING_DIR);
+        fs::create_dir_all(&steering_dir).unwrap();
+        fs::write(steering_dir.join("product.md"), "Hand-written").unwrap();
+
+        let kiro = find_target(KIRO_TARGET).unwrap();
+        kiro.sync_rule(dir.path(), &rule_path).unwrap();
+        kiro.remove_rule(dir.path(), OsStr::new("product.md"))
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(steering_dir.join("product.md")).unwrap(),
+            "Hand-written"
+        );
+    }
+
+    #[test]
+    fn test_sync_rule_respects_agents_frontmatter() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        let rule_path = rules_path.join("composer.mdc");
+        fs::write(&rule_path, "Composer tips").unwrap();
+
+        let cursor = find_target(CURSOR_TARGET).unwrap();
+        let windsurf = find_target(WINDSURF_TARGET).unwrap();
+        cursor.sync_rule(dir.path(), &rule_path).unwrap();
+        windsurf.sync_rule(dir.path(), &rule_path).unwrap();
+        let windsurf_link = dir.path().join(WINDSURF_RULES_DIR).join("composer.mdc");
+        assert!(windsurf.is_managed_entry(&windsurf_link));
+
+        // Limiting the rule to Cursor removes the Windsurf link on the next sync
+        fs::write(&rule_path, "---\nagents: [cursor]\n---\nComposer tips").unwrap();
+        cursor.sync_rule(dir.path(), &rule_path).unwrap();
+        windsurf.sync_rule(dir.path(), &rule_path).unwrap();
+        assert!(cursor.is_managed_entry(&dir.path().join(CURSOR_RULES_DIR).join("composer.mdc")));
+        assert!(fs::symlink_metadata(&windsurf_link).is_err());
+    }
+
+    #[test]
+    fn test_symlink_target_removes_dangling_link() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        let rule_path = rules_path.join("rule.md");
+        fs::write(&rule_path, "content").unwrap();
+
+        let amazonq = find_target(AMAZONQ_TARGET).unwrap();
+        amazonq.sync_rule(dir.path(), &rule_path).unwrap();
+        let link_path = dir.path().join(AMAZONQ_RULES_DIR).join("rule.md");
+        assert!(amazonq.is_managed_entry(&link_path));
+
+        fs::remove_file(&rule_path).unwrap();
+        amazonq
+            .remove_rule(dir.path(), OsStr::new("rule.md"))
+            .unwrap();
+        assert!(fs::symlink_metadata(&link_path).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 //! Single instance enforcement functionality for daemons.
-//! 
+//!
 //! This module provides functionality to ensure only one instance of a daemon
 //! process can run at a time using PID files and file locking.
 
@@ -9,7 +9,7 @@ mod process;
 mod stop;
 
 pub use lock::SingleInstanceLock;
-pub use stop::stop_daemon;
+pub use stop::{stop_daemon, stop_daemon_and_wait};
 
 use path::get_system_wide_lock_path;
 use process::is_process_running;
@@ -51,10 +51,10 @@ mod tests {
     use super::process::is_process_running;
     use super::stop::stop_daemon_with_test_path;
     use std::io::{self, Read, Write};
+    use std::path::Path;
     use std::thread;
     use std::time::Duration;
     use tempfile::tempdir;
-    use std::path::Path;
 
     fn is_daemon_running_with_path<P: AsRef<Path>>(path: P) -> io::Result<bool> {
         if !path.as_ref().exists() {
@@ -198,11 +198,8 @@ mod tests {
         }
 
         // Clean up the successful lock
-        for result in results {
-            if let Ok(lock) = result {
-                drop(lock);
-                break;
-            }
+        if let Some(lock) = results.into_iter().flatten().next() {
+            drop(lock);
         }
     }
 
@@ -352,8 +349,8 @@ mod tests {
         let lock_path = readonly_dir.join("test.pid");
         let result = SingleInstanceLock::acquire_with_test_path(&lock_path);
 
-        if result.is_err() {
-            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        if let Err(e) = result {
+            assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
         }
     }
 
@@ -22,10 +22,7 @@ pub(crate) const PID_FILE_NAME: &str = "known_daemon.pid";
 /// or if directory creation fails.
 pub(crate) fn get_system_wide_lock_path() -> io::Result<std::path::PathBuf> {
     let project_dirs = ProjectDirs::from("", "", "known").ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            "Unable to determine application directories for this platform",
-        )
+        io::Error::other("Unable to determine application directories for this platform")
     })?;
 
     let data_dir = project_dirs.data_dir();
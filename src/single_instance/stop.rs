@@ -5,6 +5,11 @@ use crate::single_instance::process::is_process_running;
 use nix::sys::signal::{kill, Signal};
 use nix::unistd::Pid;
 use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often [`stop_daemon_and_wait`] checks whether the daemon has exited
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Attempts to stop the running daemon process by reading the PID from the lock file
 /// and sending a SIGTERM signal.
@@ -33,6 +38,47 @@ pub fn stop_daemon() -> io::Result<()> {
     stop_daemon_with_path(pid_file_path)
 }
 
+/// Stops the running daemon, if there is one, and waits until its process has exited.
+///
+/// Unlike [`stop_daemon`], which only sends the signal, this returns once the
+/// daemon no longer watches any directory, so callers can change managed files
+/// without the daemon reacting to them.
+///
+/// # Arguments
+///
+/// * `timeout` - How long to wait for the daemon to exit
+///
+/// # Returns
+///
+/// Returns `true` if a daemon was stopped, or `false` if none was running.
+///
+/// # Errors
+///
+/// Returns a `TimedOut` error if the daemon is still running after `timeout`,
+/// or an error if it cannot be signalled.
+pub fn stop_daemon_and_wait(timeout: Duration) -> io::Result<bool> {
+    if !crate::single_instance::is_daemon_running()? {
+        return Ok(false);
+    }
+    match stop_daemon() {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    }
+
+    let start = Instant::now();
+    while crate::single_instance::is_daemon_running()? {
+        if start.elapsed() > timeout {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("The daemon did not stop within {:?}", timeout),
+            ));
+        }
+        thread::sleep(STOP_POLL_INTERVAL);
+    }
+    Ok(true)
+}
+
 /// Stops a daemon process using a custom PID file path (for testing).
 #[cfg(test)]
 pub fn stop_daemon_with_test_path<P: AsRef<std::path::Path>>(pid_file_path: P) -> io::Result<()> {
@@ -0,0 +1,368 @@
+//! Health checks for a project's instruction files, reported by `known status`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::claude::{check_claude_import, ImportState};
+use crate::conditional::rendered_alias_content;
+use crate::config::{load_project_config, ClaudeStrategy, Config, GeminiStrategy, ProjectConfig};
+use crate::constants::{
+    AGENTS_FILENAME, CLAUDE_FILENAME, GEMINI_FILENAME, PROJECT_CONFIG_FILENAME, RULES_DIR,
+};
+use crate::gemini::gemini_uses_agents_file;
+use crate::repair::{alias_backups, pending_merges};
+use crate::rules::{enabled_targets, is_rule_in_profile, list_rule_files, rule_applies_to, Rule};
+
+/// The result of a single status check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusCheck {
+    /// What was checked (for example `CLAUDE.md`)
+    pub name: String,
+    /// Whether the check passed
+    pub healthy: bool,
+    /// Human-readable explanation of the result
+    pub detail: String,
+}
+
+impl StatusCheck {
+    fn new(name: impl Into<String>, healthy: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            healthy,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Checks the instruction files, aliases and rules targets of a project.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `config` - The system-wide configuration, used to check if the project is watched
+///
+/// # Errors
+///
+/// Returns an error if the project configuration is malformed or the project
+/// files cannot be read.
+pub fn project_status(dir: &Path, config: &Config) -> io::Result<Vec<StatusCheck>> {
+    let project_config = load_project_config(dir)?;
+    let mut checks = Vec::new();
+
+    let agents_present = dir.join(AGENTS_FILENAME).is_file();
+    checks.push(StatusCheck::new(
+        AGENTS_FILENAME,
+        agents_present,
+        if agents_present {
+            "present"
+        } else {
+            "missing; run 'known init'"
+        },
+    ));
+
+    checks.push(match project_config.aliases.claude {
+        ClaudeStrategy::Symlink => check_alias_symlink(dir, CLAUDE_FILENAME),
+        ClaudeStrategy::Import => {
+            let (healthy, detail) = match check_claude_import(dir)? {
+                ImportState::Intact => (true, "imports AGENTS.md"),
+                ImportState::Missing => (false, "missing; run 'known symlink'"),
+                ImportState::Symlink => (
+                    false,
+                    "is a symlink but the import strategy is selected; run 'known symlink'",
+                ),
+                ImportState::NoManagedSection => (
+                    false,
+                    "doesn't import AGENTS.md; run 'known symlink' to add the import",
+                ),
+                ImportState::Modified => (
+                    false,
+                    "the section managed by known was edited; run 'known symlink' to repair it",
+                ),
+            };
+            StatusCheck::new(format!("{} (import)", CLAUDE_FILENAME), healthy, detail)
+        }
+    });
+
+    checks.push(match project_config.aliases.gemini {
+        GeminiStrategy::Symlink => check_alias_symlink(dir, GEMINI_FILENAME),
+        GeminiStrategy::Settings => {
+            let healthy = gemini_uses_agents_file(dir);
+            StatusCheck::new(
+                format!("{} (settings)", GEMINI_FILENAME),
+                healthy,
+                if healthy {
+                    ".gemini/settings.json uses AGENTS.md"
+                } else {
+                    ".gemini/settings.json doesn't use AGENTS.md; run 'known symlink'"
+                },
+            )
+        }
+    });
+
+    checks.extend(alias_repairs(dir));
+
+    let rule_files = list_rule_files(&dir.join(RULES_DIR))?;
+    for target in enabled_targets(&project_config) {
+        let rule_files: Vec<_> = rule_files
+            .iter()
+            .filter(|path| rule_applies_to(path, target.name) && is_rule_in_profile(dir, path))
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .is_some_and(|name| project_config.is_rule_disabled(&name.to_string_lossy()))
+            })
+            .collect();
+        let missing = rule_files
+            .iter()
+            .filter_map(|path| path.file_name())
+            .filter(|name| {
+                let entry = target.path(dir).join(target.entry_name(name));
+                !target.is_managed_entry(&entry)
+            })
+            .count();
+        checks.push(StatusCheck::new(
+            target.dir,
+            missing == 0,
+            if missing == 0 {
+                format!("{} rule(s) in sync", rule_files.len())
+            } else {
+                format!(
+                    "{} of {} rule(s) not synchronized; is the daemon running?",
+                    missing,
+                    rule_files.len()
+                )
+            },
+        ));
+    }
+
+    if let Some(profile) = &project_config.profile {
+        let defined = project_config.profiles.contains_key(profile);
+        let selected = rule_files
+            .iter()
+            .filter(|path| is_rule_in_profile(dir, path))
+            .count();
+        checks.push(StatusCheck::new(
+            "profile",
+            defined,
+            if defined {
+                format!(
+                    "{} ({} of {} rule(s) active)",
+                    profile,
+                    selected,
+                    rule_files.len()
+                )
+            } else {
+                format!(
+                    "'{}' is not defined in {}; every rule is synced",
+                    profile, PROJECT_CONFIG_FILENAME
+                )
+            },
+        ));
+    }
+
+    let disabled = disabled_rules(&project_config, &rule_files);
+    if !disabled.is_empty() {
+        checks.push(StatusCheck::new(
+            "disabled rules",
+            true,
+            disabled.join(", "),
+        ));
+    }
+
+    let watched = config.contains_directory(dir);
+    checks.push(StatusCheck::new(
+        "daemon",
+        watched,
+        if watched {
+            "directory is watched"
+        } else {
+            "directory is not watched; run 'known add'"
+        },
+    ));
+
+    Ok(checks)
+}
+
+/// Reports the aliases the daemon repaired after a tool replaced them with a regular file.
+///
+/// A repair is a problem while the content it merged into AGENTS.md still awaits review.
+fn alias_repairs(dir: &Path) -> Vec<StatusCheck> {
+    let agents_content = fs::read_to_string(dir.join(AGENTS_FILENAME)).unwrap_or_default();
+    let mut checks = Vec::new();
+    for alias in [CLAUDE_FILENAME, GEMINI_FILENAME] {
+        let backups = alias_backups(dir, alias);
+        let latest = match backups.last() {
+            Some(latest) => latest.strip_prefix(dir).unwrap_or(latest).display(),
+            None => continue,
+        };
+        let pending = pending_merges(&agents_content, alias);
+        checks.push(StatusCheck::new(
+            format!("{} repairs", alias),
+            pending == 0,
+            if pending == 0 {
+                format!(
+                    "replaced by a regular file {} time(s) and restored; latest content preserved in {}",
+                    backups.len(),
+                    latest
+                )
+            } else {
+                format!(
+                    "{} block(s) merged into {} await review; latest content preserved in {}",
+                    pending, AGENTS_FILENAME, latest
+                )
+            },
+        ));
+    }
+    checks
+}
+
+/// Lists the rules that are not synced to any target, and where they were disabled.
+fn disabled_rules(project_config: &ProjectConfig, rule_files: &[PathBuf]) -> Vec<String> {
+    let mut disabled: Vec<String> = project_config
+        .disabled
+        .iter()
+        .map(|name| format!("{} ({})", name, PROJECT_CONFIG_FILENAME))
+        .collect();
+    for path in rule_files {
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => continue,
+        };
+        if !project_config.is_rule_disabled(&name)
+            && Rule::load(path).is_ok_and(|rule| rule.metadata.disabled)
+        {
+            disabled.push(format!("{} (enabled: false)", name));
+        }
+    }
+    disabled.sort();
+    disabled
+}
+
+/// Checks that an alias is a symlink resolving to the project's AGENTS.md.
+///
+/// When AGENTS.md has conditional sections, the alias must be an up-to-date copy
+/// rendered for its agent instead.
+fn check_alias_symlink(dir: &Path, alias: &str) -> StatusCheck {
+    let alias_path = dir.join(alias);
+    match rendered_alias_content(dir, alias) {
+        Ok(Some(rendered)) => {
+            let healthy = fs::read_to_string(&alias_path).is_ok_and(|content| content == rendered);
+            return StatusCheck::new(
+                format!("{} (rendered)", alias),
+                healthy,
+                if healthy {
+                    "rendered from AGENTS.md"
+                } else {
+                    "out of date with AGENTS.md; run 'known symlink'"
+                },
+            );
+        }
+        Ok(None) => {}
+        Err(e) => return StatusCheck::new(alias, false, e.to_string()),
+    }
+    let (healthy, detail) = match fs::symlink_metadata(&alias_path) {
+        Err(_) => (false, "missing; run 'known symlink'"),
+        Ok(metadata) if !metadata.file_type().is_symlink() => {
+            (false, "is a regular file, not a symlink to AGENTS.md")
+        }
+        Ok(_) => {
+            let resolved = alias_path.canonicalize().ok();
+            if resolved.is_some() && resolved == dir.join(AGENTS_FILENAME).canonicalize().ok() {
+                (true, "symlink to AGENTS.md")
+            } else {
+                (false, "symlink doesn't point to AGENTS.md")
+            }
+        }
+    };
+    StatusCheck::new(alias, healthy, detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{save_project_config, ProjectConfig};
+    use crate::symlinks::create_symlinks_in_dir;
+    use tempfile::tempdir;
+
+    fn check<'a>(checks: &'a [StatusCheck], name: &str) -> &'a StatusCheck {
+        checks.iter().find(|check| check.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_status_of_uninitialized_project() {
+        let dir = tempdir().unwrap();
+        let checks = project_status(dir.path(), &Config::new()).unwrap();
+
+        assert!(!check(&checks, AGENTS_FILENAME).healthy);
+        assert!(!check(&checks, CLAUDE_FILENAME).healthy);
+        assert!(!check(&checks, "daemon").healthy);
+    }
+
+    #[test]
+    fn test_status_detects_broken_import_pointer() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents").unwrap();
+        let mut project_config = ProjectConfig::new();
+        project_config.aliases.claude = ClaudeStrategy::Import;
+        save_project_config(dir.path(), &project_config).unwrap();
+        create_symlinks_in_dir(dir.path()).unwrap();
+
+        let mut config = Config::new();
+        config.add_directory(dir.path());
+        let checks = project_status(dir.path(), &config).unwrap();
+        assert!(checks.iter().all(|check| check.healthy), "{:?}", checks);
+
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        let content = fs::read_to_string(&claude_path).unwrap();
+        fs::write(&claude_path, content.replace("@AGENTS.md", "")).unwrap();
+
+        let checks = project_status(dir.path(), &config).unwrap();
+        assert!(!check(&checks, "CLAUDE.md (import)").healthy);
+    }
+
+    #[test]
+    fn test_status_reports_alias_repairs() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(AGENTS_FILENAME), "# Agents\n").unwrap();
+        create_symlinks_in_dir(dir.path()).unwrap();
+        let checks = project_status(dir.path(), &Config::new()).unwrap();
+        assert!(!checks.iter().any(|check| check.name == "CLAUDE.md repairs"));
+
+        // A tool replaces the alias and the daemon repairs it
+        let claude_path = dir.path().join(CLAUDE_FILENAME);
+        fs::remove_file(&claude_path).unwrap();
+        fs::write(&claude_path, "# Agents\nRun tests.\n").unwrap();
+        crate::repair::repair_alias(dir.path(), CLAUDE_FILENAME).unwrap();
+
+        let checks = project_status(dir.path(), &Config::new()).unwrap();
+        let repairs = check(&checks, "CLAUDE.md repairs");
+        assert!(!repairs.healthy);
+        assert!(repairs.detail.contains("await review"));
+        assert!(repairs.detail.contains(".known/backups/repairs/CLAUDE.md."));
+    }
+
+    #[test]
+    fn test_status_lists_disabled_rules() {
+        let dir = tempdir().unwrap();
+        let rules_path = dir.path().join(RULES_DIR);
+        fs::create_dir(&rules_path).unwrap();
+        fs::write(
+            rules_path.join("draft.md"),
+            "---\nenabled: false\n---\nDraft",
+        )
+        .unwrap();
+        fs::write(rules_path.join("style.md"), "Use tabs.").unwrap();
+        let mut project_config = ProjectConfig::new();
+        project_config.set_rule_disabled("style.md", true);
+        save_project_config(dir.path(), &project_config).unwrap();
+
+        let checks = project_status(dir.path(), &Config::new()).unwrap();
+        assert_eq!(
+            check(&checks, "disabled rules").detail,
+            "draft.md (enabled: false), style.md (.known.json)"
+        );
+        // Disabled rules are not expected in the targets
+        assert!(check(&checks, crate::constants::CURSOR_RULES_DIR).healthy);
+    }
+}
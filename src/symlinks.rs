@@ -2,10 +2,31 @@
 
 use std::fs;
 use std::io;
-use std::path::Path;
-
-use crate::config::add_directory_to_config;
-use crate::constants::{CURSOR_RULES_DIR, RULES_DIR, WINDSURF_RULES_DIR};
+use std::path::{Path, PathBuf};
+
+use crate::aider::sync_aider_config_if_enabled;
+use crate::claude::{remove_claude_import, sync_claude_import};
+use crate::conditional::{has_conditional_sections, sync_symlinked_alias};
+use crate::config::{
+    load_project_config, save_project_config, AliasConfig, ClaudeStrategy, GeminiStrategy,
+};
+use crate::constants::{
+    AGENTS_FILENAME, AGENTS_LOCAL_FILENAME, AIDER_CONFIG_FILENAME, CLAUDE_FILENAME,
+    CLAUDE_LOCAL_FILENAME, GEMINI_FILENAME, GEMINI_SETTINGS_PATH, PROJECT_CONFIG_FILENAME,
+    RULES_DIR, RULES_LOCAL_DIR,
+};
+use crate::gemini::{
+    set_gemini_context_file, set_gemini_local_context_file, unset_gemini_context_file,
+};
+use crate::git::{exclude_file_path, update_exclude_section};
+use crate::migration::{
+    apply_rules_migration, plan_rules_migration, ConflictPolicy, MigrationAction, MigrationReport,
+};
+use crate::monorepo::discover_packages;
+use crate::transaction::Transaction;
+
+/// Name of the section of `.git/info/exclude` that excludes the local override layer
+pub(crate) const LOCAL_LAYER_EXCLUDE_SECTION: &str = "local layer";
 
 /// Creates symbolic links from AGENTS.md to CLAUDE.md and GEMINI.md in the current working directory.
 ///
@@ -30,9 +51,28 @@ pub fn create_symlinks() -> io::Result<()> {
     create_symlinks_in_dir(&current_dir)
 }
 
+/// Options for [`create_symlinks_with_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SymlinkOptions {
+    /// What to do when a migrated rule conflicts with a file in .rules
+    pub conflict_policy: ConflictPolicy,
+    /// Only plan the rules migration without changing anything
+    pub dry_run: bool,
+    /// Claude strategy to save to .known.json before linking
+    pub claude: Option<ClaudeStrategy>,
+    /// Gemini strategy to save to .known.json before linking
+    pub gemini: Option<GeminiStrategy>,
+    /// Turn on recursive mode for nested packages and save it to .known.json
+    pub recursive: bool,
+    /// Turn on rule inheritance (and recursive mode) and save it to .known.json
+    pub inherit: bool,
+}
+
 /// Creates symbolic links from AGENTS.md to CLAUDE.md and GEMINI.md in the specified directory.
 ///
-/// This is the core function that handles symlink creation logic.
+/// This is the core function that handles symlink creation logic. Rules that
+/// conflict with a file in .rules are skipped with a warning; use
+/// [`create_symlinks_with_options`] to choose another conflict policy.
 ///
 /// # Arguments
 ///
@@ -42,17 +82,67 @@ pub fn create_symlinks() -> io::Result<()> {
 ///
 /// - Verifies that AGENTS.md exists in the target directory
 /// - Creates symlinks from CLAUDE.md and GEMINI.md to AGENTS.md
-/// - Moves files from .cursor/rules and .windsurf/rules to .rules directory
+/// - With the `import` Claude strategy, maintains a CLAUDE.md that imports AGENTS.md
+///   with `@AGENTS.md`, preserving any notes outside the section managed by known
+/// - With the `settings` Gemini strategy, sets `contextFileName` in .gemini/settings.json
+///   instead of creating GEMINI.md; the setting is removed again with the `symlink` strategy
+/// - Moves files from .cursor/rules, .windsurf/rules, .amazonq/rules and .kiro/steering to .rules directory
+/// - Updates the managed `read:` section of .aider.conf.yml if the Aider target is enabled
+/// - In recursive mode, does the same for every nested package with its own AGENTS.md
+///   (skipping directories excluded by .gitignore), following the package's .known.json
 /// - Uses platform-specific symlink functions for cross-platform compatibility
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - AGENTS.md doesn't exist in the target directory
+/// - The project configuration or .gemini/settings.json is malformed
+/// - Switching CLAUDE.md back to a symlink would discard notes outside the managed section
 /// - Symlink creation fails
 /// - File moving fails
 ///
 pub fn create_symlinks_in_dir<P: AsRef<Path>>(dir: P) -> io::Result<()> {
+    let report = create_symlinks_with_options(dir, &SymlinkOptions::default())?;
+    for decision in report.with_action(MigrationAction::Skip) {
+        println!(
+            "Warning: File '{}' already exists in .rules directory. Skipping.",
+            decision
+                .source
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        );
+    }
+    Ok(())
+}
+
+/// Creates the AGENTS.md aliases and migrates rules with the given options.
+///
+/// Behaves like [`create_symlinks_in_dir`], but resolves rules conflicts with
+/// `options.conflict_policy` and saves the alias strategies, recursive mode and
+/// inheritance given in the options to .known.json first. With `options.dry_run`,
+/// only the rules migration is planned and nothing is changed.
+///
+/// The run is journaled in `.known/`: if any step fails, every change made so far
+/// is rolled back, and a successful run can be reverted with `known undo`.
+///
+/// # Arguments
+///
+/// * `dir` - The directory path where the symlinks should be created
+/// * `options` - Conflict policy, dry-run flag, alias strategies, recursive mode and inheritance
+///
+/// # Returns
+///
+/// Returns the report of the rules migration, describing every decision made.
+///
+/// # Errors
+///
+/// Returns the same errors as [`create_symlinks_in_dir`], and an `AlreadyExists`
+/// error if the conflict policy is `fail` and a rule conflicts with a file in .rules.
+pub fn create_symlinks_with_options<P: AsRef<Path>>(
+    dir: P,
+    options: &SymlinkOptions,
+) -> io::Result<MigrationReport> {
     let dir = dir.as_ref();
     let agents_path = dir.join("AGENTS.md");
 
@@ -64,124 +154,279 @@ pub fn create_symlinks_in_dir<P: AsRef<Path>>(dir: P) -> io::Result<()> {
         ));
     }
 
-    // Create .rules directory if it doesn't exist
-    ensure_rules_directory_exists(dir)?;
-    let rules_path = dir.join(RULES_DIR);
+    let mut project_config = load_project_config(dir)?;
+    let inherit = options.inherit || project_config.inherit;
+    let recursive = options.recursive || inherit || project_config.recursive;
+    let packages = if recursive {
+        discover_packages(dir)?
+    } else {
+        Vec::new()
+    };
+
+    // Decide what happens to every file in the tool-specific rules directories
+    let mut report = plan_rules_migration(dir, options.conflict_policy)?;
+    for package in &packages {
+        let package_report = plan_rules_migration(package, options.conflict_policy)?;
+        report.decisions.extend(package_report.decisions);
+    }
+    if options.dry_run {
+        return Ok(report);
+    }
 
-    // Move files from .cursor/rules to .rules directory
-    let cursor_rules_path = dir.join(CURSOR_RULES_DIR);
-    move_files_to_rules_dir(&cursor_rules_path, &rules_path)?;
+    // Every change below is journaled and rolled back if a later step fails
+    Transaction::run(dir, "symlink", |tx| {
+        // Save strategies chosen on the command line to .known.json
+        let mut aliases = project_config.aliases.clone();
+        if let Some(strategy) = options.claude {
+            aliases.claude = strategy;
+        }
+        if let Some(strategy) = options.gemini {
+            aliases.gemini = strategy;
+        }
+        if aliases != project_config.aliases
+            || recursive != project_config.recursive
+            || inherit != project_config.inherit
+        {
+            project_config.aliases = aliases;
+            project_config.recursive = recursive;
+            project_config.inherit = inherit;
+            tx.snapshot(&dir.join(PROJECT_CONFIG_FILENAME))?;
+            save_project_config(dir, &project_config)?;
+        }
 
-    // Move files from .windsurf/rules to .rules directory
-    let windsurf_rules_path = dir.join(WINDSURF_RULES_DIR);
-    move_files_to_rules_dir(&windsurf_rules_path, &rules_path)?;
+        // Create .rules directories if they don't exist
+        tx.create_dir(&dir.join(RULES_DIR))?;
+        for package in &packages {
+            tx.create_dir(&package.join(RULES_DIR))?;
+        }
 
-    let claude_path = dir.join("CLAUDE.md");
-    let gemini_path = dir.join("GEMINI.md");
+        // Move files from the tool-specific rules directories to .rules directories
+        apply_rules_migration(&report, tx)?;
 
-    // Remove existing symlinks if they exist
-    remove_existing_symlinks(&claude_path, &gemini_path)?;
+        for path in alias_paths(dir) {
+            tx.snapshot(&path)?;
+        }
+        link_aliases(dir, &project_config.aliases)?;
 
-    // Create symlinks using platform-specific functions
-    let agents_symlink_target = Path::new("AGENTS.md");
-    create_platform_symlink(agents_symlink_target, &claude_path)?;
-    create_platform_symlink(agents_symlink_target, &gemini_path)?;
+        // Keep the local layer out of Git
+        if let Some(exclude_path) = exclude_file_path(dir).filter(|path| path.starts_with(dir)) {
+            tx.snapshot(&exclude_path)?;
+        }
+        exclude_local_layer(dir)?;
+
+        // Keep the Aider configuration in sync when the target is enabled
+        tx.snapshot(&dir.join(AIDER_CONFIG_FILENAME))?;
+        sync_aider_config_if_enabled(dir)?;
+
+        // Nested packages follow their own .known.json
+        for package in &packages {
+            for path in alias_paths(package) {
+                tx.snapshot(&path)?;
+            }
+            tx.snapshot(&package.join(AIDER_CONFIG_FILENAME))?;
+            link_aliases(package, &load_project_config(package)?.aliases)?;
+            sync_aider_config_if_enabled(package)?;
+        }
 
-    // Add directory to configuration file for daemon tracking
-    if let Err(e) = add_directory_to_config(dir) {
-        eprintln!("Warning: Failed to add directory to config: {}", e);
-    }
+        // Add directory to configuration file for daemon tracking
+        if let Err(e) = tx.register_directory() {
+            eprintln!("Warning: Failed to add directory to config: {}", e);
+        }
 
-    Ok(())
+        Ok(report)
+    })
 }
 
-/// Ensures the .rules directory exists in the specified directory.
+/// Sets up a nested package of a recursive project.
+///
+/// Creates the package's .rules directory if needed, links its aliases as set
+/// in its own .known.json and updates its Aider configuration. Used by the
+/// daemon for packages added while it runs; the changes are not journaled.
 ///
 /// # Arguments
 ///
-/// * `dir` - The parent directory where .rules should be created
+/// * `dir` - Path to the package directory
 ///
 /// # Errors
 ///
-/// Returns an error if directory creation fails
+/// Returns an error if the package configuration is malformed, or if a
+/// directory, link or configuration file cannot be written.
+pub(crate) fn link_package(dir: &Path) -> io::Result<()> {
+    ensure_rules_directory_exists(dir)?;
+    relink_aliases(dir)?;
+    sync_aider_config_if_enabled(dir)
+}
+
+/// Points the aliases of a project or package at AGENTS.md again, as set in its .known.json.
 ///
-pub fn ensure_rules_directory_exists<P: AsRef<Path>>(dir: P) -> io::Result<()> {
-    let rules_path = dir.as_ref().join(RULES_DIR);
-    if !rules_path.exists() {
-        fs::create_dir(rules_path)?;
+/// Used by the daemon when AGENTS.local.md appears or goes away; the changes are
+/// not journaled.
+///
+/// # Errors
+///
+/// Returns an error if the project configuration is malformed or a link cannot
+/// be created (see [`link_aliases`]).
+pub(crate) fn relink_aliases(dir: &Path) -> io::Result<()> {
+    link_aliases(dir, &load_project_config(dir)?.aliases)
+}
+
+/// Returns the paths [`link_aliases`] may change, in the order they are snapshotted.
+fn alias_paths(dir: &Path) -> Vec<PathBuf> {
+    let settings_path = dir.join(GEMINI_SETTINGS_PATH);
+    let mut paths = vec![
+        dir.join(CLAUDE_FILENAME),
+        dir.join(CLAUDE_LOCAL_FILENAME),
+        dir.join(GEMINI_FILENAME),
+    ];
+    if let Some(settings_dir) = settings_path.parent() {
+        paths.push(settings_dir.to_path_buf());
     }
-    Ok(())
+    paths.push(settings_path);
+    paths
 }
 
-/// Removes existing symlink files if they exist.
+/// Points CLAUDE.md and GEMINI.md (or the Gemini settings) at AGENTS.md.
+///
+/// With the `symlink` strategy, an alias is a copy rendered for its agent instead
+/// of a symlink when AGENTS.md has conditional sections.
+///
+/// If the directory has an AGENTS.local.md, it is added as well: through a
+/// CLAUDE.local.md symlink (or an extra import with the `import` strategy) for
+/// Claude Code, and as a second context file with the `settings` strategy for
+/// Gemini CLI. With the `symlink` strategy, Gemini CLI has no way to read it.
 ///
 /// # Arguments
 ///
-/// * `claude_path` - Path to the CLAUDE.md symlink
-/// * `gemini_path` - Path to the GEMINI.md symlink
+/// * `dir` - Path to the project or package directory
+/// * `aliases` - The alias strategies to apply
 ///
 /// # Errors
 ///
-/// Returns an error if file removal fails
-///
-fn remove_existing_symlinks<P: AsRef<Path>>(claude_path: P, gemini_path: P) -> io::Result<()> {
-    let claude_path = claude_path.as_ref();
-    let gemini_path = gemini_path.as_ref();
+/// Returns an error if .gemini/settings.json is malformed, if switching CLAUDE.md
+/// back to a symlink would discard notes, or if a link cannot be created.
+fn link_aliases(dir: &Path, aliases: &AliasConfig) -> io::Result<()> {
+    let gemini_path = dir.join(GEMINI_FILENAME);
+    let has_local_agents = dir.join(AGENTS_LOCAL_FILENAME).exists();
+    let conditional = fs::read_to_string(dir.join(AGENTS_FILENAME))
+        .is_ok_and(|content| has_conditional_sections(&content));
+    match aliases.claude {
+        ClaudeStrategy::Symlink => {
+            // A CLAUDE.md generated by the import strategy is replaced by the symlink
+            remove_claude_import(dir)?;
+            sync_symlinked_alias(dir, CLAUDE_FILENAME)?;
+            link_claude_local(dir, has_local_agents)?;
+        }
+        ClaudeStrategy::Import => {
+            warn_unfiltered(dir, CLAUDE_FILENAME, conditional);
+            sync_claude_import(dir)?;
+            link_claude_local(dir, false)?;
+        }
+    }
+    match aliases.gemini {
+        GeminiStrategy::Symlink => {
+            sync_symlinked_alias(dir, GEMINI_FILENAME)?;
+            set_gemini_local_context_file(dir, false)?;
+            unset_gemini_context_file(dir)?;
+        }
+        GeminiStrategy::Settings => {
+            warn_unfiltered(dir, GEMINI_FILENAME, conditional);
+            remove_existing_symlink(&gemini_path)?;
+            set_gemini_context_file(dir)?;
+            set_gemini_local_context_file(dir, has_local_agents)?;
+        }
+    }
+    Ok(())
+}
 
-    if claude_path.exists() {
-        fs::remove_file(claude_path)?;
+/// Warns that an agent reading AGENTS.md directly sees the sections of other agents.
+fn warn_unfiltered(dir: &Path, alias: &str, conditional: bool) {
+    if conditional {
+        eprintln!(
+            "Warning: {} in {} has conditional sections, but {} reads it directly and sees all of them; use the 'symlink' strategy to render a filtered copy",
+            AGENTS_FILENAME,
+            dir.display(),
+            alias
+        );
     }
-    if gemini_path.exists() {
-        fs::remove_file(gemini_path)?;
+}
+
+/// Creates or removes the CLAUDE.local.md symlink to AGENTS.local.md.
+///
+/// A CLAUDE.local.md that isn't a link to AGENTS.local.md belongs to the user and
+/// is never replaced.
+fn link_claude_local(dir: &Path, enabled: bool) -> io::Result<()> {
+    let claude_local_path = dir.join(CLAUDE_LOCAL_FILENAME);
+    let managed = fs::read_link(&claude_local_path)
+        .is_ok_and(|target| target == Path::new(AGENTS_LOCAL_FILENAME));
+    match fs::symlink_metadata(&claude_local_path) {
+        Ok(_) if !managed => {
+            if enabled {
+                eprintln!(
+                    "Warning: {} already exists; it was not linked to {}",
+                    claude_local_path.display(),
+                    AGENTS_LOCAL_FILENAME
+                );
+            }
+            return Ok(());
+        }
+        Ok(_) => fs::remove_file(&claude_local_path)?,
+        Err(_) => {}
+    }
+    if enabled {
+        create_platform_symlink(Path::new(AGENTS_LOCAL_FILENAME), &claude_local_path)?;
     }
     Ok(())
 }
 
-/// Moves files from a source directory to the target .rules directory.
-///
-/// This function scans the source directory for files and attempts to move them
-/// to the target directory. If a file with the same name already exists in the
-/// target directory, it prints a warning and skips the file.
+/// Adds the files of the local layer to the section of `.git/info/exclude` managed by known.
+///
+/// AGENTS.local.md, the CLAUDE.local.md link and `.rules.local` are personal and
+/// must never be committed. The patterns match at any depth, so they cover
+/// nested packages as well.
+fn exclude_local_layer(dir: &Path) -> io::Result<()> {
+    let patterns = [
+        AGENTS_LOCAL_FILENAME.to_string(),
+        CLAUDE_LOCAL_FILENAME.to_string(),
+        format!("{}/", RULES_LOCAL_DIR),
+    ];
+    update_exclude_section(dir, LOCAL_LAYER_EXCLUDE_SECTION, &patterns)
+}
+
+/// Ensures the .rules directory exists in the specified directory.
 ///
 /// # Arguments
 ///
-/// * `source_dir` - The source directory path to move files from
-/// * `target_dir` - The target directory path to move files to
+/// * `dir` - The parent directory where .rules should be created
 ///
 /// # Errors
 ///
-/// Returns an error if:
-/// - Directory reading fails
-/// - File moving fails for reasons other than the target file already existing
+/// Returns an error if directory creation fails
 ///
-fn move_files_to_rules_dir<P: AsRef<Path>>(source_dir: P, target_dir: P) -> io::Result<()> {
-    let source_dir = source_dir.as_ref();
-    let target_dir = target_dir.as_ref();
-
-    // Check if source directory exists
-    if !source_dir.exists() {
-        return Ok(());
+pub fn ensure_rules_directory_exists<P: AsRef<Path>>(dir: P) -> io::Result<()> {
+    let rules_path = dir.as_ref().join(RULES_DIR);
+    if !rules_path.exists() {
+        fs::create_dir(rules_path)?;
     }
+    Ok(())
+}
 
-    for entry in fs::read_dir(source_dir)? {
-        let entry = entry?;
-        let file_name = entry.file_name();
-        let source_path = entry.path();
-        let target_path = target_dir.join(&file_name);
-
-        // Skip if target file already exists
-        if target_path.exists() {
-            println!(
-                "Warning: File '{}' already exists in .rules directory. Skipping.",
-                file_name.to_string_lossy()
-            );
-            continue;
-        }
+/// Removes an existing alias file or symlink if it exists.
+///
+/// # Arguments
+///
+/// * `path` - Path to the alias (for example CLAUDE.md or GEMINI.md)
+///
+/// # Errors
+///
+/// Returns an error if file removal fails
+///
+fn remove_existing_symlink<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    let path = path.as_ref();
 
-        // Move the file
-        fs::rename(&source_path, &target_path)?;
+    // symlink_metadata also catches symlinks whose target is missing
+    if fs::symlink_metadata(path).is_ok() {
+        fs::remove_file(path)?;
     }
-
     Ok(())
 }
 
@@ -196,7 +441,7 @@ fn move_files_to_rules_dir<P: AsRef<Path>>(source_dir: P, target_dir: P) -> io::
 ///
 /// Returns an error if symlink creation fails
 ///
-fn create_platform_symlink(source: &Path, target: &Path) -> io::Result<()> {
+pub(crate) fn create_platform_symlink(source: &Path, target: &Path) -> io::Result<()> {
     #[cfg(unix)]
     {
         std::os::unix::fs::symlink(source, target)?;
@@ -212,6 +457,10 @@ fn create_platform_symlink(source: &Path, target: &Path) -> io::Result<()> {
 
 /// Creates a symlink from target to source file.
 ///
+/// An existing entry is replaced atomically: the new link is created next to it
+/// and renamed over it, so tools reading the directory never see the entry
+/// missing. A link that already points to the source is left untouched.
+///
 /// # Arguments
 ///
 /// * `source` - Path to the source file
@@ -222,13 +471,25 @@ fn create_platform_symlink(source: &Path, target: &Path) -> io::Result<()> {
 /// Returns an error if symlink creation fails
 ///
 pub fn create_symlink_to_file(source: &Path, target: &Path) -> io::Result<()> {
-    // Remove existing symlink if it exists
-    if target.exists() {
-        fs::remove_file(target)?;
+    // Replace existing entries, including dangling symlinks
+    if fs::symlink_metadata(target).is_err() {
+        return create_platform_symlink(source, target);
+    }
+    if fs::read_link(target).is_ok_and(|destination| destination == source) {
+        return Ok(());
     }
 
-    // Create platform-specific symlink
-    create_platform_symlink(source, target)
+    let mut staging_name = std::ffi::OsString::from(".");
+    staging_name.push(target.file_name().unwrap_or_default());
+    staging_name.push(".known-tmp");
+    let staging_path = target.with_file_name(staging_name);
+    if fs::symlink_metadata(&staging_path).is_ok() {
+        fs::remove_file(&staging_path)?;
+    }
+    create_platform_symlink(source, &staging_path)?;
+    fs::rename(&staging_path, target).inspect_err(|_| {
+        let _ = fs::remove_file(&staging_path);
+    })
 }
 
 #[cfg(test)]
@@ -266,6 +527,37 @@ mod tests {
         assert_eq!(claude_content, "# Agents content");
     }
 
+    #[test]
+    fn test_create_symlinks_with_gemini_settings_strategy() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "# Agents content").unwrap();
+        let gemini_path = dir.path().join("GEMINI.md");
+        let settings_path = dir.path().join(crate::constants::GEMINI_SETTINGS_PATH);
+
+        // Start with the default strategy so GEMINI.md exists
+        create_symlinks_in_dir(dir.path()).unwrap();
+        assert!(gemini_path.exists());
+
+        let mut config = load_project_config(dir.path()).unwrap();
+        config.aliases.gemini = GeminiStrategy::Settings;
+        crate::config::save_project_config(dir.path(), &config).unwrap();
+
+        create_symlinks_in_dir(dir.path()).unwrap();
+        assert!(fs::symlink_metadata(&gemini_path).is_err());
+        assert!(dir.path().join("CLAUDE.md").exists());
+        assert!(fs::read_to_string(&settings_path)
+            .unwrap()
+            .contains("\"contextFileName\": \"AGENTS.md\""));
+
+        // Switching back restores the symlink and removes the setting
+        config.aliases.gemini = GeminiStrategy::Symlink;
+        crate::config::save_project_config(dir.path(), &config).unwrap();
+
+        create_symlinks_in_dir(dir.path()).unwrap();
+        assert!(gemini_path.exists());
+        assert!(!settings_path.exists());
+    }
+
     #[test]
     fn test_create_symlinks_no_agents_file() {
         let dir = tempdir().unwrap();
@@ -443,6 +735,55 @@ mod tests {
         assert_eq!(content, "existing content");
     }
 
+    #[test]
+    fn test_failed_run_is_rolled_back_and_can_be_undone() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "# Agents content").unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "# Old Claude content").unwrap();
+        let cursor_rules_path = dir.path().join(".cursor/rules");
+        fs::create_dir_all(&cursor_rules_path).unwrap();
+        fs::write(cursor_rules_path.join("rule.md"), "rule").unwrap();
+
+        // A malformed settings file makes the Gemini step fail after the rules were moved
+        let settings_path = dir.path().join(crate::constants::GEMINI_SETTINGS_PATH);
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(&settings_path, "[]").unwrap();
+        let options = SymlinkOptions {
+            gemini: Some(GeminiStrategy::Settings),
+            ..SymlinkOptions::default()
+        };
+
+        let error = create_symlinks_with_options(dir.path(), &options).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            fs::read_to_string(cursor_rules_path.join("rule.md")).unwrap(),
+            "rule"
+        );
+        assert!(!dir.path().join(RULES_DIR).exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("CLAUDE.md")).unwrap(),
+            "# Old Claude content"
+        );
+        assert!(!dir.path().join(PROJECT_CONFIG_FILENAME).exists());
+
+        // A successful run can be reverted afterwards
+        fs::remove_file(&settings_path).unwrap();
+        create_symlinks_in_dir(dir.path()).unwrap();
+        assert!(dir.path().join(RULES_DIR).join("rule.md").exists());
+
+        let report = crate::transaction::undo_last_run(dir.path()).unwrap();
+        assert_eq!(report.command, "symlink");
+        assert_eq!(
+            fs::read_to_string(cursor_rules_path.join("rule.md")).unwrap(),
+            "rule"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("CLAUDE.md")).unwrap(),
+            "# Old Claude content"
+        );
+        assert!(fs::symlink_metadata(dir.path().join("GEMINI.md")).is_err());
+    }
+
     #[test]
     fn test_move_rules_no_source_directories() {
         let dir = tempdir().unwrap();
@@ -488,8 +829,67 @@ mod tests {
 
         let result = create_symlinks_in_dir(&readonly_dir);
 
-        if result.is_err() {
-            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        if let Err(e) = result {
+            assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
         }
     }
+
+    #[test]
+    fn test_recursive_mode_links_nested_packages() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "# Root").unwrap();
+        fs::write(dir.path().join(".gitignore"), "node_modules/\n").unwrap();
+        let package = dir.path().join("packages").join("web");
+        let cursor_rules_path = package.join(crate::constants::CURSOR_RULES_DIR);
+        fs::create_dir_all(&cursor_rules_path).unwrap();
+        fs::write(package.join("AGENTS.md"), "# Web").unwrap();
+        fs::write(cursor_rules_path.join("style.md"), "Use tabs.").unwrap();
+        let ignored = dir.path().join("node_modules").join("dep");
+        fs::create_dir_all(&ignored).unwrap();
+        fs::write(ignored.join("AGENTS.md"), "# Dependency").unwrap();
+
+        let options = SymlinkOptions {
+            recursive: true,
+            ..SymlinkOptions::default()
+        };
+        let report = create_symlinks_with_options(dir.path(), &options).unwrap();
+        assert_eq!(report.decisions.len(), 1);
+
+        let project_config = load_project_config(dir.path()).unwrap();
+        assert!(project_config.recursive);
+        assert!(!project_config.inherit);
+        assert_eq!(
+            fs::read_to_string(package.join("CLAUDE.md")).unwrap(),
+            "# Web"
+        );
+        assert_eq!(
+            fs::read_to_string(package.join(RULES_DIR).join("style.md")).unwrap(),
+            "Use tabs."
+        );
+        assert!(!ignored.join("CLAUDE.md").exists());
+
+        // Packages are linked by the same run, so undo reverts them as well
+        crate::transaction::undo_last_run(dir.path()).unwrap();
+        assert!(!package.join("CLAUDE.md").exists());
+        assert!(cursor_rules_path.join("style.md").exists());
+    }
+
+    #[test]
+    fn test_local_layer_is_linked_and_excluded() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git").join("info")).unwrap();
+        fs::write(dir.path().join("AGENTS.md"), "# Team").unwrap();
+        fs::write(dir.path().join(AGENTS_LOCAL_FILENAME), "# Mine").unwrap();
+
+        create_symlinks_in_dir(dir.path()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join(CLAUDE_LOCAL_FILENAME)).unwrap(),
+            "# Mine"
+        );
+        let exclude =
+            fs::read_to_string(dir.path().join(".git").join("info").join("exclude")).unwrap();
+        assert!(exclude.contains(AGENTS_LOCAL_FILENAME));
+        assert!(exclude.contains(".rules.local/"));
+    }
 }
@@ -0,0 +1,877 @@
+//! Project-aware templates for new AGENTS.md files.
+//!
+//! `known init` inspects the project (Cargo.toml, package.json, pyproject.toml,
+//! go.mod, justfile and Makefile targets, test directories) and fills a template
+//! with the build, test and lint commands and the layout it finds. Templates are
+//! built in per ecosystem, and users can add their own Markdown templates to the
+//! `templates` directory next to the global configuration file.
+//!
+//! Templates may use these placeholders:
+//!
+//! - `{{intro}}`: the standard introduction of AGENTS.md
+//! - `{{name}}`: the project name, or the directory name if none is declared
+//! - `{{commands}}`: a `## Commands` section listing the detected commands
+//! - `{{layout}}`: a `## Project Layout` section listing the top-level directories
+//! - `{{testing}}`: a `## Testing` section describing where tests live
+//!
+//! Section placeholders expand to nothing when there is nothing to list.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::get_templates_dir;
+use crate::constants::AGENTS_CONTENTS;
+
+/// Names of the built-in templates
+pub const BUILTIN_TEMPLATES: [&str; 6] = ["minimal", "generic", "rust", "node", "python", "go"];
+
+/// Directories that are build output or dependencies rather than project layout
+const IGNORED_DIRS: [&str; 9] = [
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "venv",
+    "__pycache__",
+    "out",
+    "coverage",
+];
+
+/// Directories that conventionally hold tests
+const TEST_DIRS: [&str; 4] = ["tests", "test", "__tests__", "spec"];
+
+const GENERIC_TEMPLATE: &str = "{{intro}}
+
+{{commands}}
+
+{{layout}}
+
+{{testing}}
+";
+
+const RUST_TEMPLATE: &str = "{{intro}}
+
+## Project
+
+`{{name}}` is a Rust project built with Cargo.
+
+{{commands}}
+
+{{layout}}
+
+{{testing}}
+
+## Conventions
+
+- Format code with `cargo fmt` and keep `cargo clippy` free of warnings
+- Put unit tests in a `#[cfg(test)]` module next to the code they test
+";
+
+const NODE_TEMPLATE: &str = "{{intro}}
+
+## Project
+
+`{{name}}` is a JavaScript/TypeScript project.
+
+{{commands}}
+
+{{layout}}
+
+{{testing}}
+
+## Conventions
+
+- Run tasks through the package.json scripts rather than calling tools directly
+- Commit the lockfile together with dependency changes
+";
+
+const PYTHON_TEMPLATE: &str = "{{intro}}
+
+## Project
+
+`{{name}}` is a Python project.
+
+{{commands}}
+
+{{layout}}
+
+{{testing}}
+
+## Conventions
+
+- Follow PEP 8 and add type hints to new code
+- Declare dependencies in pyproject.toml
+";
+
+const GO_TEMPLATE: &str = "{{intro}}
+
+## Project
+
+`{{name}}` is a Go module.
+
+{{commands}}
+
+{{layout}}
+
+{{testing}}
+
+## Conventions
+
+- Format code with `go fmt` and keep `go vet` clean
+- Put tests in `_test.go` files next to the code they test
+";
+
+/// A language ecosystem detected from its manifest file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    /// Cargo.toml
+    Rust,
+    /// go.mod
+    Go,
+    /// pyproject.toml
+    Python,
+    /// package.json
+    Node,
+}
+
+impl Ecosystem {
+    /// Name of the built-in template for this ecosystem
+    pub fn template_name(self) -> &'static str {
+        match self {
+            Ecosystem::Rust => "rust",
+            Ecosystem::Go => "go",
+            Ecosystem::Python => "python",
+            Ecosystem::Node => "node",
+        }
+    }
+}
+
+/// What a project command is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommandKind {
+    Build,
+    Test,
+    Lint,
+    Format,
+    Run,
+}
+
+impl fmt::Display for CommandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandKind::Build => write!(f, "Build"),
+            CommandKind::Test => write!(f, "Test"),
+            CommandKind::Lint => write!(f, "Lint"),
+            CommandKind::Format => write!(f, "Format"),
+            CommandKind::Run => write!(f, "Run"),
+        }
+    }
+}
+
+/// A command found in the project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectCommand {
+    /// What the command is used for
+    pub kind: CommandKind,
+    /// The command line, for example `cargo test`
+    pub command: String,
+}
+
+/// What `known init` found out about a project
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectInfo {
+    /// Name declared in the manifest, if any
+    pub name: Option<String>,
+    /// Detected ecosystems, in the order Rust, Go, Python, Node
+    pub ecosystems: Vec<Ecosystem>,
+    /// Build, test, lint, format and run commands
+    pub commands: Vec<ProjectCommand>,
+    /// Top-level directories, sorted by name
+    pub layout: Vec<String>,
+    /// Top-level directories that hold tests
+    pub test_dirs: Vec<String>,
+}
+
+impl ProjectInfo {
+    /// Checks whether nothing was detected.
+    pub fn is_empty(&self) -> bool {
+        self.ecosystems.is_empty() && self.commands.is_empty() && self.layout.is_empty()
+    }
+}
+
+/// An AGENTS.md rendered from a template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedTemplate {
+    /// Name of the template that was used
+    pub name: String,
+    /// The rendered content
+    pub content: String,
+}
+
+/// Inspects a project for its ecosystems, commands and layout.
+///
+/// Commands declared explicitly (justfile recipes, Makefile targets and
+/// package.json scripts) come first. The standard commands of an ecosystem, such
+/// as `cargo test`, are only added for kinds that no explicit command covers.
+/// When a project has several ecosystems, only the commands of the primary one,
+/// the first in the order Rust, Go, Python, Node, are listed, so a Rust project
+/// with a package.json for its docs site doesn't list npm scripts.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+pub fn detect_project(dir: &Path) -> ProjectInfo {
+    let mut info = ProjectInfo::default();
+
+    for name in ["justfile", "Justfile", ".justfile"] {
+        if let Ok(content) = fs::read_to_string(dir.join(name)) {
+            add_targets(&mut info, "just", parse_justfile_recipes(&content));
+            break;
+        }
+    }
+    for name in ["GNUmakefile", "Makefile", "makefile"] {
+        if let Ok(content) = fs::read_to_string(dir.join(name)) {
+            add_targets(&mut info, "make", parse_makefile_targets(&content));
+            break;
+        }
+    }
+
+    let mut inferred = Vec::new();
+    if let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) {
+        info.ecosystems.push(Ecosystem::Rust);
+        info.name = info
+            .name
+            .or_else(|| toml_value(&content, "package", "name"));
+        let scope = if content.lines().any(|line| line.trim() == "[workspace]") {
+            " --workspace"
+        } else {
+            ""
+        };
+        inferred.push((
+            Ecosystem::Rust,
+            CommandKind::Build,
+            format!("cargo build{}", scope),
+        ));
+        inferred.push((
+            Ecosystem::Rust,
+            CommandKind::Test,
+            format!("cargo test{}", scope),
+        ));
+        inferred.push((
+            Ecosystem::Rust,
+            CommandKind::Lint,
+            format!("cargo clippy{} --all-targets -- -D warnings", scope),
+        ));
+        inferred.push((
+            Ecosystem::Rust,
+            CommandKind::Format,
+            "cargo fmt".to_string(),
+        ));
+    }
+    if let Ok(content) = fs::read_to_string(dir.join("go.mod")) {
+        info.ecosystems.push(Ecosystem::Go);
+        info.name = info.name.or_else(|| {
+            content
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("module "))
+                .map(|module| module.trim().to_string())
+        });
+        inferred.push((
+            Ecosystem::Go,
+            CommandKind::Build,
+            "go build ./...".to_string(),
+        ));
+        inferred.push((
+            Ecosystem::Go,
+            CommandKind::Test,
+            "go test ./...".to_string(),
+        ));
+        inferred.push((Ecosystem::Go, CommandKind::Lint, "go vet ./...".to_string()));
+        inferred.push((
+            Ecosystem::Go,
+            CommandKind::Format,
+            "go fmt ./...".to_string(),
+        ));
+    }
+    if let Ok(content) = fs::read_to_string(dir.join("pyproject.toml")) {
+        info.ecosystems.push(Ecosystem::Python);
+        info.name = info.name.or_else(|| {
+            toml_value(&content, "project", "name")
+                .or_else(|| toml_value(&content, "tool.poetry", "name"))
+        });
+        let runner = if dir.join("uv.lock").exists() {
+            "uv run "
+        } else if dir.join("poetry.lock").exists() || content.contains("[tool.poetry") {
+            "poetry run "
+        } else {
+            ""
+        };
+        if content.contains("pytest") || TEST_DIRS.iter().any(|d| dir.join(d).is_dir()) {
+            inferred.push((
+                Ecosystem::Python,
+                CommandKind::Test,
+                format!("{}pytest", runner),
+            ));
+        }
+        if content.contains("ruff") {
+            inferred.push((
+                Ecosystem::Python,
+                CommandKind::Lint,
+                format!("{}ruff check .", runner),
+            ));
+            inferred.push((
+                Ecosystem::Python,
+                CommandKind::Format,
+                format!("{}ruff format .", runner),
+            ));
+        } else if content.contains("black") {
+            inferred.push((
+                Ecosystem::Python,
+                CommandKind::Format,
+                format!("{}black .", runner),
+            ));
+        }
+        if content.contains("mypy") {
+            inferred.push((
+                Ecosystem::Python,
+                CommandKind::Lint,
+                format!("{}mypy .", runner),
+            ));
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("package.json")) {
+        info.ecosystems.push(Ecosystem::Node);
+        let node_name = detect_node(dir, &content, &mut info);
+        info.name = info.name.or(node_name);
+    }
+
+    // Inferred commands of the primary ecosystem fill the gaps the explicit ones leave
+    let primary = info.ecosystems.first().copied();
+    let explicit: Vec<CommandKind> = info.commands.iter().map(|c| c.kind).collect();
+    for (ecosystem, kind, command) in inferred {
+        if Some(ecosystem) == primary && !explicit.contains(&kind) {
+            info.commands.push(ProjectCommand { kind, command });
+        }
+    }
+    info.commands.sort_by_key(|command| command.kind);
+
+    let mut layout: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.') && !IGNORED_DIRS.contains(&name.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    layout.sort();
+    info.test_dirs = layout
+        .iter()
+        .filter(|name| TEST_DIRS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    info.layout = layout;
+
+    info
+}
+
+/// Renders the AGENTS.md for a new project.
+///
+/// Without a template name, the template of the first detected ecosystem is
+/// used, `generic` if only commands or directories were found, and `minimal`
+/// (the standard introduction only) for an empty project. A named template is
+/// looked up in the user template directory first (`<name>.md`), then among the
+/// built-in templates.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `template` - Name of the template to use, if chosen explicitly
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if no template has the given name, or an
+/// error if a user template cannot be read.
+pub fn render_agents_template(dir: &Path, template: Option<&str>) -> io::Result<RenderedTemplate> {
+    let templates_dir = match template {
+        Some(_) => Some(get_templates_dir()?),
+        None => None,
+    };
+    render_agents_template_from(dir, template, templates_dir.as_deref())
+}
+
+/// Renders the AGENTS.md for a new project, with an explicit user template directory.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+/// * `template` - Name of the template to use, if chosen explicitly
+/// * `templates_dir` - Directory holding user templates, if any
+///
+/// # Errors
+///
+/// Returns the same errors as [`render_agents_template`].
+pub fn render_agents_template_from(
+    dir: &Path,
+    template: Option<&str>,
+    templates_dir: Option<&Path>,
+) -> io::Result<RenderedTemplate> {
+    let info = detect_project(dir);
+
+    let name = match template {
+        Some(name) => name.to_string(),
+        None => match info.ecosystems.first() {
+            Some(ecosystem) => ecosystem.template_name().to_string(),
+            None if info.is_empty() => "minimal".to_string(),
+            None => "generic".to_string(),
+        },
+    };
+
+    let user_template = templates_dir.map(|d| d.join(format!("{}.md", name)));
+    let text = match user_template.filter(|path| path.is_file()) {
+        Some(path) => fs::read_to_string(path)?,
+        None => match builtin_template(&name) {
+            Some(text) => text.to_string(),
+            None => {
+                let mut available: Vec<String> =
+                    BUILTIN_TEMPLATES.iter().map(|t| t.to_string()).collect();
+                available.extend(templates_dir.map(user_templates).unwrap_or_default());
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown template '{}'. Available templates: {}",
+                        name,
+                        available.join(", ")
+                    ),
+                ));
+            }
+        },
+    };
+
+    let project_name = info.name.clone().unwrap_or_else(|| {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "project".to_string())
+    });
+
+    Ok(RenderedTemplate {
+        name,
+        content: render_template(&text, &project_name, &info),
+    })
+}
+
+/// Fills the placeholders of a template.
+///
+/// Runs of blank lines left by empty sections are collapsed, and the result ends
+/// with a single newline (unless the template had no trailing newline at all).
+///
+/// # Arguments
+///
+/// * `template` - The template text
+/// * `project_name` - Value for `{{name}}`
+/// * `info` - The detected project information
+pub fn render_template(template: &str, project_name: &str, info: &ProjectInfo) -> String {
+    let rendered = template
+        .replace("{{intro}}", AGENTS_CONTENTS)
+        .replace("{{name}}", project_name)
+        .replace("{{commands}}", &render_commands(info))
+        .replace("{{layout}}", &render_layout(info))
+        .replace("{{testing}}", &render_testing(info));
+
+    let mut content = String::new();
+    let mut blank_lines = 0;
+    for line in rendered.trim_end().lines() {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if blank_lines > 0 && !content.is_empty() {
+            content.push('\n');
+        }
+        blank_lines = 0;
+        content.push_str(line);
+        content.push('\n');
+    }
+
+    if !template.ends_with('\n') {
+        content.pop();
+    }
+    content
+}
+
+/// Returns the text of a built-in template.
+fn builtin_template(name: &str) -> Option<&'static str> {
+    match name {
+        "minimal" => Some("{{intro}}"),
+        "generic" => Some(GENERIC_TEMPLATE),
+        "rust" => Some(RUST_TEMPLATE),
+        "node" => Some(NODE_TEMPLATE),
+        "python" => Some(PYTHON_TEMPLATE),
+        "go" => Some(GO_TEMPLATE),
+        _ => None,
+    }
+}
+
+/// Lists the names of the user templates in a directory.
+fn user_templates(templates_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(templates_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Renders the `## Commands` section.
+fn render_commands(info: &ProjectInfo) -> String {
+    if info.commands.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("## Commands\n\n");
+    for command in &info.commands {
+        section.push_str(&format!("- {}: `{}`\n", command.kind, command.command));
+    }
+    section
+}
+
+/// Renders the `## Project Layout` section.
+fn render_layout(info: &ProjectInfo) -> String {
+    if info.layout.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("## Project Layout\n\n");
+    for name in &info.layout {
+        match describe_directory(name) {
+            Some(description) => section.push_str(&format!("- `{}/`: {}\n", name, description)),
+            None => section.push_str(&format!("- `{}/`\n", name)),
+        }
+    }
+    section
+}
+
+/// Renders the `## Testing` section.
+fn render_testing(info: &ProjectInfo) -> String {
+    let test_command = info
+        .commands
+        .iter()
+        .find(|command| command.kind == CommandKind::Test);
+    if info.test_dirs.is_empty() && test_command.is_none() {
+        return String::new();
+    }
+
+    let mut sentences = Vec::new();
+    if !info.test_dirs.is_empty() {
+        let dirs: Vec<String> = info.test_dirs.iter().map(|d| format!("`{}/`", d)).collect();
+        sentences.push(format!("Tests live in {}.", dirs.join(" and ")));
+    }
+    if let Some(command) = test_command {
+        sentences.push(format!("Run them with `{}`.", command.command));
+    }
+    sentences.push("Add or update tests with every change.".to_string());
+    format!("## Testing\n\n{}\n", sentences.join(" "))
+}
+
+/// Describes a conventional top-level directory.
+fn describe_directory(name: &str) -> Option<&'static str> {
+    match name {
+        "src" => Some("Source code"),
+        "lib" => Some("Library code"),
+        "tests" | "test" | "__tests__" | "spec" => Some("Tests"),
+        "docs" | "doc" => Some("Documentation"),
+        "examples" => Some("Examples"),
+        "benches" | "benchmarks" => Some("Benchmarks"),
+        "scripts" => Some("Scripts"),
+        "crates" | "packages" => Some("Workspace members"),
+        "cmd" => Some("Command entry points"),
+        "internal" => Some("Private packages"),
+        "pkg" => Some("Public packages"),
+        "bin" => Some("Executables"),
+        "assets" | "static" | "public" => Some("Static assets"),
+        "migrations" => Some("Database migrations"),
+        _ => None,
+    }
+}
+
+/// Maps a task runner target or script name to the kind of command it is.
+fn classify_target(name: &str) -> Option<CommandKind> {
+    match name.to_lowercase().as_str() {
+        "build" | "compile" => Some(CommandKind::Build),
+        "test" | "tests" | "check" => Some(CommandKind::Test),
+        "lint" | "clippy" | "vet" | "typecheck" | "type-check" => Some(CommandKind::Lint),
+        "fmt" | "format" => Some(CommandKind::Format),
+        "dev" | "run" | "start" | "serve" => Some(CommandKind::Run),
+        _ => None,
+    }
+}
+
+/// Adds the recognized targets of a task runner as commands.
+fn add_targets(info: &mut ProjectInfo, runner: &str, targets: Vec<String>) {
+    for target in targets {
+        if let Some(kind) = classify_target(&target) {
+            info.commands.push(ProjectCommand {
+                kind,
+                command: format!("{} {}", runner, target),
+            });
+        }
+    }
+}
+
+/// Adds the package.json scripts, run with the project's package manager, if
+/// Node is the primary ecosystem, and returns the package name.
+fn detect_node(dir: &Path, content: &str, info: &mut ProjectInfo) -> Option<String> {
+    let package: serde_json::Value = serde_json::from_str(content).ok()?;
+
+    let manager = if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else if dir.join("bun.lockb").exists() || dir.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    };
+
+    let scripts = package.get("scripts").and_then(|s| s.as_object());
+    if let (Some(scripts), Some(Ecosystem::Node)) = (scripts, info.ecosystems.first()) {
+        let targets = scripts.keys().cloned().collect();
+        add_targets(info, &format!("{} run", manager), targets);
+    }
+
+    package
+        .get("name")
+        .and_then(|name| name.as_str())
+        .map(|name| name.to_string())
+}
+
+/// Reads a string value from a section of a TOML file without a full parser.
+fn toml_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let header = format!("[{}]", section);
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == header;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            if name.trim() == key {
+                let value = value.trim().trim_matches('"').trim_matches('\'');
+                if !value.is_empty() {
+                    return Some(value.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Lists the recipe names of a justfile.
+fn parse_justfile_recipes(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            if line.starts_with(|c: char| c.is_whitespace() || c == '#' || c == '[') {
+                return None;
+            }
+            let line = line.strip_prefix('@').unwrap_or(line);
+            let name = target_name(line);
+            let rest = &line[name.len()..];
+            // `name:` or `name param="default":`, but not `name := value`
+            let colon = rest.find(':')?;
+            if name.is_empty() || rest[colon..].starts_with(":=") {
+                return None;
+            }
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+/// Lists the target names of a Makefile.
+fn parse_makefile_targets(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            if line.starts_with(|c: char| c.is_whitespace() || c == '#' || c == '.') {
+                return None;
+            }
+            let name = target_name(line);
+            let rest = line[name.len()..].trim_start();
+            if name.is_empty() || !rest.starts_with(':') || rest.starts_with(":=") {
+                return None;
+            }
+            Some(name.to_string())
+        })
+        .collect()
+}
+
+/// Returns the leading identifier of a line.
+fn target_name(line: &str) -> &str {
+    let end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_rust_project_with_justfile() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("justfile"),
+            "set shell := [\"bash\", \"-c\"]\n\n# Run tests\ntest filter=\"\":\n    cargo test {{filter}}\n\nrelease:\n    cargo build --release\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::create_dir(dir.path().join("tests")).unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+
+        let info = detect_project(dir.path());
+        assert_eq!(info.name.as_deref(), Some("demo"));
+        assert_eq!(info.ecosystems, vec![Ecosystem::Rust]);
+        let commands: Vec<&str> = info.commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                "cargo build",
+                "just test",
+                "cargo clippy --all-targets -- -D warnings",
+                "cargo fmt"
+            ]
+        );
+        assert_eq!(info.layout, vec!["src", "tests"]);
+
+        let rendered = render_agents_template_from(dir.path(), None, None).unwrap();
+        assert_eq!(rendered.name, "rust");
+        assert!(rendered.content.starts_with(AGENTS_CONTENTS));
+        assert!(rendered.content.contains("`demo` is a Rust project"));
+        assert!(rendered.content.contains("- Test: `just test`\n"));
+        assert!(rendered.content.contains("- `src/`: Source code\n"));
+        assert!(rendered
+            .content
+            .contains("Tests live in `tests/`. Run them with `just test`."));
+        assert!(!rendered.content.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn test_detect_node_and_makefile() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "web", "scripts": {"dev": "vite", "lint": "eslint .", "deploy": "x"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(
+            dir.path().join("Makefile"),
+            "CC := gcc\n.PHONY: build\nbuild: deps\n\tnpm run build\n",
+        )
+        .unwrap();
+
+        let info = detect_project(dir.path());
+        assert_eq!(info.name.as_deref(), Some("web"));
+        let commands: Vec<&str> = info.commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec!["make build", "pnpm run lint", "pnpm run dev"]
+        );
+        assert!(info.test_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_commands_come_from_primary_ecosystem() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("go.mod"), "module example.com/demo\n").unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "docs", "scripts": {"build": "vite build", "test": "vitest"}}"#,
+        )
+        .unwrap();
+
+        let info = detect_project(dir.path());
+        assert_eq!(
+            info.ecosystems,
+            vec![Ecosystem::Rust, Ecosystem::Go, Ecosystem::Node]
+        );
+        let commands: Vec<&str> = info.commands.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                "cargo build",
+                "cargo test",
+                "cargo clippy --all-targets -- -D warnings",
+                "cargo fmt"
+            ]
+        );
+
+        let rendered = render_agents_template_from(dir.path(), None, None).unwrap();
+        assert_eq!(rendered.name, "rust");
+        assert!(!rendered.content.contains("npm"));
+        assert!(!rendered.content.contains("go vet"));
+    }
+
+    #[test]
+    fn test_empty_project_uses_minimal_template() {
+        let dir = tempdir().unwrap();
+        let rendered = render_agents_template_from(dir.path(), None, None).unwrap();
+        assert_eq!(rendered.name, "minimal");
+        assert_eq!(rendered.content, AGENTS_CONTENTS);
+    }
+
+    #[test]
+    fn test_user_template_and_unknown_template() {
+        let dir = tempdir().unwrap();
+        let templates_dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/tool\n\ngo 1.22\n",
+        )
+        .unwrap();
+        fs::write(
+            templates_dir.path().join("team.md"),
+            "# {{name}}\n\n{{layout}}\n\n{{commands}}\n",
+        )
+        .unwrap();
+
+        let rendered =
+            render_agents_template_from(dir.path(), Some("team"), Some(templates_dir.path()))
+                .unwrap();
+        assert_eq!(
+            rendered.content,
+            "# example.com/tool\n\n## Commands\n\n- Build: `go build ./...`\n- Test: `go test ./...`\n- Lint: `go vet ./...`\n- Format: `go fmt ./...`\n"
+        );
+
+        let error =
+            render_agents_template_from(dir.path(), Some("java"), Some(templates_dir.path()))
+                .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("go, team"));
+    }
+}
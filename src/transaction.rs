@@ -0,0 +1,871 @@
+//! Journaled transactions for commands that change a project.
+//!
+//! `known init` and `known symlink` move, replace and remove several files in a
+//! row. Each run records the previous state of everything it touches in a journal
+//! under `.known/` *before* changing it. If a step fails, the run is rolled back
+//! from the journal; after a successful run the journal is kept so that
+//! `known undo` can revert it later.
+//!
+//! The journal of a run in progress lives in `.known/transaction/`. A leftover
+//! pending journal means a run was interrupted, and it is reverted by `known undo`
+//! before any new run can start. Completed runs are moved to `.known/last-run/`,
+//! along with a fingerprint of every path they left behind, so that `known undo`
+//! can tell which files were edited since. The `.known` directory holds copies of
+//! the user's files and is excluded from Git.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{add_directory_to_config, remove_directory_from_config};
+use crate::constants::{
+    JOURNAL_FILENAME, KNOWN_STATE_DIR, LAST_RUN_JOURNAL_DIR, PENDING_JOURNAL_DIR,
+};
+use crate::git::update_exclude_section;
+use crate::repair::{backup_file, unix_timestamp};
+use crate::symlinks::create_platform_symlink;
+
+/// Name of the section of `.git/info/exclude` that excludes the state directory
+pub(crate) const STATE_EXCLUDE_SECTION: &str = "state";
+
+/// Directory inside a journal directory that holds copies of replaced files
+const SAVED_FILES_DIR: &str = "files";
+
+/// The recorded state of a run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Journal {
+    /// The command that started the run (for example `symlink`)
+    command: String,
+    /// When the run started, in seconds since the Unix epoch
+    started_at: u64,
+    /// Changes in the order they were made
+    entries: Vec<JournalEntry>,
+    /// Fingerprint of each changed path when the run completed (see [`fingerprint`])
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    fingerprints: BTreeMap<PathBuf, String>,
+}
+
+/// A single change, described by what is needed to revert it.
+///
+/// Paths are relative to the project directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    /// The path didn't exist before the run
+    Created { path: PathBuf },
+    /// The path was a directory
+    Directory { path: PathBuf },
+    /// The path was a regular file, copied into the journal under `copy`
+    File { path: PathBuf, copy: String },
+    /// The path was a symlink pointing to `target`
+    Symlink { path: PathBuf, target: PathBuf },
+    /// The file or directory at `from` was moved to `to`
+    Moved { from: PathBuf, to: PathBuf },
+    /// The project directory was added to the watched directories
+    Registered,
+}
+
+/// What `known undo` reverted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoReport {
+    /// The command of the reverted run (for example `symlink`)
+    pub command: String,
+    /// Number of changes that were reverted
+    pub changes: usize,
+    /// Whether the run had been interrupted before it completed
+    pub interrupted: bool,
+    /// Files that were edited after the run, saved before being reverted
+    pub preserved: Vec<PreservedEdit>,
+    /// Changes that could not be reverted, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// A file edited after a run, whose content was backed up before `known undo`
+/// replaced or removed it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreservedEdit {
+    /// Path of the edited file, relative to the project directory
+    pub path: PathBuf,
+    /// Where its content was saved
+    pub backup_path: PathBuf,
+}
+
+/// A run that records every change it makes to a project.
+///
+/// Changes are made through the methods of the transaction, or announced with
+/// [`Transaction::snapshot`] before a path is changed by other code.
+#[derive(Debug)]
+pub struct Transaction {
+    project_dir: PathBuf,
+    journal_dir: PathBuf,
+    journal: Journal,
+    recorded: HashSet<PathBuf>,
+}
+
+impl Transaction {
+    /// Starts a new transaction for a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Path to the project directory
+    /// * `command` - Name of the command that starts the run, stored in the journal
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an interrupted run still has to be undone, or if the
+    /// journal cannot be created.
+    pub fn begin(dir: &Path, command: &str) -> io::Result<Self> {
+        let journal_dir = dir.join(KNOWN_STATE_DIR).join(PENDING_JOURNAL_DIR);
+        if journal_dir.exists() {
+            let previous = read_journal(&journal_dir)
+                .map(|journal| journal.command)
+                .unwrap_or_else(|_| "known".to_string());
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "An interrupted 'known {}' run left a journal in {}. Run 'known undo' to revert it first.",
+                    previous,
+                    journal_dir.display()
+                ),
+            ));
+        }
+
+        fs::create_dir_all(journal_dir.join(SAVED_FILES_DIR))?;
+        exclude_state_dir(dir)?;
+        let transaction = Transaction {
+            project_dir: dir.to_path_buf(),
+            journal_dir,
+            journal: Journal {
+                command: command.to_string(),
+                started_at: unix_timestamp(),
+                entries: Vec::new(),
+                fingerprints: BTreeMap::new(),
+            },
+            recorded: HashSet::new(),
+        };
+        transaction.save()?;
+        Ok(transaction)
+    }
+
+    /// Runs `f` in a new transaction, committing it on success and rolling it
+    /// back on error.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Path to the project directory
+    /// * `command` - Name of the command that starts the run, stored in the journal
+    /// * `f` - The changes to make
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `f` after rolling back, or an error if the transaction
+    /// cannot be started or committed.
+    pub fn run<T, F>(dir: &Path, command: &str, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Transaction) -> io::Result<T>,
+    {
+        let mut transaction = Transaction::begin(dir, command)?;
+        match f(&mut transaction) {
+            Ok(value) => {
+                transaction.commit()?;
+                Ok(value)
+            }
+            Err(e) => {
+                let changed = !transaction.journal.entries.is_empty();
+                match transaction.rollback() {
+                    Ok(()) if changed => eprintln!(
+                        "All changes made by 'known {}' have been rolled back.",
+                        command
+                    ),
+                    Ok(()) => {}
+                    Err(rollback_error) => eprintln!(
+                        "Warning: Failed to roll back all changes: {}. Run 'known undo' to retry.",
+                        rollback_error
+                    ),
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the project directory of the transaction.
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    /// Records the current state of a path so that it can be restored.
+    ///
+    /// Only the first snapshot of a path is kept, so a path can be announced
+    /// before every change without losing its original state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path cannot be inspected or copied, or if the
+    /// journal cannot be written.
+    pub fn snapshot(&mut self, path: &Path) -> io::Result<()> {
+        let relative = self.relative(path);
+        if !self.recorded.insert(relative.clone()) {
+            return Ok(());
+        }
+
+        let entry = match fs::symlink_metadata(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                JournalEntry::Created { path: relative }
+            }
+            Err(e) => return Err(e),
+            Ok(metadata) if metadata.file_type().is_symlink() => JournalEntry::Symlink {
+                path: relative,
+                target: fs::read_link(path)?,
+            },
+            Ok(metadata) if metadata.is_dir() => JournalEntry::Directory { path: relative },
+            Ok(_) => {
+                let copy = self.journal.entries.len().to_string();
+                fs::copy(path, self.journal_dir.join(SAVED_FILES_DIR).join(&copy))?;
+                JournalEntry::File {
+                    path: relative,
+                    copy,
+                }
+            }
+        };
+        self.record(entry)
+    }
+
+    /// Creates a directory (and its parents) if it doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written or the directory cannot
+    /// be created.
+    pub fn create_dir(&mut self, path: &Path) -> io::Result<()> {
+        if path.exists() {
+            return Ok(());
+        }
+        self.snapshot(path)?;
+        fs::create_dir_all(path)
+    }
+
+    /// Writes a file, replacing its previous content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the previous content cannot be recorded or the file
+    /// cannot be written.
+    pub fn write<C: AsRef<[u8]>>(&mut self, path: &Path, contents: C) -> io::Result<()> {
+        self.snapshot(path)?;
+        fs::write(path, contents)
+    }
+
+    /// Removes a file or symlink.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be recorded or removed.
+    pub fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        self.snapshot(path)?;
+        fs::remove_file(path)
+    }
+
+    /// Moves a file or directory, falling back to copying across file systems.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written or the move fails.
+    pub fn move_path(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        let entry = JournalEntry::Moved {
+            from: self.relative(from),
+            to: self.relative(to),
+        };
+        self.recorded.insert(self.relative(from));
+        self.recorded.insert(self.relative(to));
+        self.record(entry)?;
+        move_path(from, to)
+    }
+
+    /// Adds the project directory to the watched directories.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the directory wasn't watched before.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the global configuration or the journal cannot be written.
+    pub fn register_directory(&mut self) -> io::Result<bool> {
+        let added = add_directory_to_config(&self.project_dir)?;
+        if added {
+            self.record(JournalEntry::Registered)?;
+        }
+        Ok(added)
+    }
+
+    /// Completes the transaction, keeping its journal for `known undo`.
+    ///
+    /// The state every changed path was left in is fingerprinted, so that edits
+    /// made after the run can be detected. A run that didn't change anything
+    /// leaves the journal of the previous run in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changed paths cannot be fingerprinted or the
+    /// journal cannot be written or moved.
+    pub fn commit(mut self) -> io::Result<()> {
+        if self.journal.entries.is_empty() {
+            fs::remove_dir_all(&self.journal_dir)?;
+            remove_state_dir_if_empty(&self.project_dir);
+            return Ok(());
+        }
+
+        for relative in &self.recorded {
+            let state = fingerprint(&self.project_dir.join(relative))?;
+            self.journal.fingerprints.insert(relative.clone(), state);
+        }
+        self.save()?;
+
+        let last_run_dir = self
+            .project_dir
+            .join(KNOWN_STATE_DIR)
+            .join(LAST_RUN_JOURNAL_DIR);
+        if last_run_dir.exists() {
+            fs::remove_dir_all(&last_run_dir)?;
+        }
+        fs::rename(&self.journal_dir, last_run_dir)
+    }
+
+    /// Reverts every change made in the transaction and discards its journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a change cannot be reverted. The journal is kept in
+    /// that case so that `known undo` can retry.
+    pub fn rollback(self) -> io::Result<()> {
+        let (_, skipped) = revert(&self.project_dir, &self.journal_dir, &self.journal)?;
+        for reason in skipped {
+            eprintln!("Warning: {}", reason);
+        }
+        fs::remove_dir_all(&self.journal_dir)?;
+        remove_state_dir_if_empty(&self.project_dir);
+        Ok(())
+    }
+
+    /// Appends an entry to the journal and writes it to disk before the change is made.
+    fn record(&mut self, entry: JournalEntry) -> io::Result<()> {
+        self.journal.entries.push(entry);
+        self.save()
+    }
+
+    /// Writes the journal file.
+    fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.journal).map_err(io::Error::other)?;
+        fs::write(self.journal_dir.join(JOURNAL_FILENAME), content)
+    }
+
+    /// Returns a path relative to the project directory, if it is inside it.
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.project_dir)
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+/// Reverts the last `known init`, `known symlink` or `known profile` run in a project.
+///
+/// An interrupted run is reverted if there is one; otherwise the last completed
+/// run is. Files are restored to the content they had before the run. Files that
+/// were edited after a completed run are backed up to `.known/backups/` before
+/// they are replaced or removed, and reported.
+///
+/// # Arguments
+///
+/// * `dir` - Path to the project directory
+///
+/// # Returns
+///
+/// Returns a description of the reverted run.
+///
+/// # Errors
+///
+/// Returns a `NotFound` error if there is nothing to undo, or an error if a
+/// change cannot be reverted.
+pub fn undo_last_run(dir: &Path) -> io::Result<UndoReport> {
+    let state_dir = dir.join(KNOWN_STATE_DIR);
+    let pending_dir = state_dir.join(PENDING_JOURNAL_DIR);
+    let (journal_dir, interrupted) = if pending_dir.exists() {
+        (pending_dir, true)
+    } else {
+        (state_dir.join(LAST_RUN_JOURNAL_DIR), false)
+    };
+
+    if !journal_dir.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Nothing to undo: no 'known init', 'known symlink' or 'known profile' run was recorded for this directory",
+        ));
+    }
+
+    let journal = read_journal(&journal_dir)?;
+    let (preserved, skipped) = revert(dir, &journal_dir, &journal)?;
+    fs::remove_dir_all(&journal_dir)?;
+    remove_state_dir_if_empty(dir);
+
+    Ok(UndoReport {
+        command: journal.command,
+        changes: journal.entries.len() - skipped.len(),
+        interrupted,
+        preserved,
+        skipped,
+    })
+}
+
+/// Moves a file or directory.
+///
+/// `fs::rename` cannot move across file systems (`EXDEV`), for example when a
+/// rules directory is a mount point. In that case the source is copied to the
+/// destination, symlinks included, and removed afterwards.
+///
+/// # Errors
+///
+/// Returns an error if the move, or the copy and removal, fails. A partial copy
+/// is cleaned up before returning.
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => copy_then_remove(from, to),
+        result => result,
+    }
+}
+
+/// Moves a file or directory by copying it and removing the source.
+fn copy_then_remove(from: &Path, to: &Path) -> io::Result<()> {
+    if let Err(e) = copy_recursively(from, to) {
+        let _ = remove_recursively(to);
+        return Err(e);
+    }
+    remove_recursively(from)
+}
+
+/// Copies a file, symlink or directory tree.
+fn copy_recursively(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.file_type().is_symlink() {
+        create_platform_symlink(&fs::read_link(from)?, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Removes a file, symlink or directory tree.
+fn remove_recursively(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads the journal file of a journal directory.
+fn read_journal(journal_dir: &Path) -> io::Result<Journal> {
+    let content = fs::read_to_string(journal_dir.join(JOURNAL_FILENAME))?;
+    serde_json::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Reverts the entries of a journal in reverse order.
+///
+/// Every entry is attempted even if an earlier one fails, so that as much as
+/// possible is restored. Files edited since the run completed are backed up
+/// first (see [`preserve_edit`]).
+fn revert(
+    dir: &Path,
+    journal_dir: &Path,
+    journal: &Journal,
+) -> io::Result<(Vec<PreservedEdit>, Vec<String>)> {
+    let mut failures = 0;
+    let mut preserved = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in journal.entries.iter().rev() {
+        let result = preserve_edit(dir, journal, entry).and_then(|edit| {
+            preserved.extend(edit);
+            revert_entry(dir, journal_dir, entry)
+        });
+        match result {
+            Ok(Some(reason)) => skipped.push(reason),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Warning: Failed to revert {:?}: {}", entry, e);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(io::Error::other(format!(
+            "Failed to revert {} change(s)",
+            failures
+        )));
+    }
+    Ok((preserved, skipped))
+}
+
+/// Backs up a file that reverting an entry would replace or remove, if it was
+/// edited after the run completed.
+///
+/// Moved files are moved back with their edits, so they need no backup.
+fn preserve_edit(
+    dir: &Path,
+    journal: &Journal,
+    entry: &JournalEntry,
+) -> io::Result<Option<PreservedEdit>> {
+    let relative = match entry {
+        JournalEntry::Created { path }
+        | JournalEntry::File { path, .. }
+        | JournalEntry::Symlink { path, .. } => path,
+        _ => return Ok(None),
+    };
+    let expected = match journal.fingerprints.get(relative) {
+        Some(expected) => expected,
+        None => return Ok(None),
+    };
+    let path = dir.join(relative);
+    let is_file = fs::symlink_metadata(&path)
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false);
+    if !is_file || fingerprint(&path)? == *expected {
+        return Ok(None);
+    }
+
+    let name = relative.to_string_lossy().replace(['/', '\\'], "-");
+    let backup_path = backup_file(dir, &name, &fs::read(&path)?)?;
+    Ok(Some(PreservedEdit {
+        path: relative.clone(),
+        backup_path,
+    }))
+}
+
+/// Describes the state of a path, so that later changes to it can be detected.
+///
+/// Files are identified by a hash of their content, symlinks by their target.
+fn fingerprint(path: &Path) -> io::Result<String> {
+    match fs::symlink_metadata(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok("missing".to_string()),
+        Err(e) => Err(e),
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            Ok(format!("symlink:{}", fs::read_link(path)?.display()))
+        }
+        Ok(metadata) if metadata.is_dir() => Ok("directory".to_string()),
+        Ok(_) => Ok(format!("file:{:016x}", content_hash(&fs::read(path)?))),
+    }
+}
+
+/// Hashes content with 64-bit FNV-1a, which stays the same across Rust versions.
+fn content_hash(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Excludes the `.known` state directory from Git.
+///
+/// It holds journals with copies of the user's files and backups, which must
+/// never be committed. The pattern matches at any depth, so one section covers
+/// every package of a monorepo.
+///
+/// # Errors
+///
+/// Returns an error if the exclude file cannot be read or written.
+pub(crate) fn exclude_state_dir(dir: &Path) -> io::Result<()> {
+    update_exclude_section(
+        dir,
+        STATE_EXCLUDE_SECTION,
+        &[format!("{}/", KNOWN_STATE_DIR)],
+    )
+}
+
+/// Reverts a single journal entry. Reverting an entry twice has no further effect.
+///
+/// Returns the reason the entry was skipped, if it could not be reverted.
+fn revert_entry(
+    dir: &Path,
+    journal_dir: &Path,
+    entry: &JournalEntry,
+) -> io::Result<Option<String>> {
+    let result = match entry {
+        JournalEntry::Created { path } => {
+            let path = dir.join(path);
+            match fs::symlink_metadata(&path) {
+                Ok(metadata) if metadata.is_dir() => {
+                    if fs::remove_dir(&path).is_err() {
+                        return Ok(Some(format!(
+                            "'{}' was left in place because it is not empty",
+                            path.display()
+                        )));
+                    }
+                    Ok(())
+                }
+                Ok(_) => fs::remove_file(&path),
+                Err(_) => Ok(()),
+            }
+        }
+        JournalEntry::Directory { path } => fs::create_dir_all(dir.join(path)),
+        JournalEntry::File { path, copy } => {
+            let path = dir.join(path);
+            prepare_restore(&path)?;
+            fs::copy(journal_dir.join(SAVED_FILES_DIR).join(copy), &path).map(|_| ())
+        }
+        JournalEntry::Symlink { path, target } => {
+            let path = dir.join(path);
+            prepare_restore(&path)?;
+            create_platform_symlink(target, &path)
+        }
+        JournalEntry::Moved { from, to } => return move_back(&dir.join(from), &dir.join(to)),
+        JournalEntry::Registered => remove_directory_from_config(dir).map(|_| ()),
+    };
+    result.map(|_| None)
+}
+
+/// Moves a file or directory back from `to` to `from`.
+///
+/// A link to the moved file at its old path, such as the rule link that
+/// `known symlink` or the daemon creates in a tool's rules directory, is replaced
+/// by the file. Returns the reason the move was skipped, if anything else is in
+/// the way or the file is gone.
+fn move_back(from: &Path, to: &Path) -> io::Result<Option<String>> {
+    let from_exists = fs::symlink_metadata(from).is_ok();
+    if fs::symlink_metadata(to).is_err() {
+        // Nothing to do for a move that never happened or was already reverted
+        return Ok(if from_exists {
+            None
+        } else {
+            Some(format!("'{}' no longer exists", to.display()))
+        });
+    }
+    if from_exists {
+        let links_to_moved = fs::symlink_metadata(from)?.file_type().is_symlink()
+            && fs::canonicalize(from).ok() == fs::canonicalize(to).ok();
+        if !links_to_moved {
+            return Ok(Some(format!(
+                "'{}' was not moved back to '{}' because something else is there now",
+                to.display(),
+                from.display()
+            )));
+        }
+        fs::remove_file(from)?;
+    }
+    if let Some(parent) = from.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_path(to, from).map(|_| None)
+}
+
+/// Clears the way for restoring a file: creates its parent directory and removes
+/// whatever file or symlink is there now.
+fn prepare_restore(path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("'{}' is now a directory", path.display()),
+        )),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Removes the `.known` state directory if nothing is left in it.
+fn remove_state_dir_if_empty(dir: &Path) {
+    let _ = fs::remove_dir(dir.join(KNOWN_STATE_DIR));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_failed_run_is_rolled_back() {
+        let dir = tempdir().unwrap();
+        let source_dir = dir.path().join("source");
+        fs::create_dir(&source_dir).unwrap();
+        fs::write(source_dir.join("rule.md"), "rule").unwrap();
+        fs::write(dir.path().join("CLAUDE.md"), "notes").unwrap();
+
+        let result: io::Result<()> = Transaction::run(dir.path(), "symlink", |tx| {
+            tx.create_dir(&dir.path().join(".rules"))?;
+            tx.move_path(
+                &source_dir.join("rule.md"),
+                &dir.path().join(".rules/rule.md"),
+            )?;
+            tx.write(&dir.path().join("CLAUDE.md"), "replaced")?;
+            tx.write(&dir.path().join("GEMINI.md"), "new")?;
+            Err(io::Error::other("step failed"))
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "step failed");
+        assert_eq!(
+            fs::read_to_string(source_dir.join("rule.md")).unwrap(),
+            "rule"
+        );
+        assert!(!dir.path().join(".rules").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("CLAUDE.md")).unwrap(),
+            "notes"
+        );
+        assert!(!dir.path().join("GEMINI.md").exists());
+        assert!(!dir.path().join(KNOWN_STATE_DIR).exists());
+    }
+
+    #[test]
+    fn test_undo_last_run() {
+        let dir = tempdir().unwrap();
+        let alias_path = dir.path().join("CLAUDE.md");
+        fs::write(dir.path().join("AGENTS.md"), "agents").unwrap();
+        fs::write(&alias_path, "notes").unwrap();
+
+        Transaction::run(dir.path(), "symlink", |tx| {
+            tx.remove_file(&alias_path)?;
+            create_platform_symlink(Path::new("AGENTS.md"), &alias_path)
+        })
+        .unwrap();
+        assert!(fs::symlink_metadata(&alias_path)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+
+        // A run without changes keeps the previous run undoable
+        Transaction::run(dir.path(), "symlink", |_| Ok(())).unwrap();
+
+        let report = undo_last_run(dir.path()).unwrap();
+        assert_eq!(report.command, "symlink");
+        assert_eq!(report.changes, 1);
+        assert!(!report.interrupted);
+        assert_eq!(fs::read_to_string(&alias_path).unwrap(), "notes");
+
+        let error = undo_last_run(dir.path()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_undo_backs_up_files_edited_after_the_run() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        let agents_path = dir.path().join("AGENTS.md");
+        let notes_path = dir.path().join("NOTES.md");
+        fs::write(&notes_path, "notes").unwrap();
+
+        Transaction::run(dir.path(), "init", |tx| {
+            tx.write(&agents_path, "agents")?;
+            tx.write(&notes_path, "generated")
+        })
+        .unwrap();
+        let exclude = fs::read_to_string(dir.path().join(".git/info/exclude")).unwrap();
+        assert!(exclude.lines().any(|line| line == ".known/"));
+
+        fs::write(&agents_path, "agents\n\nMy own notes").unwrap();
+
+        let report = undo_last_run(dir.path()).unwrap();
+        assert!(!agents_path.exists());
+        assert_eq!(fs::read_to_string(&notes_path).unwrap(), "notes");
+        assert_eq!(report.preserved.len(), 1);
+        assert_eq!(report.preserved[0].path, Path::new("AGENTS.md"));
+        assert_eq!(
+            fs::read_to_string(&report.preserved[0].backup_path).unwrap(),
+            "agents\n\nMy own notes"
+        );
+    }
+
+    #[test]
+    fn test_undo_moves_rules_back_over_their_links() {
+        let dir = tempdir().unwrap();
+        let cursor_rules = dir.path().join(".cursor/rules");
+        let rules = dir.path().join(".rules");
+        fs::create_dir_all(&cursor_rules).unwrap();
+        fs::write(cursor_rules.join("ts.mdc"), "TypeScript").unwrap();
+        fs::write(cursor_rules.join("go.mdc"), "Go").unwrap();
+
+        Transaction::run(dir.path(), "symlink", |tx| {
+            tx.create_dir(&rules)?;
+            tx.move_path(&cursor_rules.join("ts.mdc"), &rules.join("ts.mdc"))?;
+            tx.move_path(&cursor_rules.join("go.mdc"), &rules.join("go.mdc"))
+        })
+        .unwrap();
+        // The daemon links the moved rules back into the target directory,
+        // and the user puts a file of their own where the other one was
+        create_platform_symlink(
+            Path::new("../../.rules/ts.mdc"),
+            &cursor_rules.join("ts.mdc"),
+        )
+        .unwrap();
+        fs::write(cursor_rules.join("go.mdc"), "Mine").unwrap();
+
+        let report = undo_last_run(dir.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(cursor_rules.join("ts.mdc")).unwrap(),
+            "TypeScript"
+        );
+        assert!(fs::symlink_metadata(cursor_rules.join("ts.mdc"))
+            .unwrap()
+            .is_file());
+        assert_eq!(
+            fs::read_to_string(cursor_rules.join("go.mdc")).unwrap(),
+            "Mine"
+        );
+        assert_eq!(report.skipped.len(), 2);
+        assert!(report.skipped[0].contains("go.mdc"));
+        assert!(report.skipped[1].contains("not empty"));
+        assert_eq!(report.changes, 1);
+        // .rules still holds the rule that could not be moved back
+        assert_eq!(fs::read_to_string(rules.join("go.mdc")).unwrap(), "Go");
+    }
+
+    #[test]
+    fn test_interrupted_run_blocks_new_runs_until_undone() {
+        let dir = tempdir().unwrap();
+        let agents_path = dir.path().join("AGENTS.md");
+
+        // Simulate a crash: the transaction is dropped without commit or rollback
+        let mut tx = Transaction::begin(dir.path(), "init").unwrap();
+        tx.write(&agents_path, "agents").unwrap();
+        drop(tx);
+
+        let error = Transaction::begin(dir.path(), "symlink").unwrap_err();
+        assert!(error.to_string().contains("known undo"));
+
+        let report = undo_last_run(dir.path()).unwrap();
+        assert!(report.interrupted);
+        assert_eq!(report.command, "init");
+        assert!(!agents_path.exists());
+        assert!(Transaction::begin(dir.path(), "symlink").is_ok());
+    }
+
+    #[test]
+    fn test_copy_then_remove_moves_directory_trees() {
+        let dir = tempdir().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("nested")).unwrap();
+        fs::write(from.join("nested/rule.md"), "rule").unwrap();
+        create_platform_symlink(Path::new("nested/rule.md"), &from.join("link.md")).unwrap();
+
+        let to = dir.path().join("to");
+        copy_then_remove(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(
+            fs::read_to_string(to.join("nested/rule.md")).unwrap(),
+            "rule"
+        );
+        assert_eq!(
+            fs::read_link(to.join("link.md")).unwrap(),
+            Path::new("nested/rule.md")
+        );
+    }
+}
@@ -177,6 +177,705 @@ fn test_symlink_command() {
     assert!(project_dir.join("GEMINI.md").exists());
 }
 
+#[test]
+fn test_symlink_command_with_gemini_settings_strategy() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    std::fs::write(project_dir.join("AGENTS.md"), "test content").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--gemini", "settings"]);
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        ".gemini/settings.json uses AGENTS.md",
+    ));
+
+    assert!(project_dir.join("CLAUDE.md").exists());
+    assert!(!project_dir.join("GEMINI.md").exists());
+    let settings = std::fs::read_to_string(project_dir.join(".gemini/settings.json")).unwrap();
+    assert!(settings.contains("\"contextFileName\": \"AGENTS.md\""));
+    let project_config = std::fs::read_to_string(project_dir.join(".known.json")).unwrap();
+    assert!(project_config.contains("\"gemini\": \"settings\""));
+
+    // The strategy is remembered, and an invalid one is rejected
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("symlink");
+    cmd.assert().success();
+    assert!(!project_dir.join("GEMINI.md").exists());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--gemini", "hardlink"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown Gemini strategy"));
+}
+
+#[test]
+fn test_symlink_command_with_claude_import_strategy_and_status() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir(&project_dir).unwrap();
+    std::fs::write(project_dir.join("AGENTS.md"), "test content").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--claude", "import"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("CLAUDE.md imports AGENTS.md"));
+
+    let claude_path = project_dir.join("CLAUDE.md");
+    assert!(!std::fs::symlink_metadata(&claude_path)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(std::fs::read_to_string(&claude_path)
+        .unwrap()
+        .contains("@AGENTS.md"));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("status");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "[ok] CLAUDE.md (import): imports AGENTS.md",
+    ));
+
+    // Breaking the pointer is reported
+    std::fs::write(&claude_path, "# Claude notes only\n").unwrap();
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("status");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("[!!] CLAUDE.md (import)"))
+        .stderr(predicate::str::contains("1 problem(s) found"));
+}
+
+#[test]
+fn test_symlink_command_conflict_policy_and_dry_run() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let rules_dir = project_dir.join(".rules");
+    let cursor_dir = project_dir.join(".cursor/rules");
+    std::fs::create_dir_all(&rules_dir).unwrap();
+    std::fs::create_dir_all(&cursor_dir).unwrap();
+    std::fs::write(project_dir.join("AGENTS.md"), "test content").unwrap();
+    std::fs::write(rules_dir.join("style.md"), "spaces").unwrap();
+    std::fs::write(cursor_dir.join("style.md"), "tabs").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--on-conflict", "keep-both", "--dry-run"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "kept both     .cursor/rules/style.md -> .rules/style-cursor.md",
+        ))
+        .stdout(predicate::str::contains("Dry run: no changes were made"));
+    assert!(cursor_dir.join("style.md").exists());
+    assert!(!project_dir.join("CLAUDE.md").exists());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--on-conflict", "fail"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Conflicting rules already exist"));
+    assert!(!project_dir.join("CLAUDE.md").exists());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--on-conflict", "keep-both"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "0 moved, 0 deduplicated, 0 skipped, 1 kept both",
+    ));
+    assert_eq!(
+        std::fs::read_to_string(rules_dir.join("style-cursor.md")).unwrap(),
+        "tabs"
+    );
+}
+
+#[test]
+fn test_undo_command_reverts_last_run() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let cursor_dir = project_dir.join(".cursor/rules");
+    std::fs::create_dir_all(&cursor_dir).unwrap();
+    std::fs::write(project_dir.join("CLAUDE.md"), "claude notes").unwrap();
+    std::fs::write(cursor_dir.join("style.md"), "style").unwrap();
+
+    for args in [["init"], ["symlink"]] {
+        let mut cmd = Command::cargo_bin("known").unwrap();
+        cmd.current_dir(&project_dir)
+            .env("HOME", temp_dir.path())
+            .args(args);
+        cmd.assert().success();
+    }
+    assert!(project_dir.join(".rules/style.md").exists());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("undo");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Reverted the last 'known symlink' run",
+    ));
+    assert_eq!(
+        std::fs::read_to_string(cursor_dir.join("style.md")).unwrap(),
+        "style"
+    );
+    assert!(std::fs::symlink_metadata(project_dir.join("CLAUDE.md")).is_err());
+    assert_eq!(
+        std::fs::read_to_string(project_dir.join("AGENTS.md")).unwrap(),
+        "claude notes"
+    );
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("undo");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Nothing to undo"))
+        .stderr(predicate::str::contains("Custom").not());
+}
+
+#[test]
+fn test_eject_command() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let cursor_dir = project_dir.join(".cursor/rules");
+    std::fs::create_dir_all(&cursor_dir).unwrap();
+    std::fs::write(project_dir.join("AGENTS.md"), "agents").unwrap();
+    std::fs::write(cursor_dir.join("style.md"), "style").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("symlink");
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["eject", "--remove-rules"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Materialized CLAUDE.md"))
+        .stdout(predicate::str::contains("Removed .rules directory"));
+
+    let claude_path = project_dir.join("CLAUDE.md");
+    assert!(!std::fs::symlink_metadata(&claude_path)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(std::fs::read_to_string(&claude_path).unwrap(), "agents");
+    assert!(!project_dir.join(".rules").exists());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", temp_dir.path()).arg("list");
+    cmd.assert().success().stdout(predicate::str::contains(
+        "No directories are currently being watched",
+    ));
+}
+
+#[test]
+fn test_init_command_imports_legacy_rules_files() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join(".cursorrules"), "Use tabs.").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["init", "--legacy-import", "rule"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Imported .cursorrules into .rules/cursorrules.md (replaced with a link)",
+    ));
+
+    assert_eq!(
+        std::fs::read_to_string(project_dir.join(".rules/cursorrules.md")).unwrap(),
+        "Use tabs."
+    );
+    assert_eq!(
+        std::fs::read_link(project_dir.join(".cursorrules")).unwrap(),
+        std::path::Path::new(".rules/cursorrules.md")
+    );
+    assert!(std::fs::read_to_string(project_dir.join(".known.json"))
+        .unwrap()
+        .contains("\"import\": \"rule\""));
+}
+
+#[test]
+fn test_init_command_merges_claude_and_gemini() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(project_dir.join("CLAUDE.md"), "## Build\n\nRun make.\n").unwrap();
+    std::fs::write(project_dir.join("GEMINI.md"), "## Build\n\nRun cargo.\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("init");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Both files have been merged into AGENTS.md",
+        ))
+        .stdout(predicate::str::contains("1 section(s) differ"));
+
+    let agents = std::fs::read_to_string(project_dir.join("AGENTS.md")).unwrap();
+    assert!(agents.contains("<<<<<<< CLAUDE.md\nRun make.\n=======\nRun cargo.\n>>>>>>> GEMINI.md"));
+    assert!(project_dir.join(".known/backups").exists());
+
+    std::fs::remove_file(project_dir.join("AGENTS.md")).unwrap();
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["init", "--strategy", "prefer-gemini"]);
+    cmd.assert().success();
+    assert_eq!(
+        std::fs::read_to_string(project_dir.join("AGENTS.md")).unwrap(),
+        "## Build\n\nRun cargo.\n"
+    );
+}
+
+#[test]
+fn test_init_command_uses_project_templates() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    std::fs::create_dir_all(project_dir.join("src")).unwrap();
+    std::fs::write(
+        project_dir.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .arg("init");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("created from the 'rust' template"));
+    let agents = std::fs::read_to_string(project_dir.join("AGENTS.md")).unwrap();
+    assert!(agents.contains("- Test: `cargo test`"));
+    assert!(agents.contains("- `src/`: Source code"));
+
+    // User templates live next to the global configuration
+    let templates_dir = temp_dir.path().join(".config/known/templates");
+    std::fs::create_dir_all(&templates_dir).unwrap();
+    std::fs::write(
+        templates_dir.join("team.md"),
+        "# {{name}}\n\n{{commands}}\n",
+    )
+    .unwrap();
+    std::fs::remove_file(project_dir.join("AGENTS.md")).unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["init", "--template", "team"]);
+    cmd.assert().success();
+    let agents = std::fs::read_to_string(project_dir.join("AGENTS.md")).unwrap();
+    assert!(agents.starts_with("# demo\n\n## Commands\n"));
+
+    std::fs::remove_file(project_dir.join("AGENTS.md")).unwrap();
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["init", "--template", "java"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown template 'java'"));
+    assert!(!project_dir.join("AGENTS.md").exists());
+}
+
+#[test]
+fn test_symlink_command_recursive() {
+    let temp_dir = tempdir().unwrap();
+    let project_dir = temp_dir.path().join("project");
+    let package_dir = project_dir.join("packages").join("web");
+    std::fs::create_dir_all(&package_dir).unwrap();
+    std::fs::write(project_dir.join("AGENTS.md"), "# Root").unwrap();
+    std::fs::write(package_dir.join("AGENTS.md"), "# Web").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.current_dir(&project_dir)
+        .env("HOME", temp_dir.path())
+        .args(["symlink", "--recursive"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Linked 1 nested package(s)"))
+        .stdout(predicate::str::contains("packages/web"));
+
+    assert_eq!(
+        std::fs::read_to_string(package_dir.join("GEMINI.md")).unwrap(),
+        "# Web"
+    );
+    assert!(package_dir.join(".rules").is_dir());
+    assert!(std::fs::read_to_string(project_dir.join(".known.json"))
+        .unwrap()
+        .contains("\"recursive\": true"));
+}
+
+#[test]
+fn test_global_init_command() {
+    let home = tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        home.path()
+            .join("Library")
+            .join("Application Support")
+            .join("known")
+    } else {
+        home.path().join(".config").join("known")
+    };
+    std::fs::create_dir_all(home.path().join(".codex")).unwrap();
+    std::fs::write(
+        home.path().join(".codex/AGENTS.md"),
+        "Prefer small commits.\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path()).args(["global", "init"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Created global AGENTS.md"))
+        .stdout(predicate::str::contains(".claude/CLAUDE.md (linked)"));
+
+    let agents_path = config_dir.join("AGENTS.md");
+    assert_eq!(
+        std::fs::read_to_string(&agents_path).unwrap(),
+        "Prefer small commits.\n"
+    );
+    for file in [".claude/CLAUDE.md", ".gemini/GEMINI.md", ".codex/AGENTS.md"] {
+        assert_eq!(
+            std::fs::read_link(home.path().join(file)).unwrap(),
+            agents_path
+        );
+    }
+}
+
+#[test]
+fn test_rule_disable_and_enable_commands() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let rules_dir = temp_dir.path().join(".rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(rules_dir.join("style.md"), "Use tabs.").unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["rule", "disable", "style"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Disabled rule style.md"));
+    let project_config = std::fs::read_to_string(temp_dir.path().join(".known.json")).unwrap();
+    assert!(project_config.contains("\"disabled\": [\n    \"style.md\"\n  ]"));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .arg("status");
+    cmd.assert().stdout(predicate::str::contains(
+        "disabled rules: style.md (.known.json)",
+    ));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["rule", "enable", "style.md"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Enabled rule style.md"));
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/style.md")).is_ok());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["rule", "disable", "missing"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No rule named 'missing'"));
+}
+
+#[test]
+fn test_rule_new_list_show_and_mv_commands() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let known = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("known").unwrap();
+        cmd.env("HOME", home.path())
+            .current_dir(temp_dir.path())
+            .args(args);
+        cmd.assert()
+    };
+
+    known(&[
+        "rule",
+        "new",
+        "rust",
+        "--globs",
+        "src/**/*.rs",
+        "--agents",
+        "cursor",
+    ])
+    .success()
+    .stdout(predicate::str::contains("rust.md"));
+    known(&["rule", "new", "tone", "--always"]).success();
+    known(&["rule", "new", "tone"])
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/rust.md")).is_ok());
+
+    known(&["rule", "list"])
+        .success()
+        .stdout(
+            predicate::str::is_match(r"rust\.md\s+globs: src/\*\*/\*\.rs\s+cursor\s+cursor\n")
+                .unwrap(),
+        )
+        .stdout(predicate::str::is_match(r"tone\.md\s+always\s+all\s+cursor, windsurf").unwrap());
+
+    known(&["rule", "show", "rust"])
+        .success()
+        .stdout(predicate::str::contains(
+            "==> cursor (rust.md): .cursor/rules/rust.md links to .rules/rust.md",
+        ))
+        .stdout(predicate::str::contains(
+            "==> windsurf (rust.md): not synced: not listed in agents",
+        ));
+
+    known(&["rule", "mv", "tone", "voice"])
+        .success()
+        .stdout(predicate::str::contains("Renamed rule tone.md to voice.md"));
+    assert!(temp_dir.path().join(".rules/voice.md").exists());
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/tone.md")).is_err());
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".windsurf/rules/voice.md")).is_ok());
+}
+
+#[test]
+fn test_lint_command() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    std::fs::write(
+        temp_dir.path().join("AGENTS.md"),
+        "# Project\n\nSee [docs](docs.md).\n",
+    )
+    .unwrap();
+    let rules_dir = temp_dir.path().join(".rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(
+        rules_dir.join("style.md"),
+        "---\nglobs: \"*.rs\"\n---\nUse tabs.\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .arg("lint");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "AGENTS.md:3: error[broken-link]: 'docs.md' doesn't exist",
+        ))
+        .stdout(predicate::str::contains(
+            ".rules/style.md:2: warning[unmatched-glob]: '*.rs' matches no file",
+        ))
+        .stdout(predicate::str::contains("1 error(s), 1 warning(s)"))
+        .stderr(predicate::str::contains("1 error(s) found"));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["lint", "--json"]);
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let findings: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(findings[0]["check"], "unmatched-glob");
+    assert_eq!(findings[1]["severity"], "error");
+
+    std::fs::write(temp_dir.path().join("docs.md"), "# Docs").unwrap();
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .arg("lint");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("0 error(s), 1 warning(s)"));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["lint", "--strict"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("1 warning(s) found with --strict"));
+
+    std::fs::write(
+        temp_dir.path().join(".known.json"),
+        r#"{"lint": {"disable": ["unmatched-glob"]}}"#,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["lint", "--strict"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found"));
+}
+
+#[test]
+fn test_budget_command() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    std::fs::write(temp_dir.path().join("AGENTS.md"), "# Project\n").unwrap();
+    let rules_dir = temp_dir.path().join(".rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(rules_dir.join("style.md"), "Use tabs.").unwrap();
+    std::fs::write(
+        rules_dir.join("rust.md"),
+        "---\nglobs: \"*.rs\"\n---\nRun clippy.",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join(".known.json"),
+        r#"{"budget": {"agents": {"cursor": 2}}}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .arg("budget");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"rust\.md\s+3\s+globs: \*\.rs").unwrap())
+        .stdout(predicate::str::is_match(r"claude\s+4 always\n").unwrap())
+        .stdout(
+            predicate::str::is_match(
+                r"cursor\s+3 always, up to 3 more for matching files \(budget 2\)",
+            )
+            .unwrap(),
+        )
+        .stderr(predicate::str::contains(
+            "Warning: cursor always loads about 3 tokens, over its budget of 2",
+        ));
+}
+
+#[test]
+fn test_explain_command() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    std::fs::write(temp_dir.path().join("AGENTS.md"), "# Project\n").unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("src/daemon")).unwrap();
+    std::fs::write(temp_dir.path().join("src/daemon/AGENTS.md"), "# Daemon\n").unwrap();
+    let rules_dir = temp_dir.path().join(".rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(rules_dir.join("style.md"), "Use tabs.").unwrap();
+    std::fs::write(
+        rules_dir.join("rust.md"),
+        "---\nglobs: \"src/**/*.rs\"\n---\nRun clippy.",
+    )
+    .unwrap();
+    std::fs::write(
+        rules_dir.join("web.md"),
+        "---\nglobs: \"*.ts\"\n---\nUse strict mode.",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["explain", "src/daemon/mod.rs"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Instructions and rules for src/daemon/mod.rs:",
+        ))
+        .stdout(predicate::str::is_match(r"src/daemon/AGENTS\.md\s+instructions").unwrap())
+        .stdout(predicate::str::is_match(r"\.rules/rust\.md\s+glob src/\*\*/\*\.rs").unwrap())
+        .stdout(predicate::str::is_match(r"\.rules/style\.md\s+always-on").unwrap())
+        .stdout(predicate::str::contains("web.md").not());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["explain", "../elsewhere.rs"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("is outside"));
+}
+
+#[test]
+fn test_profile_commands() {
+    let home = tempdir().unwrap();
+    let temp_dir = tempdir().unwrap();
+    let rules_dir = temp_dir.path().join(".rules");
+    std::fs::create_dir(&rules_dir).unwrap();
+    std::fs::write(rules_dir.join("style.md"), "Use tabs.").unwrap();
+    std::fs::write(
+        rules_dir.join("checklist.md"),
+        "---\ntags: [review]\n---\nCheck the tests.",
+    )
+    .unwrap();
+    std::fs::write(
+        temp_dir.path().join(".known.json"),
+        r#"{"profiles": {"review": {"tags": ["review"]}}}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["profile", "use", "review"]);
+    cmd.assert().success().stdout(predicate::str::contains(
+        "Switched to profile review: 1 rule(s) linked",
+    ));
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/checklist.md")).is_ok());
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/style.md")).is_err());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .arg("status");
+    cmd.assert().stdout(predicate::str::contains(
+        "profile: review (1 of 2 rule(s) active)",
+    ));
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["profile", "clear"]);
+    cmd.assert().success();
+    assert!(std::fs::symlink_metadata(temp_dir.path().join(".cursor/rules/style.md")).is_ok());
+
+    let mut cmd = Command::cargo_bin("known").unwrap();
+    cmd.env("HOME", home.path())
+        .current_dir(temp_dir.path())
+        .args(["profile", "use", "incident"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("defined profiles: review"));
+}
+
 #[test]
 fn test_autostart_commands() {
     if std::env::var("CI").is_ok() {
@@ -219,8 +918,15 @@ fn test_enable_autostart_starts_daemon() {
     std::env::set_var("HOME", temp_dir.path());
 
     // Ensure daemon is not running before test
-    Command::cargo_bin("known").unwrap().arg("stop").status().unwrap();
-    assert!(!known::is_daemon_running().unwrap(), "Daemon should not be running before test");
+    Command::cargo_bin("known")
+        .unwrap()
+        .arg("stop")
+        .status()
+        .unwrap();
+    assert!(
+        !known::is_daemon_running().unwrap(),
+        "Daemon should not be running before test"
+    );
 
     // Run enable-autostart
     let mut enable_cmd = Command::cargo_bin("known").unwrap();
@@ -238,7 +944,10 @@ fn test_enable_autostart_starts_daemon() {
     }
 
     // Verify daemon is running
-    assert!(known::is_daemon_running().unwrap(), "Daemon should be running after enable-autostart");
+    assert!(
+        known::is_daemon_running().unwrap(),
+        "Daemon should be running after enable-autostart"
+    );
 
     // Cleanup: stop daemon and disable autostart
     let mut stop_cmd = Command::cargo_bin("known").unwrap();
@@ -247,8 +956,12 @@ fn test_enable_autostart_starts_daemon() {
         .assert()
         .success()
         .stdout(predicate::str::contains("Daemon stopped successfully"));
-    
-    Command::cargo_bin("known").unwrap().arg("disable-autostart").status().unwrap();
+
+    Command::cargo_bin("known")
+        .unwrap()
+        .arg("disable-autostart")
+        .status()
+        .unwrap();
 }
 
 #[test]
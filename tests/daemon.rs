@@ -106,6 +106,7 @@ fn test_daemon_commands() {
         );
 
     child.kill().unwrap();
+    child.wait().unwrap();
 }
 
 #[test]
@@ -165,6 +166,7 @@ fn test_daemon_full_lifecycle() {
     // If daemon never started, skip this test as it may be a system limitation
     if !daemon_started {
         daemon_process.kill().ok();
+        daemon_process.wait().ok();
         println!("Daemon failed to start in test environment, skipping test");
         return;
     }
@@ -199,7 +201,9 @@ fn test_daemon_full_lifecycle() {
         .success();
 
     daemon_process.kill().ok();
+    daemon_process.wait().ok();
     daemon_process2.kill().ok();
+    daemon_process2.wait().ok();
 }
 
 #[test]
@@ -274,6 +278,7 @@ fn test_daemon_file_watching() {
         .success();
 
     daemon_process.kill().ok();
+    daemon_process.wait().ok();
 }
 
 #[test]
@@ -361,4 +366,5 @@ fn test_daemon_multiple_projects() {
         .success();
 
     daemon_process.kill().ok();
+    daemon_process.wait().ok();
 }